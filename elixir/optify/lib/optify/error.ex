@@ -0,0 +1,13 @@
+defmodule Optify.Error do
+  @moduledoc """
+  Errors from Optify are returned as `{:error, {code, message}}`,
+  where `code` is an atom for the kind of error, such as `:unknown_feature` or `:key_not_found`,
+  and `message` describes the error for people.
+  """
+
+  @doc """
+  Gets the message from the reason in an `{:error, reason}` tuple.
+  """
+  def message({code, message}) when is_atom(code) and is_binary(message), do: message
+  def message(message) when is_binary(message), do: message
+end
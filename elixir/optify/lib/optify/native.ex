@@ -33,6 +33,9 @@ defmodule Optify.Native do
   def provider_get_options(_provider, _key, _feature_names, _preferences),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  def provider_get_options_batch(_provider, _keys, _feature_names, _preferences),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   def provider_get_filtered_feature_names(_provider, _feature_names, _preferences),
     do: :erlang.nif_error(:nif_not_loaded)
 
@@ -71,6 +74,9 @@ defmodule Optify.Native do
   def watcher_get_options(_watcher, _key, _feature_names, _preferences),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  def watcher_get_options_batch(_watcher, _keys, _feature_names, _preferences),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   def watcher_get_filtered_feature_names(_watcher, _feature_names, _preferences),
     do: :erlang.nif_error(:nif_not_loaded)
 
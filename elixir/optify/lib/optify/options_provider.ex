@@ -19,7 +19,7 @@ defmodule Optify.OptionsProvider do
   def build!(directory) do
     case build(directory) do
       {:ok, provider} -> provider
-      {:error, reason} -> raise ArgumentError, reason
+      {:error, reason} -> raise ArgumentError, Optify.Error.message(reason)
     end
   end
 
@@ -35,7 +35,7 @@ defmodule Optify.OptionsProvider do
   def build_with_schema!(directory, schema_path) do
     case build_with_schema(directory, schema_path) do
       {:ok, provider} -> provider
-      {:error, reason} -> raise ArgumentError, reason
+      {:error, reason} -> raise ArgumentError, Optify.Error.message(reason)
     end
   end
 
@@ -50,7 +50,7 @@ defmodule Optify.OptionsProvider do
   def build_from_directories!(directories) do
     case build_from_directories(directories) do
       {:ok, provider} -> provider
-      {:error, reason} -> raise ArgumentError, reason
+      {:error, reason} -> raise ArgumentError, Optify.Error.message(reason)
     end
   end
 
@@ -66,7 +66,7 @@ defmodule Optify.OptionsProvider do
   def build_from_directories_with_schema!(directories, schema_path) do
     case build_from_directories_with_schema(directories, schema_path) do
       {:ok, provider} -> provider
-      {:error, reason} -> raise ArgumentError, reason
+      {:error, reason} -> raise ArgumentError, Optify.Error.message(reason)
     end
   end
 
@@ -91,7 +91,7 @@ defmodule Optify.OptionsProvider do
       when is_binary(feature_name) do
     case Optify.Native.provider_get_canonical_feature_name(ref, feature_name) do
       {:ok, name} -> name
-      {:error, reason} -> raise ArgumentError, reason
+      {:error, reason} -> raise ArgumentError, Optify.Error.message(reason)
       name when is_binary(name) -> name
     end
   end
@@ -125,6 +125,18 @@ defmodule Optify.OptionsProvider do
     get_options(provider, key, feature_names, prefs)
   end
 
+  def get_options_batch(%__MODULE__{ref: ref}, keys, feature_names, %Optify.GetOptionsPreferences{
+        ref: prefs_ref
+      })
+      when is_list(keys) and is_list(feature_names) do
+    Optify.Native.provider_get_options_batch(ref, keys, feature_names, prefs_ref)
+  end
+
+  def get_options_batch(%__MODULE__{} = provider, keys, feature_names) do
+    prefs = Optify.GetOptionsPreferences.new()
+    get_options_batch(provider, keys, feature_names, prefs)
+  end
+
   def get_filtered_feature_names(
         %__MODULE__{ref: ref},
         feature_names,
@@ -20,7 +20,7 @@ defmodule Optify.OptionsProviderBuilder do
   def add_directory!(%__MODULE__{} = builder, directory) do
     case add_directory(builder, directory) do
       {:ok, builder} -> builder
-      {:error, reason} -> raise ArgumentError, reason
+      {:error, reason} -> raise ArgumentError, Optify.Error.message(reason)
     end
   end
 
@@ -35,7 +35,7 @@ defmodule Optify.OptionsProviderBuilder do
   def build!(%__MODULE__{} = builder) do
     case build(builder) do
       {:ok, provider} -> provider
-      {:error, reason} -> raise ArgumentError, reason
+      {:error, reason} -> raise ArgumentError, Optify.Error.message(reason)
     end
   end
 end
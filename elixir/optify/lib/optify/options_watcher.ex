@@ -17,7 +17,7 @@ defmodule Optify.OptionsWatcher do
   def build!(directory) do
     case build(directory) do
       {:ok, watcher} -> watcher
-      {:error, reason} -> raise ArgumentError, reason
+      {:error, reason} -> raise ArgumentError, Optify.Error.message(reason)
     end
   end
 
@@ -33,7 +33,7 @@ defmodule Optify.OptionsWatcher do
   def build_with_schema!(directory, schema_path) do
     case build_with_schema(directory, schema_path) do
       {:ok, watcher} -> watcher
-      {:error, reason} -> raise ArgumentError, reason
+      {:error, reason} -> raise ArgumentError, Optify.Error.message(reason)
     end
   end
 
@@ -48,7 +48,7 @@ defmodule Optify.OptionsWatcher do
   def build_from_directories!(directories) do
     case build_from_directories(directories) do
       {:ok, watcher} -> watcher
-      {:error, reason} -> raise ArgumentError, reason
+      {:error, reason} -> raise ArgumentError, Optify.Error.message(reason)
     end
   end
 
@@ -64,7 +64,7 @@ defmodule Optify.OptionsWatcher do
   def build_from_directories_with_schema!(directories, schema_path) do
     case build_from_directories_with_schema(directories, schema_path) do
       {:ok, watcher} -> watcher
-      {:error, reason} -> raise ArgumentError, reason
+      {:error, reason} -> raise ArgumentError, Optify.Error.message(reason)
     end
   end
 
@@ -89,7 +89,7 @@ defmodule Optify.OptionsWatcher do
       when is_binary(feature_name) do
     case Optify.Native.watcher_get_canonical_feature_name(ref, feature_name) do
       {:ok, name} -> name
-      {:error, reason} -> raise ArgumentError, reason
+      {:error, reason} -> raise ArgumentError, Optify.Error.message(reason)
       name when is_binary(name) -> name
     end
   end
@@ -123,6 +123,18 @@ defmodule Optify.OptionsWatcher do
     get_options(watcher, key, feature_names, prefs)
   end
 
+  def get_options_batch(%__MODULE__{ref: ref}, keys, feature_names, %Optify.GetOptionsPreferences{
+        ref: prefs_ref
+      })
+      when is_list(keys) and is_list(feature_names) do
+    Optify.Native.watcher_get_options_batch(ref, keys, feature_names, prefs_ref)
+  end
+
+  def get_options_batch(%__MODULE__{} = watcher, keys, feature_names) do
+    prefs = Optify.GetOptionsPreferences.new()
+    get_options_batch(watcher, keys, feature_names, prefs)
+  end
+
   def get_filtered_feature_names(
         %__MODULE__{ref: ref},
         feature_names,
@@ -1,4 +1,5 @@
-use rustler::{Env, Term};
+use optify::OptifyError;
+use rustler::{Atom, Encoder, Env, Term};
 
 pub mod preferences;
 pub mod provider;
@@ -15,4 +16,26 @@ pub fn term_to_json_value(term: Term) -> Result<serde_json::Value, rustler::Erro
         .map_err(|e| rustler::Error::Term(Box::new(format!("Failed to decode term: {}", e))))
 }
 
+/// An error returned to Elixir as `{code, message}`, such as `{:unknown_feature, message}`,
+/// so that callers can match on the kind of error.
+struct OptifyErrorReason {
+    code: &'static str,
+    message: String,
+}
+
+impl Encoder for OptifyErrorReason {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let code = Atom::from_str(env, self.code).expect("error codes should be valid atoms");
+        (code, &self.message).encode(env)
+    }
+}
+
+/// Errors are returned to Elixir as `{code, message}` where `code` is an atom for the variant of the error.
+pub fn optify_error_to_term(error: OptifyError) -> rustler::Error {
+    rustler::Error::Term(Box::new(OptifyErrorReason {
+        code: error.code(),
+        message: error.to_string(),
+    }))
+}
+
 rustler::init!("Elixir.Optify.Native");
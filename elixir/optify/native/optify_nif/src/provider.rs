@@ -1,8 +1,8 @@
 use optify::provider::{OptionsProvider, OptionsRegistry};
 use rustler::{Env, NifResult, ResourceArc, Term};
 
-use crate::json_value_to_term;
 use crate::preferences::PreferencesResource;
+use crate::{json_value_to_term, optify_error_to_term};
 
 pub struct ProviderResource(pub OptionsProvider);
 
@@ -15,7 +15,7 @@ impl rustler::Resource for ProviderResource {}
 pub fn provider_build(directory: String) -> NifResult<ResourceArc<ProviderResource>> {
     match OptionsProvider::build(&directory) {
         Ok(provider) => Ok(ResourceArc::new(ProviderResource(provider))),
-        Err(e) => Err(rustler::Error::Term(Box::new(e))),
+        Err(e) => Err(optify_error_to_term(e)),
     }
 }
 
@@ -26,7 +26,7 @@ pub fn provider_build_with_schema(
 ) -> NifResult<ResourceArc<ProviderResource>> {
     match OptionsProvider::build_with_schema(&directory, &schema_path) {
         Ok(provider) => Ok(ResourceArc::new(ProviderResource(provider))),
-        Err(e) => Err(rustler::Error::Term(Box::new(e))),
+        Err(e) => Err(optify_error_to_term(e)),
     }
 }
 
@@ -36,7 +36,7 @@ pub fn provider_build_from_directories(
 ) -> NifResult<ResourceArc<ProviderResource>> {
     match OptionsProvider::build_from_directories(&directories) {
         Ok(provider) => Ok(ResourceArc::new(ProviderResource(provider))),
-        Err(e) => Err(rustler::Error::Term(Box::new(e))),
+        Err(e) => Err(optify_error_to_term(e)),
     }
 }
 
@@ -47,7 +47,7 @@ pub fn provider_build_from_directories_with_schema(
 ) -> NifResult<ResourceArc<ProviderResource>> {
     match OptionsProvider::build_from_directories_with_schema(&directories, &schema_path) {
         Ok(provider) => Ok(ResourceArc::new(ProviderResource(provider))),
-        Err(e) => Err(rustler::Error::Term(Box::new(e))),
+        Err(e) => Err(optify_error_to_term(e)),
     }
 }
 
@@ -76,7 +76,7 @@ pub fn provider_get_canonical_feature_name(
     provider
         .0
         .get_canonical_feature_name(&feature_name)
-        .map_err(|e| rustler::Error::Term(Box::new(e)))
+        .map_err(optify_error_to_term)
 }
 
 #[rustler::nif]
@@ -87,7 +87,7 @@ pub fn provider_get_canonical_feature_names(
     provider
         .0
         .get_canonical_feature_names(&feature_names)
-        .map_err(|e| rustler::Error::Term(Box::new(e)))
+        .map_err(optify_error_to_term)
 }
 
 // ===== Options =====
@@ -108,7 +108,7 @@ pub fn provider_get_all_options<'a>(
         .get_all_options(&feature_names, None, Some(&prefs))
     {
         Ok(options) => Ok(json_value_to_term(env, &options)),
-        Err(e) => Err(rustler::Error::Term(Box::new(e))),
+        Err(e) => Err(optify_error_to_term(e)),
     }
 }
 
@@ -129,7 +129,31 @@ pub fn provider_get_options<'a>(
         .get_options_with_preferences(&key, &feature_names, None, Some(&prefs))
     {
         Ok(options) => Ok(json_value_to_term(env, &options)),
-        Err(e) => Err(rustler::Error::Term(Box::new(e))),
+        Err(e) => Err(optify_error_to_term(e)),
+    }
+}
+
+#[rustler::nif]
+pub fn provider_get_options_batch<'a>(
+    env: Env<'a>,
+    provider: ResourceArc<ProviderResource>,
+    keys: Vec<String>,
+    feature_names: Vec<String>,
+    preferences: ResourceArc<PreferencesResource>,
+) -> NifResult<Term<'a>> {
+    let prefs = preferences
+        .0
+        .lock()
+        .map_err(|e| rustler::Error::Term(Box::new(format!("Lock poisoned: {}", e))))?;
+    match provider
+        .0
+        .get_options_batch(&keys, &feature_names, None, Some(&prefs))
+    {
+        Ok(options) => Ok(json_value_to_term(
+            env,
+            &serde_json::Value::Object(options.into_iter().collect()),
+        )),
+        Err(e) => Err(optify_error_to_term(e)),
     }
 }
 
@@ -146,7 +170,7 @@ pub fn provider_get_filtered_feature_names(
     provider
         .0
         .get_filtered_feature_names(&feature_names, Some(&prefs))
-        .map_err(|e| rustler::Error::Term(Box::new(e)))
+        .map_err(optify_error_to_term)
 }
 
 #[rustler::nif]
@@ -162,7 +186,7 @@ pub fn provider_map_feature_names(
     provider
         .0
         .map_feature_names(&feature_names, Some(&prefs))
-        .map_err(|e| rustler::Error::Term(Box::new(e)))
+        .map_err(optify_error_to_term)
 }
 
 #[rustler::nif]
@@ -2,6 +2,7 @@ use optify::builder::{OptionsProviderBuilder, OptionsRegistryBuilder};
 use rustler::{NifResult, ResourceArc};
 use std::sync::Mutex;
 
+use crate::optify_error_to_term;
 use crate::provider::ProviderResource;
 
 pub struct ProviderBuilderResource(pub Mutex<OptionsProviderBuilder>);
@@ -27,7 +28,7 @@ pub fn provider_builder_add_directory(
         .map_err(|e| rustler::Error::Term(Box::new(format!("Lock poisoned: {}", e))))?;
     guard
         .add_directory(&directory)
-        .map_err(|e| rustler::Error::Term(Box::new(e)))?;
+        .map_err(optify_error_to_term)?;
     drop(guard);
     Ok(builder)
 }
@@ -42,6 +43,6 @@ pub fn provider_builder_build(
         .map_err(|e| rustler::Error::Term(Box::new(format!("Lock poisoned: {}", e))))?;
     match guard.build() {
         Ok(provider) => Ok(ResourceArc::new(ProviderResource(provider))),
-        Err(e) => Err(rustler::Error::Term(Box::new(e))),
+        Err(e) => Err(optify_error_to_term(e)),
     }
 }
@@ -2,8 +2,8 @@ use optify::provider::{OptionsRegistry, OptionsWatcher};
 use rustler::{Env, NifResult, ResourceArc, Term};
 use std::sync::Mutex;
 
-use crate::json_value_to_term;
 use crate::preferences::PreferencesResource;
+use crate::{json_value_to_term, optify_error_to_term};
 
 pub struct WatcherResource(pub Mutex<OptionsWatcher>);
 
@@ -16,7 +16,7 @@ impl rustler::Resource for WatcherResource {}
 pub fn watcher_build(directory: String) -> NifResult<ResourceArc<WatcherResource>> {
     match OptionsWatcher::build(&directory) {
         Ok(watcher) => Ok(ResourceArc::new(WatcherResource(Mutex::new(watcher)))),
-        Err(e) => Err(rustler::Error::Term(Box::new(e))),
+        Err(e) => Err(optify_error_to_term(e)),
     }
 }
 
@@ -27,7 +27,7 @@ pub fn watcher_build_with_schema(
 ) -> NifResult<ResourceArc<WatcherResource>> {
     match OptionsWatcher::build_with_schema(&directory, &schema_path) {
         Ok(watcher) => Ok(ResourceArc::new(WatcherResource(Mutex::new(watcher)))),
-        Err(e) => Err(rustler::Error::Term(Box::new(e))),
+        Err(e) => Err(optify_error_to_term(e)),
     }
 }
 
@@ -37,7 +37,7 @@ pub fn watcher_build_from_directories(
 ) -> NifResult<ResourceArc<WatcherResource>> {
     match OptionsWatcher::build_from_directories(&directories) {
         Ok(watcher) => Ok(ResourceArc::new(WatcherResource(Mutex::new(watcher)))),
-        Err(e) => Err(rustler::Error::Term(Box::new(e))),
+        Err(e) => Err(optify_error_to_term(e)),
     }
 }
 
@@ -48,7 +48,7 @@ pub fn watcher_build_from_directories_with_schema(
 ) -> NifResult<ResourceArc<WatcherResource>> {
     match OptionsWatcher::build_from_directories_with_schema(&directories, &schema_path) {
         Ok(watcher) => Ok(ResourceArc::new(WatcherResource(Mutex::new(watcher)))),
-        Err(e) => Err(rustler::Error::Term(Box::new(e))),
+        Err(e) => Err(optify_error_to_term(e)),
     }
 }
 
@@ -94,7 +94,7 @@ pub fn watcher_get_canonical_feature_name(
         .map_err(|e| rustler::Error::Term(Box::new(format!("Lock poisoned: {}", e))))?;
     guard
         .get_canonical_feature_name(&feature_name)
-        .map_err(|e| rustler::Error::Term(Box::new(e)))
+        .map_err(optify_error_to_term)
 }
 
 #[rustler::nif]
@@ -108,7 +108,7 @@ pub fn watcher_get_canonical_feature_names(
         .map_err(|e| rustler::Error::Term(Box::new(format!("Lock poisoned: {}", e))))?;
     guard
         .get_canonical_feature_names(&feature_names)
-        .map_err(|e| rustler::Error::Term(Box::new(e)))
+        .map_err(optify_error_to_term)
 }
 
 // ===== Options =====
@@ -130,7 +130,7 @@ pub fn watcher_get_all_options<'a>(
         .map_err(|e| rustler::Error::Term(Box::new(format!("Lock poisoned: {}", e))))?;
     match watch_guard.get_all_options(&feature_names, None, Some(&prefs)) {
         Ok(options) => Ok(json_value_to_term(env, &options)),
-        Err(e) => Err(rustler::Error::Term(Box::new(e))),
+        Err(e) => Err(optify_error_to_term(e)),
     }
 }
 
@@ -152,7 +152,32 @@ pub fn watcher_get_options<'a>(
         .map_err(|e| rustler::Error::Term(Box::new(format!("Lock poisoned: {}", e))))?;
     match watch_guard.get_options_with_preferences(&key, &feature_names, None, Some(&prefs)) {
         Ok(options) => Ok(json_value_to_term(env, &options)),
-        Err(e) => Err(rustler::Error::Term(Box::new(e))),
+        Err(e) => Err(optify_error_to_term(e)),
+    }
+}
+
+#[rustler::nif]
+pub fn watcher_get_options_batch<'a>(
+    env: Env<'a>,
+    watcher: ResourceArc<WatcherResource>,
+    keys: Vec<String>,
+    feature_names: Vec<String>,
+    preferences: ResourceArc<PreferencesResource>,
+) -> NifResult<Term<'a>> {
+    let watch_guard = watcher
+        .0
+        .lock()
+        .map_err(|e| rustler::Error::Term(Box::new(format!("Lock poisoned: {}", e))))?;
+    let prefs = preferences
+        .0
+        .lock()
+        .map_err(|e| rustler::Error::Term(Box::new(format!("Lock poisoned: {}", e))))?;
+    match watch_guard.get_options_batch(&keys, &feature_names, None, Some(&prefs)) {
+        Ok(options) => Ok(json_value_to_term(
+            env,
+            &serde_json::Value::Object(options.into_iter().collect()),
+        )),
+        Err(e) => Err(optify_error_to_term(e)),
     }
 }
 
@@ -172,7 +197,7 @@ pub fn watcher_get_filtered_feature_names(
         .map_err(|e| rustler::Error::Term(Box::new(format!("Lock poisoned: {}", e))))?;
     watch_guard
         .get_filtered_feature_names(&feature_names, Some(&prefs))
-        .map_err(|e| rustler::Error::Term(Box::new(e)))
+        .map_err(optify_error_to_term)
 }
 
 #[rustler::nif]
@@ -191,7 +216,7 @@ pub fn watcher_map_feature_names(
         .map_err(|e| rustler::Error::Term(Box::new(format!("Lock poisoned: {}", e))))?;
     watch_guard
         .map_feature_names(&feature_names, Some(&prefs))
-        .map_err(|e| rustler::Error::Term(Box::new(e)))
+        .map_err(optify_error_to_term)
 }
 
 #[rustler::nif]
@@ -2,6 +2,7 @@ use optify::builder::{OptionsRegistryBuilder, OptionsWatcherBuilder};
 use rustler::{NifResult, ResourceArc};
 use std::sync::Mutex;
 
+use crate::optify_error_to_term;
 use crate::watcher::WatcherResource;
 
 pub struct WatcherBuilderResource(pub Mutex<OptionsWatcherBuilder>);
@@ -27,7 +28,7 @@ pub fn watcher_builder_add_directory(
         .map_err(|e| rustler::Error::Term(Box::new(format!("Lock poisoned: {}", e))))?;
     guard
         .add_directory(&directory)
-        .map_err(|e| rustler::Error::Term(Box::new(e)))?;
+        .map_err(optify_error_to_term)?;
     drop(guard);
     Ok(builder)
 }
@@ -42,6 +43,6 @@ pub fn watcher_builder_build(
         .map_err(|e| rustler::Error::Term(Box::new(format!("Lock poisoned: {}", e))))?;
     match guard.build() {
         Ok(watcher) => Ok(ResourceArc::new(WatcherResource(Mutex::new(watcher)))),
-        Err(e) => Err(rustler::Error::Term(Box::new(e))),
+        Err(e) => Err(optify_error_to_term(e)),
     }
 }
@@ -83,7 +83,8 @@ defmodule Optify.OptionsProviderTest do
     assert is_reference(wrapper_provider_ref)
 
     assert {:error,
-            ~s(Error getting options with features ["feature_A"]: configuration property "unknown_key" not found)} =
+            {:key_not_found,
+             ~s(Error getting options with features ["feature_A"]: configuration property "unknown_key" not found)}} =
              Optify.Native.provider_get_options(
                wrapper_provider_ref,
                "unknown_key",
@@ -92,7 +93,8 @@ defmodule Optify.OptionsProviderTest do
              )
 
     assert {:error,
-            ~s(Error getting options with features ["feature_A"]: configuration property "unknown_key" not found)} =
+            {:key_not_found,
+             ~s(Error getting options with features ["feature_A"]: configuration property "unknown_key" not found)}} =
              OptionsProvider.get_options(
                %OptionsProvider{ref: wrapper_provider_ref},
                "unknown_key",
@@ -144,22 +146,32 @@ defmodule Optify.OptionsProviderTest do
              "override from prefs"
   end
 
+  test "get_options_batch returns options for each key" do
+    provider = OptionsProvider.build!(simple_configs())
+
+    options =
+      OptionsProvider.get_options_batch(provider, ["myConfig", "/myConfig/myArray"], ["a"])
+
+    assert options["myConfig"] == OptionsProvider.get_options(provider, "myConfig", ["a"])
+    assert options["/myConfig/myArray"] == ["example item 1"]
+  end
+
   test "unknown feature names and unknown keys yield errors" do
     provider = OptionsProvider.build!(simple_configs())
     unknown_feature = "does not exist"
     unknown_feature_error = ~s(Feature name "#{unknown_feature}" is not a known feature.)
 
-    assert {:error, ^unknown_feature_error} =
+    assert {:error, {:unknown_feature, ^unknown_feature_error}} =
              OptionsProvider.get_canonical_feature_name(provider, unknown_feature)
 
     assert_raise ArgumentError, unknown_feature_error, fn ->
       OptionsProvider.get_canonical_feature_name!(provider, unknown_feature)
     end
 
-    assert {:error, ^unknown_feature_error} =
+    assert {:error, {:unknown_feature, ^unknown_feature_error}} =
              OptionsProvider.get_canonical_feature_names(provider, [unknown_feature])
 
-    assert {:error, ^unknown_feature_error} =
+    assert {:error, {:unknown_feature, ^unknown_feature_error}} =
              OptionsProvider.get_all_options(provider, [unknown_feature])
 
     key = "unknown_key"
@@ -167,10 +179,10 @@ defmodule Optify.OptionsProviderTest do
     expected_error =
       ~s(Error getting options with features ["feature_A"]: configuration property "#{key}" not found)
 
-    assert {:error, ^expected_error} =
+    assert {:error, {:key_not_found, ^expected_error}} =
              OptionsProvider.get_options(provider, key, ["feature_A"])
 
-    assert {:error, ^unknown_feature_error} =
+    assert {:error, {:unknown_feature, ^unknown_feature_error}} =
              OptionsProvider.get_filtered_feature_names(provider, ["feature_A", unknown_feature])
   end
 
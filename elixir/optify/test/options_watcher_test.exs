@@ -79,7 +79,8 @@ defmodule Optify.OptionsWatcherTest do
     assert is_reference(wrapper_watcher_ref)
 
     assert {:error,
-            ~s(Error getting options with features ["feature_A"]: configuration property "unknown_key" not found)} =
+            {:key_not_found,
+             ~s(Error getting options with features ["feature_A"]: configuration property "unknown_key" not found)}} =
              Optify.Native.watcher_get_options(
                wrapper_watcher_ref,
                "unknown_key",
@@ -88,7 +89,8 @@ defmodule Optify.OptionsWatcherTest do
              )
 
     assert {:error,
-            ~s(Error getting options with features ["feature_A"]: configuration property "unknown_key" not found)} =
+            {:key_not_found,
+             ~s(Error getting options with features ["feature_A"]: configuration property "unknown_key" not found)}} =
              OptionsWatcher.get_options(
                %OptionsWatcher{ref: wrapper_watcher_ref},
                "unknown_key",
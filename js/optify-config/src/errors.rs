@@ -0,0 +1,11 @@
+use napi::{Env, Status};
+use optify::OptifyError;
+
+/// Throw the error to JavaScript with `code` set to the kind of `OptifyError`,
+/// such as `"unknown_feature"` or `"key_not_found"`, so that callers do not need to parse the message.
+pub(crate) fn to_napi_error(env: &Env, error: OptifyError) -> napi::Error {
+  if let Err(e) = env.throw_error(&error.to_string(), Some(error.code())) {
+    return e;
+  }
+  napi::Error::from_status(Status::PendingException)
+}
@@ -1,6 +1,7 @@
 #![deny(clippy::all)]
 
 mod builder_options;
+mod errors;
 mod metadata;
 mod preferences;
 mod provider;
@@ -34,6 +34,14 @@ impl JsOptionsMetadata {
     self.inner.as_ref().and_then(|m| m.owners.clone())
   }
 
+  /// The paths of the files for features with the same canonical feature name that this feature overrides,
+  /// in the order that they were added.
+  /// This is set when directories are added with an overlay mode.
+  #[napi]
+  pub fn overridden_paths(&self) -> Option<Vec<String>> {
+    self.inner.as_ref().and_then(|m| m.overridden_paths.clone())
+  }
+
   #[napi]
   pub fn path(&self) -> Option<String> {
     self.inner.as_ref().and_then(|m| m.path.clone())
@@ -1,9 +1,11 @@
 #![deny(clippy::all)]
 
+use napi::Env;
 use optify::builder::{OptionsProviderBuilder, OptionsRegistryBuilder};
 use optify::provider::{OptionsProvider, OptionsRegistry};
 
 use crate::builder_options::JsBuilderOptions;
+use crate::errors::to_napi_error;
 use crate::metadata::{to_js_options_metadata, JsOptionsMetadata};
 use crate::preferences::JsGetOptionsPreferences;
 
@@ -21,6 +23,7 @@ impl JsOptionsProvider {
 
   #[napi]
   pub fn build(
+    env: Env,
     directory: String,
     options: Option<&JsBuilderOptions>,
   ) -> napi::Result<JsOptionsProvider> {
@@ -32,17 +35,20 @@ impl JsOptionsProvider {
       Ok(provider) => Ok(JsOptionsProvider {
         inner: Some(provider),
       }),
-      Err(e) => Err(napi::Error::from_reason(e.to_string())),
+      Err(e) => Err(to_napi_error(&env, e)),
     }
   }
 
   #[napi]
-  pub fn build_from_directories(directories: Vec<String>) -> napi::Result<JsOptionsProvider> {
+  pub fn build_from_directories(
+    env: Env,
+    directories: Vec<String>,
+  ) -> napi::Result<JsOptionsProvider> {
     match OptionsProvider::build_from_directories(&directories) {
       Ok(provider) => Ok(JsOptionsProvider {
         inner: Some(provider),
       }),
-      Err(e) => Err(napi::Error::from_reason(e.to_string())),
+      Err(e) => Err(to_napi_error(&env, e)),
     }
   }
 
@@ -74,6 +80,7 @@ impl JsOptionsProvider {
   #[napi(js_name = "_getAllOptions")]
   pub fn get_all_options(
     &self,
+    env: Env,
     feature_names: Vec<String>,
     preferences: Option<&JsGetOptionsPreferences>,
   ) -> napi::Result<serde_json::Value> {
@@ -83,7 +90,7 @@ impl JsOptionsProvider {
       .as_ref()
       .unwrap()
       .get_all_options(&feature_names, None, preferences)
-      .map_err(|e| napi::Error::from_reason(e.to_string()))
+      .map_err(|e| to_napi_error(&env, e))
   }
 
   /// Gets all options for the specified feature names.
@@ -95,6 +102,7 @@ impl JsOptionsProvider {
   #[napi]
   pub fn get_all_options_json(
     &self,
+    env: Env,
     feature_names: Vec<String>,
     preferences: Option<&JsGetOptionsPreferences>,
   ) -> napi::Result<String> {
@@ -106,7 +114,7 @@ impl JsOptionsProvider {
       .get_all_options(&feature_names, None, preferences)
     {
       Ok(json) => Ok(json.to_string()),
-      Err(e) => Err(napi::Error::from_reason(e.to_string())),
+      Err(e) => Err(to_napi_error(&env, e)),
     }
   }
 
@@ -139,6 +147,7 @@ impl JsOptionsProvider {
   #[napi(js_name = "_getOptions")]
   pub fn get_options(
     &self,
+    env: Env,
     key: String,
     feature_names: Vec<String>,
     preferences: Option<&JsGetOptionsPreferences>,
@@ -149,12 +158,13 @@ impl JsOptionsProvider {
       .as_ref()
       .unwrap()
       .get_options_with_preferences(&key, &feature_names, None, preferences)
-      .map_err(|e| napi::Error::from_reason(e.to_string()))
+      .map_err(|e| to_napi_error(&env, e))
   }
 
   #[napi]
   pub fn get_options_json(
     &self,
+    env: Env,
     key: String,
     feature_names: Vec<String>,
     preferences: Option<&JsGetOptionsPreferences>,
@@ -166,7 +176,27 @@ impl JsOptionsProvider {
       .unwrap()
       .get_options_with_preferences(&key, &feature_names, None, preferences)
       .map(|json| json.to_string())
-      .map_err(|e| napi::Error::from_reason(e.to_string()))
+      .map_err(|e| to_napi_error(&env, e))
+  }
+
+  /// Gets options for many keys with the same feature names and preferences.
+  /// Returns a JSON object mapping each key to its options.
+  #[napi]
+  pub fn get_options_batch_json(
+    &self,
+    env: Env,
+    keys: Vec<String>,
+    feature_names: Vec<String>,
+    preferences: Option<&JsGetOptionsPreferences>,
+  ) -> napi::Result<String> {
+    let preferences = preferences.map(|p| &p.inner);
+    let options = self
+      .inner
+      .as_ref()
+      .unwrap()
+      .get_options_batch(&keys, &feature_names, None, preferences)
+      .map_err(|e| to_napi_error(&env, e))?;
+    serde_json::to_string(&options).map_err(|e| napi::Error::from_reason(e.to_string()))
   }
 
   /// Indicates if the feature has conditions.
@@ -184,6 +214,7 @@ impl JsOptionsProvider {
   #[napi(js_name = "getFilteredFeatures")]
   pub fn get_filtered_features(
     &self,
+    env: Env,
     feature_names: Vec<String>,
     preferences: Option<&JsGetOptionsPreferences>,
   ) -> napi::Result<Vec<String>> {
@@ -193,7 +224,7 @@ impl JsOptionsProvider {
       .as_ref()
       .unwrap()
       .get_filtered_feature_names(&feature_names, preferences)
-      .map_err(|e| napi::Error::from_reason(e.to_string()))
+      .map_err(|e| to_napi_error(&env, e))
   }
 
   /// Filters feature names based on constraints and preferences.
@@ -201,6 +232,7 @@ impl JsOptionsProvider {
   #[napi(js_name = "mapFeatureNames")]
   pub fn map_feature_names(
     &self,
+    env: Env,
     feature_names: Vec<String>,
     preferences: Option<&JsGetOptionsPreferences>,
   ) -> napi::Result<Vec<Option<String>>> {
@@ -210,7 +242,7 @@ impl JsOptionsProvider {
       .as_ref()
       .unwrap()
       .map_feature_names(&feature_names, preferences)
-      .map_err(|e| napi::Error::from_reason(e.to_string()))
+      .map_err(|e| to_napi_error(&env, e))
   }
 }
 
@@ -229,21 +261,18 @@ impl JsOptionsProviderBuilder {
   }
 
   #[napi]
-  pub fn add_directory(&mut self, directory: String) -> napi::Result<()> {
+  pub fn add_directory(&mut self, env: Env, directory: String) -> napi::Result<()> {
     let path = std::path::Path::new(&directory);
     self
       .inner
       .add_directory(path)
-      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+      .map_err(|e| to_napi_error(&env, e))?;
     Ok(())
   }
 
   #[napi]
-  pub fn build(&mut self) -> napi::Result<JsOptionsProvider> {
-    let provider = self
-      .inner
-      .build()
-      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+  pub fn build(&mut self, env: Env) -> napi::Result<JsOptionsProvider> {
+    let provider = self.inner.build().map_err(|e| to_napi_error(&env, e))?;
     Ok(JsOptionsProvider {
       inner: Some(provider),
     })
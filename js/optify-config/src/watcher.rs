@@ -1,10 +1,12 @@
 #![deny(clippy::all)]
 
+use napi::Env;
 use optify::builder::{OptionsRegistryBuilder, OptionsWatcherBuilder};
 use optify::provider::{OptionsRegistry, OptionsWatcher};
 use std::sync::Arc;
 
 use crate::builder_options::JsBuilderOptions;
+use crate::errors::to_napi_error;
 use crate::metadata::{to_js_options_metadata, JsOptionsMetadata};
 use crate::preferences::JsGetOptionsPreferences;
 use crate::watcher_options::JsWatcherOptions;
@@ -56,6 +58,7 @@ impl JsOptionsWatcher {
 
   #[napi]
   pub fn build(
+    env: Env,
     directory: String,
     builder_options: Option<&JsBuilderOptions>,
     watcher_options: Option<&JsWatcherOptions>,
@@ -64,25 +67,26 @@ impl JsOptionsWatcher {
     if let Some(opts) = builder_options {
       builder
         .with_options(opts.inner.clone())
-        .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+        .map_err(|e| to_napi_error(&env, e))?;
     }
     if let Some(opts) = watcher_options {
       builder.with_watcher_options(opts.inner.clone());
     }
     builder
       .add_directory(&directory)
-      .map_err(|e: String| napi::Error::from_reason(e.to_string()))?;
+      .map_err(|e| to_napi_error(&env, e))?;
 
     match builder.build() {
       Ok(provider) => Ok(JsOptionsWatcher {
         inner: Some(provider),
       }),
-      Err(e) => Err(napi::Error::from_reason(e.to_string())),
+      Err(e) => Err(to_napi_error(&env, e)),
     }
   }
 
   #[napi]
   pub fn build_with_options(
+    env: Env,
     directory: String,
     options: &JsWatcherOptions,
   ) -> napi::Result<JsOptionsWatcher> {
@@ -91,12 +95,13 @@ impl JsOptionsWatcher {
       Ok(provider) => Ok(JsOptionsWatcher {
         inner: Some(provider),
       }),
-      Err(e) => Err(napi::Error::from_reason(e.to_string())),
+      Err(e) => Err(to_napi_error(&env, e)),
     }
   }
 
   #[napi]
   pub fn build_from_directories(
+    env: Env,
     directories: Vec<String>,
     builder_options: Option<&JsBuilderOptions>,
     watcher_options: Option<&JsWatcherOptions>,
@@ -105,25 +110,26 @@ impl JsOptionsWatcher {
     if let Some(opts) = builder_options {
       builder
         .with_options(opts.inner.clone())
-        .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+        .map_err(|e| to_napi_error(&env, e))?;
     }
     if let Some(opts) = watcher_options {
       builder.with_watcher_options(opts.inner.clone());
     }
     builder
       .add_directories(&directories)
-      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+      .map_err(|e| to_napi_error(&env, e))?;
 
     match builder.build() {
       Ok(provider) => Ok(JsOptionsWatcher {
         inner: Some(provider),
       }),
-      Err(e) => Err(napi::Error::from_reason(e.to_string())),
+      Err(e) => Err(to_napi_error(&env, e)),
     }
   }
 
   #[napi]
   pub fn build_from_directories_with_options(
+    env: Env,
     directories: Vec<String>,
     options: &JsWatcherOptions,
   ) -> napi::Result<JsOptionsWatcher> {
@@ -131,7 +137,7 @@ impl JsOptionsWatcher {
       Ok(provider) => Ok(JsOptionsWatcher {
         inner: Some(provider),
       }),
-      Err(e) => Err(napi::Error::from_reason(e.to_string())),
+      Err(e) => Err(to_napi_error(&env, e)),
     }
   }
 
@@ -162,6 +168,7 @@ impl JsOptionsWatcher {
   #[napi(js_name = "_getAllOptions")]
   pub fn get_all_options(
     &self,
+    env: Env,
     feature_names: Vec<String>,
     preferences: Option<&JsGetOptionsPreferences>,
   ) -> napi::Result<serde_json::Value> {
@@ -171,7 +178,7 @@ impl JsOptionsWatcher {
       .as_ref()
       .unwrap()
       .get_all_options(&feature_names, None, preferences)
-      .map_err(|e| napi::Error::from_reason(e.to_string()))
+      .map_err(|e| to_napi_error(&env, e))
   }
 
   /// Gets all options for the specified feature names.
@@ -183,6 +190,7 @@ impl JsOptionsWatcher {
   #[napi]
   pub fn get_all_options_json(
     &self,
+    env: Env,
     feature_names: Vec<String>,
     preferences: Option<&JsGetOptionsPreferences>,
   ) -> napi::Result<String> {
@@ -194,7 +202,7 @@ impl JsOptionsWatcher {
       .get_all_options(&feature_names, None, preferences)
     {
       Ok(json) => Ok(json.to_string()),
-      Err(e) => Err(napi::Error::from_reason(e.to_string())),
+      Err(e) => Err(to_napi_error(&env, e)),
     }
   }
 
@@ -227,6 +235,7 @@ impl JsOptionsWatcher {
   #[napi(js_name = "_getOptions")]
   pub fn get_options(
     &self,
+    env: Env,
     key: String,
     feature_names: Vec<String>,
     preferences: Option<&JsGetOptionsPreferences>,
@@ -237,12 +246,13 @@ impl JsOptionsWatcher {
       .as_ref()
       .unwrap()
       .get_options_with_preferences(&key, &feature_names, None, preferences)
-      .map_err(|e| napi::Error::from_reason(e.to_string()))
+      .map_err(|e| to_napi_error(&env, e))
   }
 
   #[napi]
   pub fn get_options_json(
     &self,
+    env: Env,
     key: String,
     feature_names: Vec<String>,
     preferences: Option<&JsGetOptionsPreferences>,
@@ -254,7 +264,27 @@ impl JsOptionsWatcher {
       .unwrap()
       .get_options_with_preferences(&key, &feature_names, None, preferences)
       .map(|json| json.to_string())
-      .map_err(|e| napi::Error::from_reason(e.to_string()))
+      .map_err(|e| to_napi_error(&env, e))
+  }
+
+  /// Gets options for many keys with the same feature names and preferences.
+  /// Returns a JSON object mapping each key to its options.
+  #[napi]
+  pub fn get_options_batch_json(
+    &self,
+    env: Env,
+    keys: Vec<String>,
+    feature_names: Vec<String>,
+    preferences: Option<&JsGetOptionsPreferences>,
+  ) -> napi::Result<String> {
+    let preferences = preferences.map(|p| &p.inner);
+    let options = self
+      .inner
+      .as_ref()
+      .unwrap()
+      .get_options_batch(&keys, &feature_names, None, preferences)
+      .map_err(|e| to_napi_error(&env, e))?;
+    serde_json::to_string(&options).map_err(|e| napi::Error::from_reason(e.to_string()))
   }
 
   /// Indicates if the feature has conditions.
@@ -272,6 +302,7 @@ impl JsOptionsWatcher {
   #[napi(js_name = "getFilteredFeatures")]
   pub fn get_filtered_features(
     &self,
+    env: Env,
     feature_names: Vec<String>,
     preferences: Option<&JsGetOptionsPreferences>,
   ) -> napi::Result<Vec<String>> {
@@ -281,7 +312,7 @@ impl JsOptionsWatcher {
       .as_ref()
       .unwrap()
       .get_filtered_feature_names(&feature_names, preferences)
-      .map_err(|e| napi::Error::from_reason(e.to_string()))
+      .map_err(|e| to_napi_error(&env, e))
   }
 
   /// Returns the time when the provider was finished building.
@@ -304,6 +335,7 @@ impl JsOptionsWatcher {
   #[napi(js_name = "mapFeatureNames")]
   pub fn map_feature_names(
     &self,
+    env: Env,
     feature_names: Vec<String>,
     preferences: Option<&JsGetOptionsPreferences>,
   ) -> napi::Result<Vec<Option<String>>> {
@@ -313,7 +345,7 @@ impl JsOptionsWatcher {
       .as_ref()
       .unwrap()
       .map_feature_names(&feature_names, preferences)
-      .map_err(|e| napi::Error::from_reason(e.to_string()))
+      .map_err(|e| to_napi_error(&env, e))
   }
 }
 
@@ -332,12 +364,12 @@ impl JsOptionsWatcherBuilder {
   }
 
   #[napi]
-  pub fn add_directory(&mut self, directory: String) -> napi::Result<()> {
+  pub fn add_directory(&mut self, env: Env, directory: String) -> napi::Result<()> {
     let path = std::path::Path::new(&directory);
     self
       .inner
       .add_directory(path)
-      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+      .map_err(|e| to_napi_error(&env, e))?;
     Ok(())
   }
 
@@ -348,11 +380,8 @@ impl JsOptionsWatcherBuilder {
   }
 
   #[napi]
-  pub fn build(&mut self) -> napi::Result<JsOptionsWatcher> {
-    let watcher = self
-      .inner
-      .build()
-      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+  pub fn build(&mut self, env: Env) -> napi::Result<JsOptionsWatcher> {
+    let watcher = self.inner.build().map_err(|e| to_napi_error(&env, e))?;
     Ok(JsOptionsWatcher {
       inner: Some(watcher),
     })
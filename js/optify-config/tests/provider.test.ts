@@ -52,13 +52,37 @@ describe("Provider", () => {
 			expect(options).toEqual(expectedOptions);
 		});
 
+		test(`${name} get_options_batch_json`, () => {
+			const options = JSON.parse(provider.getOptionsBatchJson(["myConfig", "/myConfig/rootString"], ["A", "B"]));
+			const expectedOptions = JSON.parse(fs.readFileSync(path.join(expectationsPath, "aliases.json"), "utf8"))["options"];
+			expect(options).toEqual({
+				myConfig: expectedOptions.myConfig,
+				"/myConfig/rootString": expectedOptions.myConfig.rootString,
+			});
+		});
+
 		test(`${name} invalid file`, () => {
 			const configDir = path.relative(__dirname, path.join(__dirname, "../../rust/optify/tests/invalid_file"));
 			const relativePath = path.join(configDir, "invalid.yaml");
 			const absolutePath = path.resolve(relativePath);
+			expect.assertions(2);
 			expect(() => OptionsProvider.build(configDir)).toThrow(
 				`Error loading file '${absolutePath}': simple key expected at byte 31 line 4 column 1 in ${relativePath}`,
 			);
+			try {
+				OptionsProvider.build(configDir);
+			} catch (error: any) {
+				expect(error.code).toBe("parse");
+			}
+		});
+
+		test(`${name} unknown feature error code`, () => {
+			expect.assertions(1);
+			try {
+				provider.getOptionsJson("myConfig", ["unknown_feature"]);
+			} catch (error: any) {
+				expect(error.code).toBe("unknown_feature");
+			}
 		});
 
 		test(`${name} features`, () => {
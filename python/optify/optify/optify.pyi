@@ -5,6 +5,11 @@ from abc import ABC
 from typing import List, Optional, Self
 
 
+class OptifyError(RuntimeError):
+    """An error from Optify. `code` identifies the kind of error, such as `"unknown_feature"`."""
+    code: str
+
+
 class GetOptionsPreferences:
     def are_configurable_strings_enabled(self) -> bool: ...
     def enable_configurable_strings(self) -> None: ...
@@ -30,6 +35,9 @@ class OptionsProviderBase(ABC):
 
     def get_filtered_features(self, feature_names: List[str], preferences: Optional[GetOptionsPreferences] = None) -> List[str]: ...
 
+    def get_options_batch_json(
+        self, keys: List[str], feature_names: List[str], preferences: Optional[GetOptionsPreferences] = None) -> str: ...
+
     def get_options_json(self, key: str, feature_names: List[str]) -> str: ...
 
     def get_options_json_with_preferences(
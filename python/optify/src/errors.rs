@@ -0,0 +1,23 @@
+use pyo3::create_exception;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+
+create_exception!(
+    optify,
+    OptifyError,
+    PyRuntimeError,
+    "An error from Optify. `code` identifies the kind of error, such as \"unknown_feature\"."
+);
+
+/// Errors raise `OptifyError`, which is a `RuntimeError`, with the error's code in `code`.
+pub(crate) fn to_py_err(error: optify::OptifyError) -> PyErr {
+    let code = error.code();
+    let py_err = OptifyError::new_err(error.to_string());
+    Python::attach(|py| {
+        py_err
+            .value(py)
+            .setattr("code", code)
+            .expect("the code should be set on the exception");
+    });
+    py_err
+}
@@ -1,5 +1,8 @@
 use pyo3::prelude::*;
 
+mod errors;
+use errors::OptifyError;
+
 mod preferences;
 use preferences::PyGetOptionsPreferences;
 
@@ -13,6 +16,9 @@ use watcher::PyOptionsWatcherBuilder;
 
 #[pymodule(name = "optify")]
 mod optify_python {
+    #[pymodule_export]
+    use super::OptifyError;
+
     #[pymodule_export]
     use super::PyGetOptionsPreferences;
 
@@ -1,9 +1,11 @@
+use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
 use pyo3::types::PyType;
 
 use optify::builder::{OptionsRegistryBuilder, OptionsWatcherBuilder};
 use optify::provider::{OptionsRegistry, OptionsWatcher};
 
+use crate::errors::to_py_err;
 use crate::preferences::PyGetOptionsPreferences;
 
 #[pyclass(name = "OptionsWatcherBuilder")]
@@ -18,7 +20,7 @@ impl PyOptionsWatcher {
     fn build(_cls: &Bound<'_, PyType>, directory: &str) -> PyResult<PyOptionsWatcher> {
         match OptionsWatcher::build(directory) {
             Ok(provider) => Ok(PyOptionsWatcher(provider)),
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e)),
+            Err(e) => Err(to_py_err(e)),
         }
     }
 
@@ -29,7 +31,7 @@ impl PyOptionsWatcher {
     ) -> PyResult<PyOptionsWatcher> {
         match OptionsWatcher::build_from_directories(&directories) {
             Ok(watcher) => Ok(PyOptionsWatcher(watcher)),
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e)),
+            Err(e) => Err(to_py_err(e)),
         }
     }
 
@@ -62,7 +64,23 @@ impl PyOptionsWatcher {
         let preferences = preferences.map(|p| &p.0);
         self.0
             .get_filtered_feature_names(&feature_names, preferences)
-            .map_err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>)
+            .map_err(to_py_err)
+    }
+
+    /// Gets options for many keys with the same features as a JSON object mapping each key to its options.
+    #[pyo3(signature = (keys, feature_names, preferences=None))]
+    fn get_options_batch_json(
+        &self,
+        keys: Vec<String>,
+        feature_names: Vec<String>,
+        preferences: Option<&PyGetOptionsPreferences>,
+    ) -> PyResult<String> {
+        let preferences = preferences.map(|p| &p.0);
+        let result = self
+            .0
+            .get_options_batch(&keys, &feature_names, None, preferences)
+            .map_err(to_py_err)?;
+        serde_json::to_string(&result).map_err(|e| PyRuntimeError::new_err(e.to_string()))
     }
 
     fn get_options_json(&self, key: &str, feature_names: Vec<String>) -> PyResult<String> {
@@ -79,7 +97,7 @@ impl PyOptionsWatcher {
         let result = &self
             .0
             .get_options_with_preferences(key, &feature_names, None, preferences)
-            .map_err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>)?;
+            .map_err(to_py_err)?;
         Ok(result.to_string())
     }
 
@@ -91,7 +109,7 @@ impl PyOptionsWatcher {
         let preferences = preferences.map(|p| &p.0);
         self.0
             .map_feature_names(&feature_names, preferences)
-            .map_err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>)
+            .map_err(to_py_err)
     }
 }
 
@@ -106,14 +124,14 @@ impl PyOptionsWatcherBuilder {
         let path = std::path::Path::new(&directory);
         match self.0.add_directory(path) {
             Ok(_) => Ok(Self(self.0.clone())),
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e)),
+            Err(e) => Err(to_py_err(e)),
         }
     }
 
     fn build(&mut self) -> PyResult<PyOptionsWatcher> {
         match self.0.build() {
             Ok(watcher) => Ok(PyOptionsWatcher(watcher)),
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e)),
+            Err(e) => Err(to_py_err(e)),
         }
     }
 }
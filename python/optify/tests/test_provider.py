@@ -1,7 +1,7 @@
 import json
 from pathlib import Path
 
-from optify import GetOptionsPreferences, OptionsProvider, OptionsProviderBuilder, OptionsWatcher
+from optify import GetOptionsPreferences, OptifyError, OptionsProvider, OptionsProviderBuilder, OptionsWatcher
 
 
 test_suites_dir = (Path(__file__) / '../../../../tests/test_suites').resolve()
@@ -34,6 +34,32 @@ def test_features():
             assert str(e) == "Error getting options with features [\"A\"]: configuration property \"key\" not found"
 
 
+def test_errors_have_codes():
+    for provider in PROVIDERS:
+        try:
+            provider.get_options_json('key', ['A'])
+            assert False, "Should have raised an error"
+        except OptifyError as e:
+            # Errors are still `RuntimeError` for callers that catch it.
+            assert isinstance(e, RuntimeError)
+            assert e.code == 'key_not_found'
+
+        try:
+            provider.get_options_json('myConfig', ['unknown'])
+            assert False, "Should have raised an error"
+        except OptifyError as e:
+            assert e.code == 'unknown_feature'
+
+
+def test_get_options_batch_json():
+    for provider in PROVIDERS:
+        keys = ['myConfig', '/myConfig/rootString']
+        batch = json.loads(provider.get_options_batch_json(keys, ['A', 'b']))
+        assert sorted(batch.keys()) == sorted(keys)
+        for key in keys:
+            assert batch[key] == json.loads(provider.get_options_json(key, ['A', 'b']))
+
+
 def test_canonical_feature_name():
     for provider in PROVIDERS:
         assert provider.get_canonical_feature_name('feaTure_A') == 'feature_A'
@@ -9,6 +9,7 @@ use optify::provider::OptionsProvider;
 use optify::provider::OptionsRegistry;
 use optify::provider::OptionsWatcher;
 use optify::schema::metadata::OptionsMetadata;
+use optify::OptifyError;
 use std::cell::RefCell;
 
 use crate::preferences::convert_preferences;
@@ -16,21 +17,18 @@ use crate::preferences::MutGetOptionsPreferences;
 
 mod preferences;
 
-const UNKNOWN_FEATURE_PATTERN: &str = "is not a known feature.";
-
 fn get_unknown_feature_error(ruby: &Ruby) -> Result<ExceptionClass, magnus::Error> {
     let module: RModule = ruby.class_object().const_get("Optify")?;
     module.const_get("UnknownFeatureError")
 }
 
-fn map_feature_error(ruby: &Ruby, error: String) -> magnus::Error {
-    if error.contains(UNKNOWN_FEATURE_PATTERN) {
-        match get_unknown_feature_error(ruby) {
-            Ok(exception_class) => magnus::Error::new(exception_class, error),
-            Err(_) => magnus::Error::new(ruby.exception_runtime_error(), error),
-        }
-    } else {
-        magnus::Error::new(ruby.exception_runtime_error(), error)
+fn map_optify_error(ruby: &Ruby, error: OptifyError) -> magnus::Error {
+    match error {
+        OptifyError::UnknownFeature { .. } => match get_unknown_feature_error(ruby) {
+            Ok(exception_class) => magnus::Error::new(exception_class, error.to_string()),
+            Err(_) => magnus::Error::new(ruby.exception_runtime_error(), error.to_string()),
+        },
+        _ => magnus::Error::new(ruby.exception_runtime_error(), error.to_string()),
     }
 }
 
@@ -79,7 +77,7 @@ impl WrappedOptionsProvider {
     fn build(ruby: &Ruby, directory: String) -> Result<WrappedOptionsProvider, magnus::Error> {
         match OptionsProvider::build(&directory) {
             Ok(provider) => Ok(WrappedOptionsProvider(RefCell::new(provider))),
-            Err(e) => Err(magnus::Error::new(ruby.exception_runtime_error(), e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -90,7 +88,7 @@ impl WrappedOptionsProvider {
     ) -> Result<WrappedOptionsProvider, magnus::Error> {
         match OptionsProvider::build_with_schema(&directory, &schema_path) {
             Ok(provider) => Ok(WrappedOptionsProvider(RefCell::new(provider))),
-            Err(e) => Err(magnus::Error::new(ruby.exception_runtime_error(), e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -100,7 +98,7 @@ impl WrappedOptionsProvider {
     ) -> Result<WrappedOptionsProvider, magnus::Error> {
         match OptionsProvider::build_from_directories(&directories) {
             Ok(provider) => Ok(WrappedOptionsProvider(RefCell::new(provider))),
-            Err(e) => Err(magnus::Error::new(ruby.exception_runtime_error(), e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -111,7 +109,7 @@ impl WrappedOptionsProvider {
     ) -> Result<WrappedOptionsProvider, magnus::Error> {
         match OptionsProvider::build_from_directories_with_schema(&directories, &schema_path) {
             Ok(provider) => Ok(WrappedOptionsProvider(RefCell::new(provider))),
-            Err(e) => Err(magnus::Error::new(ruby.exception_runtime_error(), e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -138,7 +136,7 @@ impl WrappedOptionsProvider {
             .get_all_options(&feature_names, None, Some(preferences))
         {
             Ok(options) => Ok(options.to_string()),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -155,7 +153,7 @@ impl WrappedOptionsProvider {
             .get_all_options(&feature_names, None, Some(preferences))
         {
             Ok(options) => json_value_to_ruby(ruby, &options),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -168,7 +166,7 @@ impl WrappedOptionsProvider {
             .0
             .borrow()
             .get_canonical_feature_name(&feature_name)
-            .map_err(|e| map_feature_error(ruby, e))
+            .map_err(|e| map_optify_error(ruby, e))
     }
 
     fn get_canonical_feature_names(
@@ -180,7 +178,7 @@ impl WrappedOptionsProvider {
             .0
             .borrow()
             .get_canonical_feature_names(&feature_names)
-            .map_err(|e| map_feature_error(ruby, e))
+            .map_err(|e| map_optify_error(ruby, e))
     }
 
     fn get_feature_metadata_json(&self, canonical_feature_name: String) -> Option<String> {
@@ -212,7 +210,7 @@ impl WrappedOptionsProvider {
             .get_filtered_feature_names(&feature_names, Some(preferences))
         {
             Ok(features) => Ok(features),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -229,7 +227,7 @@ impl WrappedOptionsProvider {
             .get_options_with_preferences(&key, &feature_names, None, None)
         {
             Ok(options) => Ok(options.to_string()),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -248,7 +246,41 @@ impl WrappedOptionsProvider {
             Some(preferences),
         ) {
             Ok(options) => Ok(options.to_string()),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
+        }
+    }
+
+    fn get_options_batch_json(
+        ruby: &Ruby,
+        rb_self: &Self,
+        keys: Vec<String>,
+        feature_names: Vec<String>,
+    ) -> Result<String, magnus::Error> {
+        match rb_self
+            .0
+            .borrow()
+            .get_options_batch(&keys, &feature_names, None, None)
+        {
+            Ok(options) => Ok(serde_json::to_string(&options).unwrap()),
+            Err(e) => Err(map_optify_error(ruby, e)),
+        }
+    }
+
+    fn get_options_batch_json_with_preferences(
+        ruby: &Ruby,
+        rb_self: &Self,
+        keys: Vec<String>,
+        feature_names: Vec<String>,
+        preferences: &MutGetOptionsPreferences,
+    ) -> Result<String, magnus::Error> {
+        let preferences = &convert_preferences(preferences);
+        match rb_self
+            .0
+            .borrow()
+            .get_options_batch(&keys, &feature_names, None, Some(preferences))
+        {
+            Ok(options) => Ok(serde_json::to_string(&options).unwrap()),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -264,7 +296,7 @@ impl WrappedOptionsProvider {
             .get_options_with_preferences(&key, &feature_names, None, None)
         {
             Ok(options) => json_value_to_ruby(ruby, &options),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -283,7 +315,7 @@ impl WrappedOptionsProvider {
             Some(preferences),
         ) {
             Ok(options) => json_value_to_ruby(ruby, &options),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -304,7 +336,7 @@ impl WrappedOptionsProvider {
             .map_feature_names(&feature_names, Some(preferences))
         {
             Ok(features) => Ok(features),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 }
@@ -325,14 +357,17 @@ impl WrappedOptionsProviderBuilder {
     ) -> Result<WrappedOptionsProviderBuilder, magnus::Error> {
         match rb_self.0.borrow_mut().add_directory(&directory) {
             Ok(builder) => Ok(WrappedOptionsProviderBuilder(RefCell::new(builder.clone()))),
-            Err(e) => Err(magnus::Error::new(ruby.exception_arg_error(), e)),
+            Err(e) => Err(magnus::Error::new(
+                ruby.exception_arg_error(),
+                e.to_string(),
+            )),
         }
     }
 
     fn build(ruby: &Ruby, rb_self: &Self) -> Result<WrappedOptionsProvider, magnus::Error> {
         match rb_self.0.borrow_mut().build() {
             Ok(provider) => Ok(WrappedOptionsProvider(RefCell::new(provider))),
-            Err(e) => Err(magnus::Error::new(ruby.exception_runtime_error(), e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 }
@@ -344,7 +379,7 @@ impl WrappedOptionsWatcher {
     fn build(ruby: &Ruby, directory: String) -> Result<WrappedOptionsWatcher, magnus::Error> {
         match OptionsWatcher::build(&directory) {
             Ok(provider) => Ok(WrappedOptionsWatcher(RefCell::new(provider))),
-            Err(e) => Err(magnus::Error::new(ruby.exception_runtime_error(), e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -355,7 +390,7 @@ impl WrappedOptionsWatcher {
     ) -> Result<WrappedOptionsWatcher, magnus::Error> {
         match OptionsWatcher::build_with_schema(&directory, &schema_path) {
             Ok(provider) => Ok(WrappedOptionsWatcher(RefCell::new(provider))),
-            Err(e) => Err(magnus::Error::new(ruby.exception_runtime_error(), e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -365,7 +400,7 @@ impl WrappedOptionsWatcher {
     ) -> Result<WrappedOptionsWatcher, magnus::Error> {
         match OptionsWatcher::build_from_directories(&directories) {
             Ok(provider) => Ok(WrappedOptionsWatcher(RefCell::new(provider))),
-            Err(e) => Err(magnus::Error::new(ruby.exception_runtime_error(), e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -376,7 +411,7 @@ impl WrappedOptionsWatcher {
     ) -> Result<WrappedOptionsWatcher, magnus::Error> {
         match OptionsWatcher::build_from_directories_with_schema(&directories, &schema_path) {
             Ok(provider) => Ok(WrappedOptionsWatcher(RefCell::new(provider))),
-            Err(e) => Err(magnus::Error::new(ruby.exception_runtime_error(), e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -401,7 +436,7 @@ impl WrappedOptionsWatcher {
             .get_all_options(&feature_names, None, Some(preferences))
         {
             Ok(options) => Ok(options.to_string()),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -418,7 +453,7 @@ impl WrappedOptionsWatcher {
             .get_all_options(&feature_names, None, Some(preferences))
         {
             Ok(options) => json_value_to_ruby(ruby, &options),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -431,7 +466,7 @@ impl WrappedOptionsWatcher {
             .0
             .borrow()
             .get_canonical_feature_name(&feature_name)
-            .map_err(|e| map_feature_error(ruby, e))
+            .map_err(|e| map_optify_error(ruby, e))
     }
 
     fn get_canonical_feature_names(
@@ -443,7 +478,7 @@ impl WrappedOptionsWatcher {
             .0
             .borrow()
             .get_canonical_feature_names(&feature_names)
-            .map_err(|e| map_feature_error(ruby, e))
+            .map_err(|e| map_optify_error(ruby, e))
     }
 
     fn get_feature_metadata_json(&self, canonical_feature_name: String) -> Option<String> {
@@ -474,7 +509,7 @@ impl WrappedOptionsWatcher {
             .get_filtered_feature_names(&feature_names, Some(preferences))
         {
             Ok(features) => Ok(features),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -490,7 +525,7 @@ impl WrappedOptionsWatcher {
             .get_options_with_preferences(&key, &feature_names, None, None)
         {
             Ok(options) => Ok(options.to_string()),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -509,7 +544,41 @@ impl WrappedOptionsWatcher {
             Some(preferences),
         ) {
             Ok(options) => Ok(options.to_string()),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
+        }
+    }
+
+    fn get_options_batch_json(
+        ruby: &Ruby,
+        rb_self: &Self,
+        keys: Vec<String>,
+        feature_names: Vec<String>,
+    ) -> Result<String, magnus::Error> {
+        match rb_self
+            .0
+            .borrow()
+            .get_options_batch(&keys, &feature_names, None, None)
+        {
+            Ok(options) => Ok(serde_json::to_string(&options).unwrap()),
+            Err(e) => Err(map_optify_error(ruby, e)),
+        }
+    }
+
+    fn get_options_batch_json_with_preferences(
+        ruby: &Ruby,
+        rb_self: &Self,
+        keys: Vec<String>,
+        feature_names: Vec<String>,
+        preferences: &MutGetOptionsPreferences,
+    ) -> Result<String, magnus::Error> {
+        let preferences = &convert_preferences(preferences);
+        match rb_self
+            .0
+            .borrow()
+            .get_options_batch(&keys, &feature_names, None, Some(preferences))
+        {
+            Ok(options) => Ok(serde_json::to_string(&options).unwrap()),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -525,7 +594,7 @@ impl WrappedOptionsWatcher {
             .get_options_with_preferences(&key, &feature_names, None, None)
         {
             Ok(options) => json_value_to_ruby(ruby, &options),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -544,7 +613,7 @@ impl WrappedOptionsWatcher {
             Some(preferences),
         ) {
             Ok(options) => json_value_to_ruby(ruby, &options),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 
@@ -569,7 +638,7 @@ impl WrappedOptionsWatcher {
             .map_feature_names(&feature_names, Some(preferences))
         {
             Ok(features) => Ok(features),
-            Err(e) => Err(map_feature_error(ruby, e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 }
@@ -590,14 +659,17 @@ impl WrappedOptionsWatcherBuilder {
     ) -> Result<WrappedOptionsWatcherBuilder, magnus::Error> {
         match rb_self.0.borrow_mut().add_directory(&directory) {
             Ok(builder) => Ok(WrappedOptionsWatcherBuilder(RefCell::new(builder.clone()))),
-            Err(e) => Err(magnus::Error::new(ruby.exception_arg_error(), e)),
+            Err(e) => Err(magnus::Error::new(
+                ruby.exception_arg_error(),
+                e.to_string(),
+            )),
         }
     }
 
     fn build(ruby: &Ruby, rb_self: &Self) -> Result<WrappedOptionsWatcher, magnus::Error> {
         match rb_self.0.borrow_mut().build() {
             Ok(provider) => Ok(WrappedOptionsWatcher(RefCell::new(provider))),
-            Err(e) => Err(magnus::Error::new(ruby.exception_runtime_error(), e)),
+            Err(e) => Err(map_optify_error(ruby, e)),
         }
     }
 }
@@ -665,6 +737,17 @@ fn init(ruby: &Ruby) -> Result<(), magnus::Error> {
         "get_options_json_with_preferences",
         method!(WrappedOptionsProvider::get_options_json_with_preferences, 3),
     )?;
+    provider_class.define_method(
+        "get_options_batch_json",
+        method!(WrappedOptionsProvider::get_options_batch_json, 2),
+    )?;
+    provider_class.define_method(
+        "get_options_batch_json_with_preferences",
+        method!(
+            WrappedOptionsProvider::get_options_batch_json_with_preferences,
+            3
+        ),
+    )?;
     provider_class.define_method(
         "get_options_hash",
         method!(WrappedOptionsProvider::get_options_hash, 2),
@@ -813,6 +896,17 @@ fn init(ruby: &Ruby) -> Result<(), magnus::Error> {
         "get_options_json_with_preferences",
         method!(WrappedOptionsWatcher::get_options_json_with_preferences, 3),
     )?;
+    watcher_class.define_method(
+        "get_options_batch_json",
+        method!(WrappedOptionsWatcher::get_options_batch_json, 2),
+    )?;
+    watcher_class.define_method(
+        "get_options_batch_json_with_preferences",
+        method!(
+            WrappedOptionsWatcher::get_options_batch_json_with_preferences,
+            3
+        ),
+    )?;
     watcher_class.define_method(
         "get_options_hash",
         method!(WrappedOptionsWatcher::get_options_hash, 2),
@@ -26,6 +26,12 @@ module Optify
     sig { returns(T.nilable(String)) }
     attr_reader :owners
 
+    # The paths of the files for features with the same canonical feature name that this feature overrides,
+    # in the order that they were added.
+    # This is set when directories are added with an overlay mode.
+    sig { returns(T.nilable(T::Array[String])) }
+    attr_reader :overridden_paths
+
     sig { returns(T.nilable(String)) }
     attr_reader :path
   end
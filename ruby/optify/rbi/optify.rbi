@@ -97,6 +97,12 @@ module Optify
     sig { returns(T.nilable(String)) }
     def owners; end
 
+    # The paths of the files for features with the same canonical feature name that this feature overrides,
+    # in the order that they were added.
+    # This is set when directories are added with an overlay mode.
+    sig { returns(T.nilable(T::Array[String])) }
+    def overridden_paths; end
+
     sig { returns(T.nilable(String)) }
     def path; end
   end
@@ -338,6 +344,28 @@ module Optify
     end
     def get_options_json_with_preferences(key, feature_names, preferences); end
 
+    # Fetches options in JSON format for many keys with the same feature names.
+    # The feature names are only resolved and filtered once.
+    #
+    # @param keys [Array<String>] the keys to fetch options for.
+    # @param feature_names [Array<String>] The enabled feature names to use to build the options.
+    # @return [String] a JSON object mapping each key to its options.
+    sig { params(keys: T::Array[String], feature_names: T::Array[String]).returns(String) }
+    def get_options_batch_json(keys, feature_names); end
+
+    # Fetches options in JSON format for many keys with the same feature names.
+    # The feature names are only resolved and filtered once.
+    #
+    # @param keys [Array<String>] the keys to fetch options for.
+    # @param feature_names [Array<String>] The enabled feature names to use to build the options.
+    # @param preferences [GetOptionsPreferences] The preferences to use when getting options.
+    # @return [String] a JSON object mapping each key to its options.
+    sig do
+      params(keys: T::Array[String], feature_names: T::Array[String], preferences: GetOptionsPreferences)
+        .returns(String)
+    end
+    def get_options_batch_json_with_preferences(keys, feature_names, preferences); end
+
     # (Optional) Eagerly initializes the cache.
     # @return `self`.
     sig do
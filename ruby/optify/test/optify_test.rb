@@ -323,4 +323,19 @@ class OptifyTest < Test::Unit::TestCase
       assert_false(provider.conditions?('B'))
     end
   end
+
+  def test_get_options_batch_json
+    PROVIDERS.each do |klass|
+      provider = klass.build('../../tests/test_suites/simple/configs')
+      options = JSON.parse(provider.get_options_batch_json(['myConfig', '/myConfig/myArray'], ['a']))
+      assert_equal(provider.get_options_hash('myConfig', ['a']), options['myConfig'])
+      assert_equal(['example item 1'], options['/myConfig/myArray'])
+
+      preferences = Optify::GetOptionsPreferences.new
+      preferences.overrides_json = '{"myConfig": {"rootString2": "override"}}'
+      options = JSON.parse(provider.get_options_batch_json_with_preferences(['/myConfig/rootString2'], ['a'],
+                                                                           preferences))
+      assert_equal({ '/myConfig/rootString2' => 'override' }, options)
+    end
+  end
 end
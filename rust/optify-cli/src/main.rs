@@ -1,8 +1,22 @@
 use clap::{Parser, Subcommand};
 use optify::builder::BuilderOptions;
 use optify::provider::{GetOptionsPreferences, OptionsProvider, OptionsRegistry};
+use optify::secret::{
+    EnvSecretResolver, FileSecretResolver, SecretResolvers, ENV_SECRET_RESOLVER,
+    FILE_SECRET_RESOLVER,
+};
+use optify::OptifyError;
+use std::fmt;
 use std::path::PathBuf;
 use std::process;
+use std::sync::Arc;
+
+/// Exit code when the configuration could not be loaded or another error occurred.
+const EXIT_CODE_ERROR: i32 = 1;
+
+/// Exit code when a requested feature or key was not found.
+/// `2` is not used because clap uses it for invalid arguments.
+const EXIT_CODE_NOT_FOUND: i32 = 3;
 
 /// Inspect and query Optify configuration options from config directories.
 #[derive(Parser)]
@@ -17,12 +31,33 @@ struct Cli {
     #[arg(long, value_name = "PATH")]
     schema: Option<PathBuf>,
 
+    /// Resolve secrets from environment variables ("env") and files relative to the current directory ("file")
+    /// instead of redacting them when configurable values are enabled.
+    #[arg(long)]
+    show_secrets: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Subcommand)]
 enum Commands {
+    /// Explain which feature supplied each value in the options for a key
+    /// and which values from lower-priority features were replaced.
+    Explain {
+        /// The configuration key to explain (e.g. "myConfig").
+        #[arg(short, long)]
+        key: String,
+
+        /// Feature names to apply, in order from lowest to highest priority.
+        #[arg(short, long, value_name = "FEATURE", num_args = 0..)]
+        features: Vec<String>,
+
+        /// JSON preferences for configuring how options are resolved.
+        #[arg(long = "preferences", visible_alias = "prefs", value_name = "JSON")]
+        preferences: Option<String>,
+    },
+
     /// List all features with their metadata as a JSON object.
     ListFeatures,
 
@@ -53,25 +88,103 @@ enum Commands {
     },
 }
 
-fn parse_preferences(json: Option<&str>) -> Result<Option<GetOptionsPreferences>, String> {
-    json.map(|s| serde_json::from_str(s).map_err(|e| format!("Failed to parse preferences: {e}")))
-        .transpose()
+enum CliError {
+    Optify(OptifyError),
+    Other(String),
+}
+
+impl CliError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            Self::Optify(OptifyError::UnknownFeature { .. } | OptifyError::KeyNotFound { .. }) => {
+                EXIT_CODE_NOT_FOUND
+            }
+            Self::Optify(_) | Self::Other(_) => EXIT_CODE_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Optify(e) => write!(f, "{e}"),
+            Self::Other(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl From<OptifyError> for CliError {
+    fn from(error: OptifyError) -> Self {
+        Self::Optify(error)
+    }
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        Self::Other(message)
+    }
+}
+
+fn parse_preferences(
+    json: Option<&str>,
+    show_secrets: bool,
+) -> Result<Option<GetOptionsPreferences>, String> {
+    let preferences = json
+        .map(|s| {
+            serde_json::from_str::<GetOptionsPreferences>(s)
+                .map_err(|e| format!("Failed to parse preferences: {e}"))
+        })
+        .transpose()?;
+    if show_secrets {
+        return Ok(preferences);
+    }
+    // Secrets are redacted by default so that they are not shown in terminals or logs.
+    let mut preferences = preferences.unwrap_or_default();
+    preferences.redact_secrets = true;
+    Ok(Some(preferences))
 }
 
-fn build_provider(dirs: &[PathBuf], schema: Option<&PathBuf>) -> Result<OptionsProvider, String> {
+fn build_provider(
+    dirs: &[PathBuf],
+    schema: Option<&PathBuf>,
+    show_secrets: bool,
+) -> Result<OptionsProvider, OptifyError> {
+    let mut secret_resolvers = SecretResolvers::new();
+    if show_secrets {
+        secret_resolvers.insert(ENV_SECRET_RESOLVER.to_owned(), Arc::new(EnvSecretResolver));
+        secret_resolvers.insert(
+            FILE_SECRET_RESOLVER.to_owned(),
+            Arc::new(FileSecretResolver::new(".")),
+        );
+    }
     let options = BuilderOptions {
         schema_path: schema.cloned(),
+        secret_resolvers,
         ..BuilderOptions::default()
     };
     OptionsProvider::build_from_directories_with_options(dirs, options)
 }
 
-fn run() -> Result<(), String> {
+fn run() -> Result<(), CliError> {
     let cli = Cli::parse();
 
-    let provider = build_provider(&cli.dirs, cli.schema.as_ref())?;
+    let provider = build_provider(&cli.dirs, cli.schema.as_ref(), cli.show_secrets)?;
 
     match cli.command {
+        Commands::Explain {
+            key,
+            features,
+            preferences,
+        } => {
+            let preferences = parse_preferences(preferences.as_deref(), cli.show_secrets)?;
+            let explanation = provider.explain_options(&key, &features, preferences.as_ref())?;
+            println!(
+                "{}",
+                serde_json::to_string(&explanation)
+                    .map_err(|e| format!("Failed to serialize the explanation: {e}"))?
+            );
+        }
+
         Commands::ListFeatures => {
             let features = provider.get_features_with_metadata();
             let list: Vec<_> = features.into_values().collect();
@@ -86,7 +199,7 @@ fn run() -> Result<(), String> {
             features,
             preferences,
         } => {
-            let preferences = parse_preferences(preferences.as_deref())?;
+            let preferences = parse_preferences(preferences.as_deref(), cli.show_secrets)?;
             let value = provider.get_all_options(&features, None, preferences.as_ref())?;
             println!(
                 "{}",
@@ -100,7 +213,7 @@ fn run() -> Result<(), String> {
             features,
             preferences,
         } => {
-            let preferences = parse_preferences(preferences.as_deref())?;
+            let preferences = parse_preferences(preferences.as_deref(), cli.show_secrets)?;
             let value = provider.get_options_with_preferences(
                 &key,
                 &features,
@@ -121,6 +234,6 @@ fn run() -> Result<(), String> {
 fn main() {
     if let Err(e) = run() {
         eprintln!("Error: {e}");
-        process::exit(1);
+        process::exit(e.exit_code());
     }
 }
@@ -87,6 +87,16 @@ check "get-options with overrides" \
     '{"extra":"from override","key":"from B","key_b":"only in B"}' \
     "$(optify --dir "$COND_CONFIGS" get-options -k config -f B --prefs '{"overrides":{"config":{"extra":"from override"}}}')"
 
+# explain shows which feature supplied a value and the values that were replaced
+check "explain -k /myConfig/rootString2 -f A B" \
+    '[{"pointer":"","shadowed":[{"pointer":"","source":{"feature":"feature_A","imported_by":[]},"value":"gets overridden"}],"source":{"feature":"feature_B/initial","imported_by":[]},"value":"override"}]' \
+    "$(optify --dir "$CONFIGS" explain -k /myConfig/rootString2 -f A B)"
+
+# explain includes values from imported features
+check "explain -k myConfig -f super_with_options" \
+    '[{"feature":"super_with_options","value":"new value"},{"feature":"base2","value":"from base2"},{"feature":"super_with_options","value":"super value"}]' \
+    "$(optify --dir ../../tests/test_suites/inheritance/configs explain -k myConfig -f super_with_options | jq -c '[.[] | {feature: .source.feature, value}]')"
+
 # invalid preferences JSON produces a clear error
 if optify --dir "$CONFIGS" get-options -k myConfig -f A --prefs 'not json' 2>/dev/null; then
     echo "FAIL: invalid preferences JSON should exit non-zero"
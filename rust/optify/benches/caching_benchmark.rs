@@ -7,7 +7,7 @@ fn get_simple_provider() -> OptionsProvider {
 
 fn benchmark_cache_vs_no_cache(c: &mut Criterion) {
     let provider = get_simple_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     let mut group = c.benchmark_group("cache_performance");
     let features_a = ["a"];
@@ -74,7 +74,7 @@ fn benchmark_cache_vs_no_cache(c: &mut Criterion) {
 fn benchmark_cache_with_preferences(c: &mut Criterion) {
     let features_a = ["a"];
     let provider = get_simple_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     let mut group = c.benchmark_group("cache_with_preferences");
 
@@ -117,7 +117,7 @@ fn benchmark_cache_with_preferences(c: &mut Criterion) {
 fn benchmark_cache_multiple_features(c: &mut Criterion) {
     let features_a_b = ["a", "b"];
     let provider = get_simple_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     let mut group = c.benchmark_group("cache_multiple_features");
 
@@ -150,7 +150,7 @@ fn benchmark_cache_multiple_features(c: &mut Criterion) {
 fn benchmark_cache_performance_scaling(c: &mut Criterion) {
     let features_a = ["a"];
     let provider = get_simple_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     let mut group = c.benchmark_group("cache_performance_scaling");
 
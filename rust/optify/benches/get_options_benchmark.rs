@@ -78,7 +78,7 @@ fn benchmark_get_options_with_preferences_breakdown(c: &mut Criterion) {
             BenchmarkId::new("get_options_with_preferences with cache", name),
             &(feature_names, key),
             |b, (features, key)| {
-                let cache_options = CacheOptions {};
+                let cache_options = CacheOptions::default();
                 // Pre-populate cache
                 let _ = provider
                     .get_options_with_preferences(key, features, Some(&cache_options), None)
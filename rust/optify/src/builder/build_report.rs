@@ -0,0 +1,124 @@
+use crate::error::OptifyError;
+
+/// The kind of problem found while building.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiagnosticCategory {
+    /// Builder options could not be loaded from a `.optify/config.json` file,
+    /// the patterns for files to ignore are invalid,
+    /// or an environment variable for overriding options is invalid.
+    BuilderConfig,
+
+    /// A directory could not be added or walked.
+    Directory,
+
+    /// A feature, alias, or file was added more than once.
+    Duplicate,
+
+    /// An import is invalid, such as an import of an unknown feature or a cycle of imports.
+    Import,
+
+    /// A file could not be read.
+    Io,
+
+    /// A path is not allowed, such as a symbolic link or a path that is not valid UTF-8.
+    Path,
+
+    /// A feature file or a Liquid template could not be parsed or a feature file does not have the expected structure.
+    Parse,
+
+    /// An `Optify.Reference` is invalid, such as a reference to a value that does not exist or a cycle of references.
+    Reference,
+
+    /// A feature file does not match the JSON schema or the schema could not be loaded.
+    Schema,
+
+    /// Any other problem.
+    Other,
+}
+
+impl DiagnosticCategory {
+    pub(crate) fn from_error(error: &OptifyError) -> Self {
+        match error {
+            OptifyError::BuilderConfig { .. }
+            | OptifyError::Glob { .. }
+            | OptifyError::InvalidEnvironmentVariable { .. } => Self::BuilderConfig,
+            OptifyError::DuplicateAlias { .. }
+            | OptifyError::DuplicateFeature { .. }
+            | OptifyError::DuplicateFile { .. } => Self::Duplicate,
+            OptifyError::ImportCycle { .. }
+            | OptifyError::ImportHasConditions { .. }
+            | OptifyError::NonCanonicalImport { .. } => Self::Import,
+            OptifyError::InvalidDirectory { .. } => Self::Directory,
+            OptifyError::InvalidPath { .. } => Self::Path,
+            OptifyError::Io { .. } => Self::Io,
+            OptifyError::Deserialize { .. }
+            | OptifyError::LiquidTemplate { .. }
+            | OptifyError::Parse { .. } => Self::Parse,
+            OptifyError::DanglingReference { .. } | OptifyError::ReferenceCycle { .. } => {
+                Self::Reference
+            }
+            OptifyError::Schema { .. } | OptifyError::SchemaValidation { .. } => Self::Schema,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// An error found while building.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BuildError {
+    pub category: DiagnosticCategory,
+
+    pub error: OptifyError,
+
+    /// The path of the file or directory with the problem, if it is known.
+    pub path: Option<String>,
+}
+
+impl BuildError {
+    /// Creates an error for the build.
+    /// When `path` is not given, the path in `error` is used, if it has one.
+    pub(crate) fn new(error: OptifyError, path: Option<String>) -> Self {
+        let path = path.or_else(|| match &error {
+            OptifyError::BuilderConfig { path, .. }
+            | OptifyError::InvalidDirectory { path }
+            | OptifyError::InvalidPath { path, .. }
+            | OptifyError::Io { path, .. } => Some(path.clone()),
+            OptifyError::Deserialize { file, .. }
+            | OptifyError::LiquidTemplate { file, .. }
+            | OptifyError::Parse { file, .. }
+            | OptifyError::SchemaValidation { file, .. } => Some(file.clone()),
+            _ => None,
+        });
+        Self {
+            category: DiagnosticCategory::from_error(&error),
+            error,
+            path,
+        }
+    }
+}
+
+/// A problem found while building that does not stop a provider from being built.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BuildWarning {
+    pub category: DiagnosticCategory,
+
+    pub message: String,
+
+    /// The path of the file or directory with the problem, if it is known.
+    pub path: Option<String>,
+}
+
+/// The problems found while building when `BuilderOptions::collect_diagnostics` is enabled.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BuildReport {
+    /// The errors in the order that they were found.
+    pub errors: Vec<BuildError>,
+
+    pub warnings: Vec<BuildWarning>,
+}
+
+impl BuildReport {
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+}
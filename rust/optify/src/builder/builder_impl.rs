@@ -1,50 +1,91 @@
-use config;
 use jsonschema::{Registry, Validator};
+use liquid_core::{ParseFilter, ParseTag};
 use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use crate::builder::builder_options::{BuilderOptions, BuilderOptionsConfig, TrackReferenceMode};
+use crate::builder::build_report::{BuildError, BuildReport, BuildWarning, DiagnosticCategory};
+use crate::builder::builder_options::{
+    keep_registrations, BuilderOptions, BuilderOptionsConfig, NonUtf8PathPolicy, OverlayMode,
+    SymlinkPolicy, TrackReferenceMode,
+};
+use crate::builder::directory_config::{load_directory_config, read_directory_config};
 use crate::builder::extract_configurable_string_files_from_config::extract_configurable_string_files_from_config;
 use crate::builder::extract_files_from_config::extract_files_from_config;
+use crate::builder::feature_file_parser::{
+    get_feature_file_parsers, FeatureFileParser, FeatureFileParsers,
+};
 use crate::builder::get_canonical_feature_name::get_canonical_feature_name;
 use crate::builder::get_supported_extensions::get_supported_extensions;
 use crate::builder::loading_result::{FeatureLoadingResult, LoadingResult, RawLoadingResult};
+use crate::builder::path_filter::{IgnoreStack, PathFilter, IGNORE_FILE_NAME};
 use crate::builder::OptionsRegistryBuilder;
+use crate::configurable_string::liquid_templates::{compile_liquid_templates, LiquidTemplates};
 use crate::configurable_string::LoadedFiles;
+use crate::configurable_values::configurable_value_type::find_invalid_configurable_values;
 use crate::configurable_values::locator::{find_configurable_values, ConfigurableValuePointers};
+use crate::configurable_values::reference::find_reference_problems;
+use crate::configurable_values::ConfigurableValueType;
+use crate::error::OptifyError;
 use crate::json::merge::{merge_json_with_defaults, FrozenPaths};
-use crate::json::reader::read_json_from_file_as;
+use crate::provider::env_overrides::{read_env_overrides, EnvOverrides};
 use crate::provider::{
-    Aliases, Conditions, Features, OptionsProvider, ReferencedFileToFeatureNames, Sources,
+    Aliases, Conditions, Features, Imports, OptionsProvider, ReferencedFileToFeatureNames, Sources,
 };
 use crate::schema::feature::FeatureConfiguration;
 use crate::schema::metadata::OptionsMetadata;
+use crate::secret::SecretResolver;
 
 type Dependents = HashMap<String, Vec<String>>;
-type Imports = HashMap<String, Vec<String>>;
+
+/// A file to load that was found when walking a directory.
+struct FoundFile {
+    path: PathBuf,
+    /// The path relative to the directory that is being added, with `/` as the separator.
+    relative_path: String,
+    /// The index of the options for the nearest directory with a configuration.
+    options_index: usize,
+}
 
 /// A builder to use in production to create an `OptionsProvider`.
 #[derive(Clone)]
 pub struct OptionsProviderBuilder {
     aliases: Aliases,
     all_configurable_string_pointers: HashSet<String>,
+    /// Pointers to values of types registered with `with_configurable_value_type`.
+    all_configurable_custom_pointers: HashSet<String>,
     all_configurable_list_pointers: HashSet<String>,
+    all_configurable_reference_pointers: HashSet<String>,
+    all_configurable_secret_pointers: HashSet<String>,
+    keyed_configurable_custom_pointers: HashMap<String, HashSet<String>>,
     keyed_configurable_list_pointers: HashMap<String, HashSet<String>>,
+    keyed_configurable_reference_pointers: HashMap<String, HashSet<String>>,
+    keyed_configurable_secret_pointers: HashMap<String, HashSet<String>>,
     keyed_configurable_string_pointers: HashMap<String, HashSet<String>>,
     builder_options: BuilderOptions,
     conditions: Conditions,
     dependents: Dependents,
+    /// The overrides from environment variables that are read when building.
+    env_overrides: Option<EnvOverrides>,
     features: Features,
     imports: Imports,
+    /// The Liquid templates of configurable strings, which are parsed when building.
+    liquid_templates: LiquidTemplates,
     loaded_files: LoadedFiles,
+    /// Canonical feature names of features in namespaces mapped to the prefixes of their namespaces.
+    /// This is used to resolve imports relative to the namespace.
+    namespace_prefixes: HashMap<String, String>,
     /// A map of files to the features that reference them.
     /// The keys are relative file paths and the values are lists of canonical feature names.
     /// This is only populated if the `BuilderOptions` enable file reference tracking.
     referenced_file_to_feature_names: ReferencedFileToFeatureNames,
+    /// The problems found while loading when `BuilderOptions::collect_diagnostics` is enabled.
+    report: BuildReport,
     schema: Option<Arc<Validator>>,
     sources: Sources,
+    /// The options from the files of features with imports before the imports were merged in.
+    sources_without_imports: Sources,
 }
 
 impl Default for OptionsProviderBuilder {
@@ -55,14 +96,16 @@ impl Default for OptionsProviderBuilder {
 
 fn add_alias(
     aliases: &mut Aliases,
-    alias: &String,
-    canonical_feature_name: &String,
-) -> Result<(), String> {
-    let uni_case_alias = unicase::UniCase::new(alias.clone());
-    if let Some(ref res) = aliases.insert(uni_case_alias, canonical_feature_name.clone()) {
-        return Err(format!(
-            "The alias '{alias}' for canonical feature name '{canonical_feature_name}' is already mapped to '{res}'."
-        ));
+    alias: &str,
+    canonical_feature_name: &str,
+) -> Result<(), OptifyError> {
+    let uni_case_alias = unicase::UniCase::new(alias.to_owned());
+    if let Some(res) = aliases.insert(uni_case_alias, canonical_feature_name.to_owned()) {
+        return Err(OptifyError::DuplicateAlias {
+            alias: alias.to_owned(),
+            canonical_feature_name: canonical_feature_name.to_owned(),
+            existing_canonical_feature_name: res,
+        });
     }
     Ok(())
 }
@@ -106,7 +149,7 @@ fn resolve_imports(
     all_imports: &Imports,
     sources: &mut Sources,
     conditions: &Conditions,
-) -> Result<(), String> {
+) -> Result<(), OptifyError> {
     // Build full configuration for the feature so that we don't need to traverse imports for the feature when configurations are requested from the provider.
     let mut merged_config = sources.get(canonical_feature_name).unwrap().clone();
     let mut frozen_paths = FrozenPaths::new();
@@ -114,21 +157,20 @@ fn resolve_imports(
         // Validate imports.
         if features_in_resolution_path.contains(import) {
             // The import is already in the path, so there is a cycle.
-            return Err(format!(
-                    "Error when resolving imports for '{canonical_feature_name}': Cycle detected with import '{import}'. The features in the path (not in order): {features_in_resolution_path:?}"
-                ));
+            let mut path: Vec<String> = features_in_resolution_path.iter().cloned().collect();
+            path.sort_unstable();
+            return Err(OptifyError::ImportCycle {
+                feature: canonical_feature_name.to_owned(),
+                import: import.clone(),
+                path,
+            });
         }
 
         if conditions.contains_key(import) {
-            return Err(format!(
-                "Error when resolving imports for '{canonical_feature_name}': The import '{import}' \
-                 has conditions. Conditions cannot be used in imported features. This helps keep \
-                 retrieving and building configuration options for a list of features fast and more \
-                 predictable because imports do not need to be re-evaluated. Instead, keep each \
-                 feature file as granular and self-contained as possible, then use conditions and \
-                 import the required granular features in a feature file that defines a common \
-                 scenario."
-            ));
+            return Err(OptifyError::ImportHasConditions {
+                feature: canonical_feature_name.to_owned(),
+                import: import.clone(),
+            });
         }
 
         all_dependents
@@ -141,18 +183,15 @@ fn resolve_imports(
         let mut source = match sources.get(import) {
             Some(s) => s,
             // The import is not a canonical feature name.
-            None => match aliases.get(&unicase::UniCase::new(import.clone())) {
-                Some(canonical_name_for_import) => {
-                    return Err(format!(
-                        "Error when resolving imports for '{canonical_feature_name}': The import '{import}' is not a canonical feature name. Use '{canonical_name_for_import}' instead of '{import}' in order to keep dependencies clear and to help with navigating through files."
-                    ))
-                }
-                None => {
-                    return Err(format!(
-                        "Error when resolving imports for '{canonical_feature_name}': The import '{import}' is not a canonical feature name and not a recognized alias. Use a canonical feature name in order to keep dependencies clear and to help with navigating through files."
-                    ))
-                }
-            },
+            None => {
+                return Err(OptifyError::NonCanonicalImport {
+                    feature: canonical_feature_name.to_owned(),
+                    import: import.clone(),
+                    canonical_feature_name: aliases
+                        .get(&unicase::UniCase::new(import.clone()))
+                        .cloned(),
+                })
+            }
         };
 
         if resolved_imports.insert(import.clone()) {
@@ -188,35 +227,77 @@ impl OptionsProviderBuilder {
         OptionsProviderBuilder {
             aliases: Aliases::new(),
             all_configurable_string_pointers: HashSet::new(),
+            all_configurable_custom_pointers: HashSet::new(),
             all_configurable_list_pointers: HashSet::new(),
+            all_configurable_reference_pointers: HashSet::new(),
+            all_configurable_secret_pointers: HashSet::new(),
+            keyed_configurable_custom_pointers: HashMap::new(),
             keyed_configurable_list_pointers: HashMap::new(),
+            keyed_configurable_reference_pointers: HashMap::new(),
+            keyed_configurable_secret_pointers: HashMap::new(),
             keyed_configurable_string_pointers: HashMap::new(),
             builder_options: BuilderOptions::default(),
             conditions: Conditions::new(),
             dependents: Dependents::new(),
+            env_overrides: None,
             features: Features::new(),
             imports: HashMap::new(),
+            liquid_templates: LiquidTemplates::new(),
             loaded_files: LoadedFiles::new(),
+            namespace_prefixes: HashMap::new(),
             referenced_file_to_feature_names: HashMap::new(),
+            report: BuildReport::default(),
             schema: None,
             sources: Sources::new(),
+            sources_without_imports: Sources::new(),
         }
     }
 
-    pub fn build_and_clear(&mut self) -> Result<OptionsProvider, String> {
-        self.prepare_build()?;
+    pub fn build_and_clear(&mut self) -> Result<OptionsProvider, OptifyError> {
+        let import_errors = self.prepare_build()?;
+        if let Some(error) = self.report.errors.first().or(import_errors.first()) {
+            return Err(error.error.clone());
+        }
+        self.report = BuildReport::default();
 
+        let all_configurable_custom_pointers =
+            std::mem::take(&mut self.all_configurable_custom_pointers)
+                .into_iter()
+                .collect();
         let all_configurable_list_pointers =
             drain_sorted_pointers(&mut self.all_configurable_list_pointers);
+        let all_configurable_reference_pointers =
+            std::mem::take(&mut self.all_configurable_reference_pointers)
+                .into_iter()
+                .collect();
+        let all_configurable_secret_pointers =
+            std::mem::take(&mut self.all_configurable_secret_pointers)
+                .into_iter()
+                .collect();
         let all_configurable_string_pointers =
             std::mem::take(&mut self.all_configurable_string_pointers)
                 .into_iter()
                 .collect();
+        let keyed_configurable_custom_pointers =
+            std::mem::take(&mut self.keyed_configurable_custom_pointers)
+                .into_iter()
+                .map(|(key, set)| (key, set.into_iter().collect()))
+                .collect();
         let keyed_configurable_list_pointers =
             std::mem::take(&mut self.keyed_configurable_list_pointers)
                 .into_iter()
                 .map(|(key, set)| (key, drain_sorted_pointers_from_set(set)))
                 .collect();
+        let keyed_configurable_reference_pointers =
+            std::mem::take(&mut self.keyed_configurable_reference_pointers)
+                .into_iter()
+                .map(|(key, set)| (key, set.into_iter().collect()))
+                .collect();
+        let keyed_configurable_secret_pointers =
+            std::mem::take(&mut self.keyed_configurable_secret_pointers)
+                .into_iter()
+                .map(|(key, set)| (key, set.into_iter().collect()))
+                .collect();
         let keyed_configurable_string_pointers =
             std::mem::take(&mut self.keyed_configurable_string_pointers)
                 .into_iter()
@@ -231,28 +312,278 @@ impl OptionsProviderBuilder {
 
         Ok(OptionsProvider::new(
             std::mem::take(&mut self.aliases),
+            all_configurable_custom_pointers,
             all_configurable_list_pointers,
+            all_configurable_reference_pointers,
+            all_configurable_secret_pointers,
             all_configurable_string_pointers,
+            keyed_configurable_custom_pointers,
             keyed_configurable_list_pointers,
+            keyed_configurable_reference_pointers,
+            keyed_configurable_secret_pointers,
             keyed_configurable_string_pointers,
             std::mem::take(&mut self.conditions),
             std::mem::take(&mut self.features),
+            std::mem::take(&mut self.imports),
             referenced_file_to_feature_names,
+            std::mem::take(&mut self.liquid_templates),
             std::mem::take(&mut self.loaded_files),
             std::mem::take(&mut self.sources),
+            std::mem::take(&mut self.sources_without_imports),
+            self.env_overrides.take(),
+            self.builder_options.configurable_value_types.clone(),
+            self.builder_options.secret_resolvers.clone(),
         ))
     }
 
-    fn prepare_build(&mut self) -> Result<(), String> {
+    /// Adds a feature without a file.
+    /// `feature` has the same format as a feature file, such as `{"imports": [...], "options": {...}}`.
+    /// The feature is processed the same way as a file, so it can have aliases, imports, conditions, and configurable values.
+    pub fn add_feature(
+        &mut self,
+        canonical_feature_name: &str,
+        feature: serde_json::Value,
+    ) -> Result<&Self, OptifyError> {
+        let loading_result = process_feature_config(
+            feature,
+            canonical_feature_name.to_owned(),
+            "",
+            canonical_feature_name,
+            None,
+            &self.builder_options,
+            &self.schema,
+        );
+        self.process_loading_result(loading_result)?;
+        Ok(self)
+    }
+
+    /// Adds a file that is not a feature without reading it from disk,
+    /// such as a file that is referenced by configurable strings.
+    /// `relative_path` uses `/` as the separator, like the paths of files in directories relative to the directory.
+    pub fn add_raw_file(
+        &mut self,
+        relative_path: &str,
+        contents: impl Into<String>,
+    ) -> Result<&Self, OptifyError> {
+        self.process_loading_result(Ok(LoadingResult::Raw(RawLoadingResult {
+            contents: contents.into(),
+            relative_path: relative_path.to_owned(),
+        })))?;
+        Ok(self)
+    }
+
+    /// Adds a directory with the canonical feature names, aliases, and files in it scoped under `prefix`, such as `"payments/"`.
+    /// This helps avoid collisions when directories from different teams have features or files with the same names.
+    /// A `/` is added to the end of `prefix` if it does not have one.
+    ///
+    /// Imports in the directory refer to features in the same directory first.
+    /// Use the fully qualified name, including the prefix, to import a feature from another namespace.
+    /// Files referenced by configurable strings are relative to the directory.
+    pub fn add_directory_with_prefix(
+        &mut self,
+        directory: impl AsRef<Path>,
+        prefix: &str,
+    ) -> Result<&Self, OptifyError> {
+        self.add_directory_in_namespace(directory.as_ref(), Some(prefix))?;
+        Ok(self)
+    }
+
+    /// Adds a directory with the features in it scoped under `prefix`.
+    /// When `prefix` is `None`, the `namespace` from the directory's configuration is used, if it is set.
+    fn add_directory_in_namespace(
+        &mut self,
+        directory: &Path,
+        prefix: Option<&str>,
+    ) -> Result<(), OptifyError> {
+        if !directory.is_dir() {
+            self.handle_error(OptifyError::InvalidDirectory {
+                path: directory.display().to_string(),
+            })?;
+            return Ok(());
+        }
+
+        // Look for .optify/config.json files in the directory and its parents which provide directory-level defaults.
+        // Builder-level options override when explicitly set (non-default values).
+        let directory_config = match load_directory_config(directory) {
+            Ok(directory_config) => directory_config,
+            Err(e) => {
+                self.handle_error(e)?;
+                return Ok(());
+            }
+        };
+        let prefix = get_namespace_prefix(
+            prefix
+                .or(directory_config.namespace.as_deref())
+                .unwrap_or_default(),
+        );
+        let builder_options = directory_config.clone().merge_with(&self.builder_options);
+
+        let feature_file_parsers = get_feature_file_parsers(&builder_options.feature_file_parsers);
+        let supported_extensions = get_supported_extensions(&feature_file_parsers);
+
+        let mut subtree_options = vec![(directory_config, builder_options)];
+        let Some(paths) =
+            self.find_files(directory, &mut subtree_options, &supported_extensions)?
+        else {
+            return Ok(());
+        };
+        let validators = self.get_validators(&subtree_options)?;
+        let loading_results: Vec<_> = paths
+            .into_par_iter()
+            .map(|file| {
+                Self::process_path(
+                    &file.path,
+                    &file.relative_path,
+                    &prefix,
+                    &subtree_options[file.options_index].1,
+                    &feature_file_parsers,
+                    &validators[file.options_index],
+                )
+            })
+            .collect();
+        // Overlays only apply to features from other directories,
+        // so files in the same directory with the same canonical feature name, such as `a.json` and `a.yaml`, are still duplicates.
+        let mut canonical_feature_names = HashSet::new();
+        for mut loading_result in loading_results {
+            if let Ok(LoadingResult::Feature(feature)) = &mut loading_result {
+                if !canonical_feature_names.insert(feature.canonical_feature_name.clone()) {
+                    feature.overlay_mode = OverlayMode::None;
+                }
+            }
+            self.process_loading_result(loading_result)?;
+        }
+
+        Ok(())
+    }
+
+    /// Builds the options provider and reports all of the problems that were found.
+    /// A provider is only built when there are no errors.
+    ///
+    /// Enable `BuilderOptions::collect_diagnostics` before adding directories or features
+    /// to keep going after errors and find all of them at once.
+    /// Otherwise, loading stops at the first error and this only reports the first error from resolving imports.
+    pub fn build_with_report(&mut self) -> (Option<OptionsProvider>, BuildReport) {
+        let mut report = self.report.clone();
+        match self.prepare_build() {
+            Ok(import_errors) => report.errors.extend(import_errors),
+            Err(error) => report.errors.push(BuildError::new(error, None)),
+        }
+        if report.has_errors() {
+            return (None, report);
+        }
+        (Some(self.create_provider()), report)
+    }
+
+    /// Creates a provider from the loaded features after imports are resolved.
+    fn create_provider(&self) -> OptionsProvider {
+        let all_configurable_custom_pointers = self
+            .all_configurable_custom_pointers
+            .iter()
+            .cloned()
+            .collect();
+        let all_configurable_reference_pointers = self
+            .all_configurable_reference_pointers
+            .iter()
+            .cloned()
+            .collect();
+        let all_configurable_secret_pointers = self
+            .all_configurable_secret_pointers
+            .iter()
+            .cloned()
+            .collect();
+        let all_configurable_string_pointers = self
+            .all_configurable_string_pointers
+            .iter()
+            .cloned()
+            .collect();
+        let all_configurable_list_pointers =
+            sorted_pointers_from_set(&self.all_configurable_list_pointers);
+        let keyed_configurable_custom_pointers = self
+            .keyed_configurable_custom_pointers
+            .iter()
+            .map(|(key, set)| (key.clone(), set.iter().cloned().collect()))
+            .collect();
+        let keyed_configurable_list_pointers = self
+            .keyed_configurable_list_pointers
+            .iter()
+            .map(|(key, set)| (key.clone(), sorted_pointers_from_set(set)))
+            .collect();
+        let keyed_configurable_reference_pointers = self
+            .keyed_configurable_reference_pointers
+            .iter()
+            .map(|(key, set)| (key.clone(), set.iter().cloned().collect()))
+            .collect();
+        let keyed_configurable_secret_pointers = self
+            .keyed_configurable_secret_pointers
+            .iter()
+            .map(|(key, set)| (key.clone(), set.iter().cloned().collect()))
+            .collect();
+        let keyed_configurable_string_pointers = self
+            .keyed_configurable_string_pointers
+            .iter()
+            .map(|(key, set)| (key.clone(), set.iter().cloned().collect()))
+            .collect();
+
+        let referenced_file_to_feature_names = if self.referenced_file_to_feature_names.is_empty() {
+            None
+        } else {
+            Some(self.referenced_file_to_feature_names.clone())
+        };
+
+        OptionsProvider::new(
+            self.aliases.clone(),
+            all_configurable_custom_pointers,
+            all_configurable_list_pointers,
+            all_configurable_reference_pointers,
+            all_configurable_secret_pointers,
+            all_configurable_string_pointers,
+            keyed_configurable_custom_pointers,
+            keyed_configurable_list_pointers,
+            keyed_configurable_reference_pointers,
+            keyed_configurable_secret_pointers,
+            keyed_configurable_string_pointers,
+            self.conditions.clone(),
+            self.features.clone(),
+            self.imports.clone(),
+            referenced_file_to_feature_names,
+            self.liquid_templates.clone(),
+            self.loaded_files.clone(),
+            self.sources.clone(),
+            self.sources_without_imports.clone(),
+            self.env_overrides.clone(),
+            self.builder_options.configurable_value_types.clone(),
+            self.builder_options.secret_resolvers.clone(),
+        )
+    }
+
+    /// Resolves imports.
+    /// When diagnostics are collected, the errors from imports are returned instead of stopping at the first error.
+    fn prepare_build(&mut self) -> Result<Vec<BuildError>, OptifyError> {
+        // Imports in a namespace refer to features in the same namespace unless they use the fully qualified name.
+        for (canonical_feature_name, prefix) in std::mem::take(&mut self.namespace_prefixes) {
+            if let Some(imports) = self.imports.get_mut(&canonical_feature_name) {
+                for import in imports.iter_mut() {
+                    let scoped_import = format!("{prefix}{import}");
+                    if self.sources.contains_key(&scoped_import) {
+                        *import = scoped_import;
+                    }
+                }
+            }
+        }
+
+        let mut errors = Vec::new();
         let mut resolved_imports: HashSet<String> = HashSet::new();
-        for (canonical_feature_name, imports_for_feature) in &self.imports {
+        // Sort to report errors in a consistent order.
+        let mut canonical_feature_names: Vec<&String> = self.imports.keys().collect();
+        canonical_feature_names.sort_unstable();
+        for canonical_feature_name in canonical_feature_names {
             if resolved_imports.insert(canonical_feature_name.clone()) {
                 // Check for infinite loops by starting a path here.
                 let mut features_in_resolution_path: HashSet<String> =
                     HashSet::from([canonical_feature_name.clone()]);
-                resolve_imports(
+                let result = resolve_imports(
                     canonical_feature_name,
-                    imports_for_feature,
+                    &self.imports[canonical_feature_name],
                     &mut resolved_imports,
                     &mut features_in_resolution_path,
                     &self.aliases,
@@ -260,86 +591,459 @@ impl OptionsProviderBuilder {
                     &self.imports,
                     &mut self.sources,
                     &self.conditions,
-                )?;
+                );
+                if let Err(error) = result {
+                    if !self.builder_options.collect_diagnostics {
+                        return Err(error);
+                    }
+                    let path = match &error {
+                        OptifyError::ImportCycle { feature, .. }
+                        | OptifyError::ImportHasConditions { feature, .. }
+                        | OptifyError::NonCanonicalImport { feature, .. } => self
+                            .features
+                            .get(feature)
+                            .and_then(|metadata| metadata.path.clone()),
+                        _ => None,
+                    };
+                    errors.push(BuildError::new(error, path));
+                }
+            }
+        }
+
+        // References and values of registered types are checked after imports are resolved
+        // so that references can point to values from imports and values can be completed by imports.
+        // Liquid templates are parsed once here instead of each time that options are retrieved.
+        if errors.is_empty() && !self.report.has_errors() {
+            // Filters and tags can be registered after the builder is created.
+            self.liquid_templates = LiquidTemplates::with_filters_and_tags(
+                &self.builder_options.liquid_filters,
+                &self.builder_options.liquid_tags,
+            );
+            let problems =
+                find_reference_problems(&self.sources, &self.all_configurable_reference_pointers)
+                    .into_iter()
+                    .chain(find_invalid_configurable_values(
+                        &self.sources,
+                        &self.all_configurable_custom_pointers,
+                        &self.builder_options.configurable_value_types,
+                    ))
+                    .chain(compile_liquid_templates(
+                        &mut self.liquid_templates,
+                        &self.all_configurable_string_pointers,
+                        &self.sources,
+                        &self.sources_without_imports,
+                        &self.features,
+                        &self.loaded_files,
+                    ));
+            for error in problems {
+                if !self.builder_options.collect_diagnostics {
+                    return Err(error);
+                }
+                let path = match &error {
+                    OptifyError::DanglingReference { feature, .. }
+                    | OptifyError::InvalidConfigurableValue { feature, .. } => self
+                        .features
+                        .get(feature)
+                        .and_then(|metadata| metadata.path.clone()),
+                    _ => None,
+                };
+                errors.push(BuildError::new(error, path));
             }
         }
 
         for (canonical_feature_name, dependents) in &self.dependents {
-            let mut sorted_dependents = dependents.clone();
-            sorted_dependents.sort_unstable();
-            self.features
-                .get_mut(canonical_feature_name)
-                .unwrap()
-                .dependents = Some(sorted_dependents);
+            // Invalid imports can be dependents when diagnostics are collected.
+            if let Some(metadata) = self.features.get_mut(canonical_feature_name) {
+                let mut sorted_dependents = dependents.clone();
+                sorted_dependents.sort_unstable();
+                metadata.dependents = Some(sorted_dependents);
+            }
         }
 
-        Ok(())
+        // Read the environment variables for each build so that a rebuild, such as by a watcher, uses their current values.
+        self.env_overrides = match &self.builder_options.env_overrides_prefix {
+            Some(prefix) => match read_env_overrides(prefix) {
+                Ok(env_overrides) => env_overrides,
+                Err(error) => {
+                    if !self.builder_options.collect_diagnostics {
+                        return Err(error);
+                    }
+                    errors.push(BuildError::new(error, None));
+                    None
+                }
+            },
+            None => None,
+        };
+
+        Ok(errors)
     }
 
+    /// `relative_path` is the path relative to the directory that is being added, with `/` as the separator.
     fn process_path(
         path: &Path,
-        directory: &Path,
+        relative_path: &str,
+        prefix: &str,
         builder_options: &BuilderOptions,
-        supported_extensions: &HashSet<&str>,
+        feature_file_parsers: &FeatureFileParsers,
         feature_contents_validator: &Option<Arc<Validator>>,
-    ) -> Result<LoadingResult, String> {
-        let is_config_file = match path.extension() {
-            Some(ext) => match ext.to_str() {
-                Some(ext_str) => supported_extensions.contains(ext_str),
-                None => false,
-            },
-            None => false,
-        };
-
-        if is_config_file {
-            process_config_file_entry(path, directory, builder_options, feature_contents_validator)
+    ) -> Result<LoadingResult, OptifyError> {
+        let parser = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| feature_file_parsers.get(extension));
+        if let Some(parser) = parser {
+            process_config_file_entry(
+                path,
+                relative_path,
+                prefix,
+                parser.as_ref(),
+                builder_options,
+                feature_contents_validator,
+            )
         } else {
             match std::fs::read_to_string(path) {
-                Ok(contents) => {
-                    let relative_path = path
-                        .strip_prefix(directory)
-                        .unwrap()
-                        .to_str()
-                        .expect("path should be valid Unicode")
-                        .replace(std::path::MAIN_SEPARATOR, "/");
-                    Ok(LoadingResult::Raw(RawLoadingResult {
-                        contents,
-                        relative_path,
-                    }))
+                Ok(contents) => Ok(LoadingResult::Raw(RawLoadingResult {
+                    contents,
+                    relative_path: format!("{prefix}{relative_path}"),
+                })),
+
+                Err(e) => Err(OptifyError::Io {
+                    path: path.display().to_string(),
+                    message: e.to_string(),
+                }),
+            }
+        }
+    }
+
+    fn process_loading_result(
+        &mut self,
+        loading_result: Result<LoadingResult, OptifyError>,
+    ) -> Result<(), OptifyError> {
+        if !self.builder_options.collect_diagnostics {
+            return match loading_result? {
+                LoadingResult::Feature(feature) => self.process_feature_loading_result(feature),
+                LoadingResult::Raw(raw) => self.process_raw_loading_result(raw),
+            };
+        }
+
+        let (result, path) = match loading_result {
+            Ok(LoadingResult::Feature(feature)) => {
+                let path = feature.metadata.path.clone();
+                (self.process_feature_loading_result(feature), path)
+            }
+            Ok(LoadingResult::Raw(raw)) => {
+                let path = Some(raw.relative_path.clone());
+                (self.process_raw_loading_result(raw), path)
+            }
+            Err(error) => (Err(error), None),
+        };
+        if let Err(error) = result {
+            self.report.errors.push(BuildError::new(error, path));
+        }
+        Ok(())
+    }
+
+    /// Finds the files to load in a directory while applying the policies for symbolic links and paths.
+    /// Ignored files and directories are skipped.
+    ///
+    /// `subtree_options` starts with the configuration and options for the directory.
+    /// The configurations and options for subdirectories with `.optify/config.json` files are added to it.
+    /// Returns `None` when the directory is skipped because of an error that was recorded.
+    fn find_files(
+        &mut self,
+        directory: &Path,
+        subtree_options: &mut Vec<(BuilderOptionsConfig, BuilderOptions)>,
+        supported_extensions: &HashSet<&str>,
+    ) -> Result<Option<Vec<FoundFile>>, OptifyError> {
+        let symlink_policy = subtree_options[0].1.symlink_policy;
+        let mut ignores = IgnoreStack::new();
+        let ignore_patterns = subtree_options[0].0.ignore.clone().unwrap_or_default();
+        // The filters for the globs in the options with the same index in `subtree_options`.
+        let mut path_filters = Vec::new();
+        match ignores
+            .push(directory, &ignore_patterns)
+            .and_then(|_| PathFilter::new(&subtree_options[0].1))
+        {
+            Ok(path_filter) => path_filters.push(path_filter),
+            Err(e) => {
+                self.handle_error(e)?;
+                return Ok(None);
+            }
+        }
+        let mut paths = Vec::new();
+        // The directories with configurations that contain the current entry, starting with `directory`.
+        let mut config_directories: Vec<(PathBuf, usize)> = vec![(directory.to_path_buf(), 0)];
+        let mut walker = walkdir::WalkDir::new(directory)
+            .follow_links(symlink_policy == SymlinkPolicy::Follow)
+            .into_iter();
+        while let Some(entry) = walker.next() {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    self.handle_error(OptifyError::Io {
+                        path: e.path().unwrap_or(directory).display().to_string(),
+                        message: format!("Error walking directory: {e}"),
+                    })?;
+                    continue;
+                }
+            };
+            let path = entry.path();
+            let is_dir = entry.file_type().is_dir();
+
+            // Entries are visited depth-first, so leave the directories that do not contain this entry.
+            ignores.leave_directories(path);
+            if entry.depth() > 0 && ignores.is_ignored(path, is_dir) {
+                if is_dir {
+                    walker.skip_current_dir();
+                }
+                continue;
+            }
+
+            // Skip the contents of.optify folders
+            if path
+                .components()
+                .any(|component| component.as_os_str() == ".optify")
+            {
+                continue;
+            }
+
+            // The directory that was given is always followed.
+            if entry.depth() > 0 && entry.path_is_symlink() {
+                match symlink_policy {
+                    SymlinkPolicy::Follow => {}
+                    SymlinkPolicy::Skip => {
+                        self.report.warnings.push(BuildWarning {
+                            category: DiagnosticCategory::Path,
+                            message: "Skipped a symbolic link.".to_owned(),
+                            path: Some(path.display().to_string()),
+                        });
+                        continue;
+                    }
+                    SymlinkPolicy::Error => {
+                        self.handle_error(OptifyError::InvalidPath {
+                            path: path.display().to_string(),
+                            message: "Symbolic links are not allowed.".to_owned(),
+                        })?;
+                        continue;
+                    }
+                }
+            }
+
+            // Entries are visited depth-first, so leave the directories that do not contain this entry.
+            while config_directories.len() > 1
+                && !path.starts_with(&config_directories.last().unwrap().0)
+            {
+                config_directories.pop();
+            }
+            let options_index = config_directories.last().unwrap().1;
+
+            if entry.depth() > 0 && is_dir {
+                let config = match read_directory_config(path).and_then(|config| {
+                    config
+                        .map(|config| {
+                            self.get_subtree_options(
+                                path,
+                                config,
+                                &subtree_options[options_index].0,
+                                &subtree_options[0].1,
+                            )
+                        })
+                        .transpose()
+                }) {
+                    Ok(config) => config,
+                    Err(e) => {
+                        self.handle_error(e)?;
+                        None
+                    }
+                };
+                let ignore_patterns = config
+                    .as_ref()
+                    .and_then(|(config, _, _)| config.ignore.as_deref())
+                    .unwrap_or_default();
+                if let Err(e) = ignores.push(path, ignore_patterns) {
+                    self.handle_error(e)?;
+                }
+                if let Some((config, builder_options, path_filter)) = config {
+                    subtree_options.push((config, builder_options));
+                    path_filters.push(path_filter);
+                    config_directories.push((path.to_path_buf(), subtree_options.len() - 1));
+                }
+                continue;
+            }
+
+            // Filter out unsupported files
+            if !path.is_file() {
+                continue;
+            }
+
+            let Ok(relative_path) = path.strip_prefix(directory) else {
+                self.handle_error(OptifyError::InvalidPath {
+                    path: path.display().to_string(),
+                    message: format!("The path is not in {}.", directory.display()),
+                })?;
+                continue;
+            };
+            if relative_path.to_str().is_none() {
+                match subtree_options[options_index].1.non_utf8_path_policy {
+                    NonUtf8PathPolicy::Lossy => {}
+                    NonUtf8PathPolicy::Skip => {
+                        self.report.warnings.push(BuildWarning {
+                            category: DiagnosticCategory::Path,
+                            message: "Skipped a path that is not valid UTF-8.".to_owned(),
+                            path: Some(path.display().to_string()),
+                        });
+                        continue;
+                    }
+                    NonUtf8PathPolicy::Error => {
+                        self.handle_error(OptifyError::InvalidPath {
+                            path: path.display().to_string(),
+                            message: "The path is not valid UTF-8.".to_owned(),
+                        })?;
+                        continue;
+                    }
                 }
+            }
 
-                Err(e) => Err(format!("Error reading file {}: {e}", path.display())),
+            let relative_path = relative_path
+                .to_string_lossy()
+                .replace(std::path::MAIN_SEPARATOR, "/");
+            if path.file_name() == Some(IGNORE_FILE_NAME.as_ref())
+                || !path_filters[options_index].is_file_included(&relative_path)
+            {
+                continue;
+            }
+            if !is_feature_file(path, supported_extensions)
+                && !path_filters[options_index].is_raw_file_allowed(&relative_path)
+            {
+                continue;
             }
+
+            paths.push(FoundFile {
+                path: path.to_path_buf(),
+                relative_path,
+                options_index,
+            });
         }
+        Ok(Some(paths))
     }
 
-    fn process_loading_result(
+    /// Gets the configuration, the options, and the filter for the files in a subdirectory with a configuration.
+    /// `directory_options` are the options for the directory that is being added.
+    fn get_subtree_options(
+        &self,
+        directory: &Path,
+        config: BuilderOptionsConfig,
+        parent_config: &BuilderOptionsConfig,
+        directory_options: &BuilderOptions,
+    ) -> Result<(BuilderOptionsConfig, BuilderOptions, PathFilter), OptifyError> {
+        let config_error = |message: &str| OptifyError::BuilderConfig {
+            path: directory
+                .join(".optify")
+                .join("config.json")
+                .display()
+                .to_string(),
+            message: message.to_owned(),
+        };
+        if config.namespace.is_some() {
+            return Err(config_error(
+                "\"namespace\" can only be set for a directory that is added to the builder, not for a subdirectory.",
+            ));
+        }
+        let config = config.inherit_from(parent_config);
+        let builder_options = config.clone().merge_with(&self.builder_options);
+        // Whether to follow symbolic links is decided once for the whole directory that is being added.
+        if builder_options.symlink_policy != directory_options.symlink_policy {
+            return Err(config_error(
+                "\"symlinkPolicy\" for a subdirectory must be the same as for the directory that is added to the builder.",
+            ));
+        }
+        let path_filter = PathFilter::new(&builder_options)?;
+        Ok((config, builder_options, path_filter))
+    }
+
+    /// Gets the validators for the schemas in the options.
+    /// The schema from the builder is used when the options have the same schema path.
+    fn get_validators(
         &mut self,
-        loading_result: Result<LoadingResult, String>,
-    ) -> Result<(), String> {
-        match loading_result? {
-            LoadingResult::Feature(feature) => self.process_feature_loading_result(feature),
-            LoadingResult::Raw(raw) => self.process_raw_loading_result(raw),
+        subtree_options: &[(BuilderOptionsConfig, BuilderOptions)],
+    ) -> Result<Vec<Option<Arc<Validator>>>, OptifyError> {
+        let mut validators: HashMap<PathBuf, Arc<Validator>> = HashMap::new();
+        let mut result = Vec::with_capacity(subtree_options.len());
+        for (_, builder_options) in subtree_options {
+            let schema_path = match &builder_options.schema_path {
+                Some(schema_path)
+                    if builder_options.schema_path != self.builder_options.schema_path =>
+                {
+                    schema_path
+                }
+                _ => {
+                    result.push(self.schema.clone());
+                    continue;
+                }
+            };
+            if let Some(validator) = validators.get(schema_path) {
+                result.push(Some(validator.clone()));
+                continue;
+            }
+            match load_schema(schema_path) {
+                Ok(validator) => {
+                    let validator = Arc::new(validator);
+                    validators.insert(schema_path.clone(), validator.clone());
+                    result.push(Some(validator));
+                }
+                Err(e) => {
+                    self.handle_error(e)?;
+                    result.push(self.schema.clone());
+                }
+            }
         }
+        Ok(result)
     }
 
-    fn process_feature_loading_result(&mut self, info: FeatureLoadingResult) -> Result<(), String> {
-        let canonical_feature_name = info.canonical_feature_name;
+    /// Records the error when diagnostics are collected. Otherwise, returns the error.
+    fn handle_error(&mut self, error: OptifyError) -> Result<(), OptifyError> {
+        if self.builder_options.collect_diagnostics {
+            self.report.errors.push(BuildError::new(error, None));
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
 
-        if self
-            .sources
-            .insert(canonical_feature_name.clone(), info.source)
-            .is_some()
+    fn process_feature_loading_result(
+        &mut self,
+        mut info: FeatureLoadingResult,
+    ) -> Result<(), OptifyError> {
+        if info.overlay_mode == OverlayMode::None
+            && self.sources.contains_key(&info.canonical_feature_name)
         {
-            return Err(format!(
-                "Error when loading feature. The canonical feature name '{canonical_feature_name}' was already added. It may be an alias for another feature."
-            ));
+            return Err(OptifyError::DuplicateFeature {
+                name: info.canonical_feature_name,
+            });
+        }
+        if let Some(existing_source) = self.sources.remove(&info.canonical_feature_name) {
+            self.overlay_feature(&mut info, existing_source);
+        }
+
+        let canonical_feature_name = info.canonical_feature_name;
+        if !info.prefix.is_empty() {
+            self.namespace_prefixes
+                .insert(canonical_feature_name.clone(), info.prefix);
         }
+
+        self.sources
+            .insert(canonical_feature_name.clone(), info.source);
         if let Some(conditions) = info.conditions {
             self.conditions
                 .insert(canonical_feature_name.clone(), conditions);
         }
         if let Some(imports) = info.imports {
+            if !imports.is_empty() {
+                self.sources_without_imports.insert(
+                    canonical_feature_name.clone(),
+                    self.sources[&canonical_feature_name].clone(),
+                );
+            }
             self.imports.insert(canonical_feature_name.clone(), imports);
         }
         self.process_loaded_configurable_value_pointers(info.configurable_value_pointers);
@@ -363,15 +1067,80 @@ impl OptionsProviderBuilder {
         Ok(())
     }
 
-    fn process_raw_loading_result(&mut self, raw_result: RawLoadingResult) -> Result<(), String> {
+    /// Removes the rest of the feature that was already added with the same canonical feature name so that `info` can be added in its place.
+    /// `source` is the options of the existing feature, which were already removed.
+    /// For `OverlayMode::Merge`, the existing feature is merged into `info`.
+    fn overlay_feature(&mut self, info: &mut FeatureLoadingResult, source: serde_json::Value) {
+        let canonical_feature_name = &info.canonical_feature_name;
+        self.sources_without_imports.remove(canonical_feature_name);
+        let conditions = self.conditions.remove(canonical_feature_name);
+        let imports = self.imports.remove(canonical_feature_name);
+        let metadata = self.features.remove(canonical_feature_name);
+        self.aliases
+            .retain(|_, existing_name| existing_name != canonical_feature_name);
+        let mut referenced_files = Vec::new();
+        self.referenced_file_to_feature_names
+            .retain(|file, feature_names| {
+                let previous_len = feature_names.len();
+                feature_names.retain(|feature_name| feature_name != canonical_feature_name);
+                if feature_names.len() != previous_len {
+                    referenced_files.push(file.clone());
+                }
+                !feature_names.is_empty()
+            });
+
+        // Features are always added with their metadata.
+        let Some(metadata) = metadata else {
+            return;
+        };
+        let mut overridden_paths = metadata.overridden_paths.unwrap_or_default();
+        overridden_paths.extend(metadata.path);
+        if !overridden_paths.is_empty() {
+            info.metadata.overridden_paths = Some(overridden_paths);
+        }
+
+        if info.overlay_mode != OverlayMode::Merge {
+            return;
+        }
+        merge_json_with_defaults(&mut info.source, &source, &mut FrozenPaths::new());
+        if info.conditions.is_none() {
+            info.conditions = conditions;
+        }
+        if info.imports.is_none() {
+            info.imports = imports;
+        }
+        if let Some(existing_aliases) = metadata.aliases {
+            let aliases = info.metadata.aliases.get_or_insert_with(Vec::new);
+            for alias in existing_aliases {
+                if !aliases.contains(&alias) {
+                    aliases.push(alias);
+                }
+            }
+        }
+        if info.metadata.details.is_none() {
+            info.metadata.details = metadata.details;
+        }
+        if info.metadata.owners.is_none() {
+            info.metadata.owners = metadata.owners;
+        }
+        for file in referenced_files {
+            if !info.configurable_string_files.contains(&file) {
+                info.configurable_string_files.push(file);
+            }
+        }
+    }
+
+    fn process_raw_loading_result(
+        &mut self,
+        raw_result: RawLoadingResult,
+    ) -> Result<(), OptifyError> {
         match self.loaded_files.entry(raw_result.relative_path) {
             std::collections::hash_map::Entry::Occupied(entry) => {
                 // Since the files are loaded from a directory,
                 // this should only happen when two directories have files with the same path relative to the given directory.
-                return Err(format!(
-                    "File '{}' is already loaded from another directory.",
-                    entry.key()
-                ));
+                return Err(OptifyError::DuplicateFile {
+                    relative_path: entry.key().clone(),
+                });
             }
             std::collections::hash_map::Entry::Vacant(entry) => {
                 entry.insert(raw_result.contents);
@@ -381,14 +1150,34 @@ impl OptionsProviderBuilder {
     }
 
     fn process_loaded_configurable_value_pointers(&mut self, pointers: ConfigurableValuePointers) {
+        if !pointers.configurable_custom_pointers.is_empty() {
+            self.all_configurable_custom_pointers
+                .extend(pointers.configurable_custom_pointers);
+        }
         if !pointers.configurable_list_pointers.is_empty() {
             self.all_configurable_list_pointers
                 .extend(pointers.configurable_list_pointers);
         }
+        if !pointers.configurable_reference_pointers.is_empty() {
+            self.all_configurable_reference_pointers
+                .extend(pointers.configurable_reference_pointers);
+        }
+        if !pointers.configurable_secret_pointers.is_empty() {
+            self.all_configurable_secret_pointers
+                .extend(pointers.configurable_secret_pointers);
+        }
         if !pointers.configurable_string_pointers.is_empty() {
             self.all_configurable_string_pointers
                 .extend(pointers.configurable_string_pointers);
         }
+        if !pointers.keyed_configurable_custom_pointers.is_empty() {
+            for (key, keyed_pointers) in pointers.keyed_configurable_custom_pointers {
+                self.keyed_configurable_custom_pointers
+                    .entry(key)
+                    .and_modify(|dest_set| dest_set.extend(keyed_pointers.clone()))
+                    .or_insert(keyed_pointers.into_iter().collect());
+            }
+        }
         if !pointers.keyed_configurable_list_pointers.is_empty() {
             for (key, keyed_pointers) in pointers.keyed_configurable_list_pointers {
                 self.keyed_configurable_list_pointers
@@ -397,6 +1186,22 @@ impl OptionsProviderBuilder {
                     .or_insert(keyed_pointers.into_iter().collect());
             }
         }
+        if !pointers.keyed_configurable_reference_pointers.is_empty() {
+            for (key, keyed_pointers) in pointers.keyed_configurable_reference_pointers {
+                self.keyed_configurable_reference_pointers
+                    .entry(key)
+                    .and_modify(|dest_set| dest_set.extend(keyed_pointers.clone()))
+                    .or_insert(keyed_pointers.into_iter().collect());
+            }
+        }
+        if !pointers.keyed_configurable_secret_pointers.is_empty() {
+            for (key, keyed_pointers) in pointers.keyed_configurable_secret_pointers {
+                self.keyed_configurable_secret_pointers
+                    .entry(key)
+                    .and_modify(|dest_set| dest_set.extend(keyed_pointers.clone()))
+                    .or_insert(keyed_pointers.into_iter().collect());
+            }
+        }
         if !pointers.keyed_configurable_string_pointers.is_empty() {
             for (key, keyed_pointers) in pointers.keyed_configurable_string_pointers {
                 self.keyed_configurable_string_pointers
@@ -408,23 +1213,31 @@ impl OptionsProviderBuilder {
     }
 }
 
+fn is_feature_file(path: &Path, supported_extensions: &HashSet<&str>) -> bool {
+    match path.extension() {
+        Some(ext) => match ext.to_str() {
+            Some(ext_str) => supported_extensions.contains(ext_str),
+            None => false,
+        },
+        None => false,
+    }
+}
+
 fn validate_with_schema(
     validator: &Option<Arc<Validator>>,
     original_config: &serde_json::Value,
-    path: &String,
-) -> Result<(), String> {
+    path: &str,
+) -> Result<(), OptifyError> {
     match validator {
         Some(validator) => {
             if validator.is_valid(original_config) {
                 Ok(())
             } else {
                 let errors = validator.iter_errors(original_config);
-                let error_messages: Vec<String> = errors.map(|e| format!("{e}")).collect();
-                Err(format!(
-                    "Schema validation failed for {:?} : {}",
-                    path,
-                    error_messages.join(", ")
-                ))
+                Err(OptifyError::SchemaValidation {
+                    file: path.to_owned(),
+                    errors: errors.map(|e| format!("{e}")).collect(),
+                })
             }
         }
         None => Ok(()),
@@ -433,58 +1246,86 @@ fn validate_with_schema(
 
 fn process_config_file_entry(
     path: &Path,
-    directory: &Path,
+    relative_path: &str,
+    prefix: &str,
+    parser: &dyn FeatureFileParser,
     builder_options: &BuilderOptions,
     feature_contents_validator: &Option<Arc<Validator>>,
-) -> Result<LoadingResult, String> {
+) -> Result<LoadingResult, OptifyError> {
     let absolute_path = dunce::canonicalize(path)
-        .expect("path should be valid")
+        .map_err(|e| OptifyError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?
         .to_string_lossy()
         .to_string();
-    // TODO Optimization: Find a more efficient way to build a more generic view of the file.
-    // The `config` library is helpful because it handles many file types.
-    // It would also be nice to support comments in .json files, even though it is not standard.
-    // The `config` library does support .json5 which supports comments.
-    let file = config::File::from(path);
-    let config_for_path = match config::Config::builder().add_source(file).build() {
-        Ok(conf) => conf,
-        Err(e) => return Err(format!("Error loading file '{}': {e}", absolute_path)),
-    };
-
+    let contents = std::fs::read_to_string(path).map_err(|e| OptifyError::Io {
+        path: absolute_path.clone(),
+        message: e.to_string(),
+    })?;
     // We need the raw JSON for validation.
-    let raw_config: serde_json::Value = match config_for_path.try_deserialize() {
+    let raw_config = match parser.parse(&contents) {
         Ok(v) => v,
-        Err(e) => {
-            return Err(format!(
-                "Error deserializing configuration for file '{}': {e}",
-                absolute_path,
-            ))
+        Err(message) => {
+            return Err(OptifyError::Parse {
+                file: absolute_path,
+                message: format!("{message} in {}", path.display()),
+            })
         }
     };
 
-    validate_with_schema(feature_contents_validator, &raw_config, &absolute_path)?;
+    let canonical_feature_name = format!("{prefix}{}", get_canonical_feature_name(relative_path));
+    process_feature_config(
+        raw_config,
+        canonical_feature_name,
+        prefix,
+        &absolute_path,
+        Some(absolute_path.clone()),
+        builder_options,
+        feature_contents_validator,
+    )
+}
+
+/// Processes the contents of a feature in the same format as a feature file.
+/// `canonical_feature_name` already starts with `prefix`.
+/// `source_name` identifies where the feature came from in errors.
+fn process_feature_config(
+    raw_config: serde_json::Value,
+    canonical_feature_name: String,
+    prefix: &str,
+    source_name: &str,
+    path: Option<String>,
+    builder_options: &BuilderOptions,
+    feature_contents_validator: &Option<Arc<Validator>>,
+) -> Result<LoadingResult, OptifyError> {
+    validate_with_schema(feature_contents_validator, &raw_config, source_name)?;
 
     let feature_config: FeatureConfiguration = match serde_json::from_value(raw_config.clone()) {
         Ok(v) => v,
         Err(e) => {
-            return Err(format!(
-                "Error deserializing configuration for file '{}': {e}",
-                absolute_path,
-            ))
+            return Err(OptifyError::Deserialize {
+                file: source_name.to_owned(),
+                message: e.to_string(),
+            })
         }
     };
 
-    let source = feature_config
+    let mut source = feature_config
         .options
         .unwrap_or(serde_json::Value::Object(serde_json::Map::new()));
 
-    let canonical_feature_name = get_canonical_feature_name(path, directory);
-
     // Ensure the name is set in the metadata.
     let metadata = match feature_config.metadata {
         Some(mut metadata) => {
+            if !prefix.is_empty() {
+                if let Some(aliases) = &mut metadata.aliases {
+                    for alias in aliases.iter_mut() {
+                        alias.insert_str(0, prefix);
+                    }
+                }
+            }
             metadata.name = Some(canonical_feature_name.clone());
-            metadata.path = Some(absolute_path);
+            metadata.path = path;
             metadata
         }
         None => OptionsMetadata::new(
@@ -493,13 +1334,16 @@ fn process_config_file_entry(
             None,
             Some(canonical_feature_name.clone()),
             None,
-            Some(absolute_path),
+            path,
         ),
     };
 
-    let (configurable_value_pointers, configurable_string_files) =
+    let (configurable_value_pointers, mut configurable_string_files) =
         if builder_options.are_configurable_strings_enabled {
-            let pointers = find_configurable_values(raw_config.get("options"));
+            let pointers = find_configurable_values(
+                raw_config.get("options"),
+                &builder_options.configurable_value_types,
+            );
 
             // Tracking file references is usually not enabled in production systems and is mainly for local development,
             // so we won't complicate `find_configurable_values` and make it also track referenced files.
@@ -517,6 +1361,16 @@ fn process_config_file_entry(
         } else {
             (ConfigurableValuePointers::default(), Vec::new())
         };
+    if !prefix.is_empty() {
+        prefix_configurable_string_files(
+            &mut source,
+            &configurable_value_pointers.configurable_string_pointers,
+            prefix,
+        );
+        for file in configurable_string_files.iter_mut() {
+            file.insert_str(0, prefix);
+        }
+    }
 
     Ok(LoadingResult::Feature(FeatureLoadingResult {
         canonical_feature_name,
@@ -525,169 +1379,180 @@ fn process_config_file_entry(
         configurable_value_pointers,
         imports: feature_config.imports,
         metadata,
+        overlay_mode: builder_options.overlay_mode,
+        prefix: prefix.to_owned(),
         source,
     }))
 }
 
-impl OptionsRegistryBuilder<OptionsProvider> for OptionsProviderBuilder {
-    fn add_directories(&mut self, directories: &[impl AsRef<Path>]) -> Result<&Self, String> {
-        for directory in directories {
-            self.add_directory(directory)?;
+/// Gets the prefix for a namespace with a `/` at the end or an empty string when there is no namespace.
+fn get_namespace_prefix(namespace: &str) -> String {
+    let namespace = namespace.trim_end_matches('/');
+    if namespace.is_empty() {
+        String::new()
+    } else {
+        format!("{namespace}/")
+    }
+}
+
+/// Scopes the files referenced by configurable strings to the namespace of the feature.
+fn prefix_configurable_string_files(
+    options: &mut serde_json::Value,
+    configurable_string_pointers: &[String],
+    prefix: &str,
+) {
+    fn prefix_file(value: &mut serde_json::Value, prefix: &str) {
+        if let Some(serde_json::Value::String(file)) = value.get_mut("file") {
+            file.insert_str(0, prefix);
         }
-        Ok(self)
     }
 
-    fn add_directory(&mut self, directory: impl AsRef<Path>) -> Result<&Self, String> {
-        let directory = directory.as_ref();
-        if !directory.is_dir() {
-            return Err(format!(
-                "Error adding directory: {directory:?} is not a directory"
-            ));
+    for pointer in configurable_string_pointers {
+        if let Some(configurable_string) = options.pointer_mut(pointer) {
+            if let Some(base) = configurable_string.get_mut("base") {
+                prefix_file(base, prefix);
+            }
+            if let Some(serde_json::Value::Object(arguments)) =
+                configurable_string.get_mut("arguments")
+            {
+                for argument in arguments.values_mut() {
+                    prefix_file(argument, prefix);
+                }
+            }
         }
+    }
+}
 
-        // Look for .optify/config.json which provides directory-level defaults.
-        // Builder-level options override when explicitly set (non-default values).
-        let config_path = directory.join(".optify").join("config.json");
-        let builder_options = if config_path.is_file() {
-            read_json_from_file_as::<BuilderOptionsConfig>(&config_path)
-                .map_err(|e| {
-                    format!(
-                        "Error loading builder options from {}: {e}",
-                        config_path.as_path().display()
-                    )
-                })?
-                .merge_with(&self.builder_options)
-        } else {
-            self.builder_options.clone()
-        };
+/// Loads a JSON schema to validate feature files.
+fn load_schema(schema_path: &Path) -> Result<Validator, OptifyError> {
+    let schema_path = dunce::canonicalize(schema_path).map_err(|e| OptifyError::Schema {
+        message: format!("Failed to resolve schema path: {e}"),
+    })?;
+    let schema_json = crate::json::reader::read_json_from_file(&schema_path).map_err(|e| {
+        OptifyError::Schema {
+            message: format!("Failed to read schema file: {e}"),
+        }
+    })?;
+    let schema_uri = url::Url::from_file_path(&schema_path).map_err(|()| OptifyError::Schema {
+        message: format!(
+            "Failed to convert schema path to a file URI: {}",
+            schema_path.display()
+        ),
+    })?;
+
+    // Load the embedded schema file (this is resolved at compile time).
+    const EMBEDDED_SCHEMA: &[u8] =
+        include_bytes!(concat!(env!("OUT_DIR"), "/schemas/feature_file.json"));
+    let optify_schema_json: serde_json::Value =
+        serde_json::from_slice(EMBEDDED_SCHEMA).map_err(|e| OptifyError::Schema {
+            message: format!("Failed to parse embedded schema: {e}"),
+        })?;
+    let registry = Registry::new()
+        .add(
+            "https://raw.githubusercontent.com/juharris/optify/refs/heads/main/schemas/feature_file.json",
+            optify_schema_json,
+        )
+        .map_err(|e| OptifyError::Schema {
+            message: format!("Failed to add schema resource to registry: {e}"),
+        })?
+        .prepare()
+        .map_err(|e| OptifyError::Schema {
+            message: format!("Failed to prepare schema registry: {e}"),
+        })?;
 
-        let supported_extensions = get_supported_extensions();
+    let validator = Validator::options()
+        .with_base_uri(schema_uri.as_str())
+        .with_registry(&registry)
+        .build(&schema_json)
+        .map_err(|e| OptifyError::Schema {
+            message: format!("Invalid schema: {e}"),
+        })?;
 
-        let loading_results: Vec<Result<LoadingResult, String>> = walkdir::WalkDir::new(directory)
-            .into_iter()
-            .filter_map(|entry| {
-                let entry = entry
-                    .unwrap_or_else(|_| panic!("Error walking directory: {}", directory.display()));
-                let path = entry.path();
+    Ok(validator)
+}
 
-                // Filter out unsupported files
-                if !path.is_file() {
-                    return None;
-                }
+impl OptionsRegistryBuilder<OptionsProvider> for OptionsProviderBuilder {
+    fn add_directories(&mut self, directories: &[impl AsRef<Path>]) -> Result<&Self, OptifyError> {
+        for directory in directories {
+            self.add_directory(directory)?;
+        }
+        Ok(self)
+    }
 
-                // Skip the contents of.optify folders
-                if path
-                    .components()
-                    .any(|component| component.as_os_str() == ".optify")
-                {
-                    return None;
-                }
+    fn add_directory(&mut self, directory: impl AsRef<Path>) -> Result<&Self, OptifyError> {
+        self.add_directory_in_namespace(directory.as_ref(), None)?;
+        Ok(self)
+    }
 
-                Some(Ok(path.to_path_buf()))
-            })
-            .collect::<Vec<_>>()
-            .into_par_iter()
-            .map(|path_result| match path_result {
-                Ok(path) => Self::process_path(
-                    &path,
-                    directory,
-                    &builder_options,
-                    &supported_extensions,
-                    &self.schema,
-                ),
-                Err(e) => Err(e),
-            })
-            .collect();
-        for loading_result in loading_results {
-            self.process_loading_result(loading_result)?;
-        }
+    fn with_configurable_value_type(
+        &mut self,
+        value_type: Arc<dyn ConfigurableValueType>,
+    ) -> Result<&Self, OptifyError> {
+        self.builder_options
+            .configurable_value_types
+            .insert(value_type.type_name().to_owned(), value_type);
+        Ok(self)
+    }
+
+    fn with_feature_file_parser(
+        &mut self,
+        extension: &str,
+        parser: Arc<dyn FeatureFileParser>,
+    ) -> Result<&Self, OptifyError> {
+        self.builder_options
+            .feature_file_parsers
+            .insert(extension.to_owned(), parser);
+        Ok(self)
+    }
+
+    fn with_liquid_filter(&mut self, filter: Box<dyn ParseFilter>) -> Result<&Self, OptifyError> {
+        self.builder_options
+            .liquid_filters
+            .insert(filter.reflection().name().to_owned(), filter);
+        Ok(self)
+    }
 
+    fn with_liquid_tag(&mut self, tag: Box<dyn ParseTag>) -> Result<&Self, OptifyError> {
+        self.builder_options
+            .liquid_tags
+            .insert(tag.reflection().tag().to_owned(), tag);
         Ok(self)
     }
 
-    fn with_options(&mut self, options: BuilderOptions) -> Result<&Self, String> {
+    fn with_options(&mut self, mut options: BuilderOptions) -> Result<&Self, OptifyError> {
         if let Some(ref schema_path) = options.schema_path {
             self.with_schema(schema_path)?;
         }
+        keep_registrations(&mut options, &mut self.builder_options);
         self.builder_options = options;
         Ok(self)
     }
 
-    fn with_schema(&mut self, schema_path: impl AsRef<Path>) -> Result<&Self, String> {
-        let schema_path = dunce::canonicalize(schema_path.as_ref())
-            .map_err(|e| format!("Failed to resolve schema path: {e}"))?;
-        let schema_json = crate::json::reader::read_json_from_file(&schema_path)
-            .map_err(|e| format!("Failed to read schema file: {e}"))?;
-        let schema_uri = url::Url::from_file_path(&schema_path).map_err(|()| {
-            format!(
-                "Failed to convert schema path to a file URI: {}",
-                schema_path.display()
-            )
-        })?;
-
-        // Load the embedded schema file (this is resolved at compile time).
-        const EMBEDDED_SCHEMA: &[u8] =
-            include_bytes!(concat!(env!("OUT_DIR"), "/schemas/feature_file.json"));
-        let optify_schema_json: serde_json::Value = serde_json::from_slice(EMBEDDED_SCHEMA)
-            .map_err(|e| format!("Failed to parse embedded schema: {e}"))?;
-        let registry = Registry::new()
-            .add(
-                "https://raw.githubusercontent.com/juharris/optify/refs/heads/main/schemas/feature_file.json",
-                optify_schema_json,
-            )
-            .map_err(|e| format!("Failed to add schema resource to registry: {e}"))?
-            .prepare()
-            .map_err(|e| format!("Failed to prepare schema registry: {e}"))?;
-
-        let validator = Validator::options()
-            .with_base_uri(schema_uri.as_str())
-            .with_registry(&registry)
-            .build(&schema_json)
-            .map_err(|e| format!("Invalid schema: {e}"))?;
-
-        self.schema = Some(Arc::new(validator));
-
+    fn with_secret_resolver(
+        &mut self,
+        name: &str,
+        resolver: Arc<dyn SecretResolver>,
+    ) -> Result<&Self, OptifyError> {
+        self.builder_options
+            .secret_resolvers
+            .insert(name.to_owned(), resolver);
         Ok(self)
     }
 
-    fn build(&mut self) -> Result<OptionsProvider, String> {
-        self.prepare_build()?;
-
-        let all_configurable_string_pointers = self
-            .all_configurable_string_pointers
-            .iter()
-            .cloned()
-            .collect();
-        let all_configurable_list_pointers =
-            sorted_pointers_from_set(&self.all_configurable_list_pointers);
-        let keyed_configurable_list_pointers = self
-            .keyed_configurable_list_pointers
-            .iter()
-            .map(|(key, set)| (key.clone(), sorted_pointers_from_set(set)))
-            .collect();
-        let keyed_configurable_string_pointers = self
-            .keyed_configurable_string_pointers
-            .iter()
-            .map(|(key, set)| (key.clone(), set.iter().cloned().collect()))
-            .collect();
+    fn with_schema(&mut self, schema_path: impl AsRef<Path>) -> Result<&Self, OptifyError> {
+        let schema_path = schema_path.as_ref();
+        self.schema = Some(Arc::new(load_schema(schema_path)?));
+        // Keep the path so that schemas in directory configurations do not override it.
+        self.builder_options.schema_path = Some(schema_path.to_path_buf());
 
-        let referenced_file_to_feature_names = if self.referenced_file_to_feature_names.is_empty() {
-            None
-        } else {
-            Some(self.referenced_file_to_feature_names.clone())
-        };
+        Ok(self)
+    }
 
-        Ok(OptionsProvider::new(
-            self.aliases.clone(),
-            all_configurable_list_pointers,
-            all_configurable_string_pointers,
-            keyed_configurable_list_pointers,
-            keyed_configurable_string_pointers,
-            self.conditions.clone(),
-            self.features.clone(),
-            referenced_file_to_feature_names,
-            self.loaded_files.clone(),
-            self.sources.clone(),
-        ))
+    fn build(&mut self) -> Result<OptionsProvider, OptifyError> {
+        let import_errors = self.prepare_build()?;
+        if let Some(error) = self.report.errors.first().or(import_errors.first()) {
+            return Err(error.error.clone());
+        }
+        Ok(self.create_provider())
     }
 }
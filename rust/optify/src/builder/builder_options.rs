@@ -1,6 +1,11 @@
 use serde::Deserialize;
 use std::path::PathBuf;
 
+use crate::builder::feature_file_parser::FeatureFileParsers;
+use crate::configurable_string::{LiquidFilters, LiquidTags};
+use crate::configurable_values::ConfigurableValueTypes;
+use crate::secret::SecretResolvers;
+
 /// Determines how file references should be tracked.
 #[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -16,30 +21,123 @@ pub enum TrackReferenceMode {
     KeyName,
 }
 
+/// Determines how symbolic links in directories are handled.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SymlinkPolicy {
+    /// Load the files that links point to and walk the directories that links point to.
+    /// Broken links and loops of links are errors.
+    #[default]
+    Follow,
+
+    /// Ignore links and report a warning.
+    Skip,
+
+    /// Links are errors.
+    Error,
+}
+
+/// Determines how paths that are not valid UTF-8 are handled.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum NonUtf8PathPolicy {
+    /// Paths that are not valid UTF-8 are errors.
+    #[default]
+    Error,
+
+    /// Ignore the files and report a warning.
+    Skip,
+
+    /// Replace invalid characters with U+FFFD in feature names and relative paths.
+    Lossy,
+}
+
+/// Determines what happens when a feature has the same canonical feature name as a feature that was already added,
+/// such as when a directory for an application overrides features from a directory in a library.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum OverlayMode {
+    /// Features with the same canonical feature name are errors.
+    #[default]
+    None,
+
+    /// The new feature replaces the existing feature, including its options, imports, conditions, and aliases.
+    Replace,
+
+    /// The options of the new feature are deep-merged over the options of the existing feature.
+    /// Imports, conditions, and metadata from the new feature are used when they are set.
+    /// Aliases from both features are kept.
+    Merge,
+}
+
 /// Deserializable form of builder options from `.optify/config.json`.
 /// Fields are optional so that unset values resolve to their defaults.
 /// These values provide defaults that can be overridden by builder-level `BuilderOptions`.
-#[derive(Deserialize, Default)]
+#[derive(Clone, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct BuilderOptionsConfig {
     #[serde(default)]
     #[serde(alias = "areConfigurableStringsEnabled")]
     pub are_configurable_values_enabled: Option<bool>,
+    /// Gitignore-style patterns for files to ignore, in addition to the patterns in `.optifyignore`.
+    /// This is not merged into `BuilderOptions`.
+    #[serde(default)]
+    pub ignore: Option<Vec<String>>,
+    /// Scopes the canonical feature names, aliases, and files in the directory under `"{namespace}/"`.
+    /// This is not merged into `BuilderOptions` and it is an error in the configuration of a subdirectory.
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub non_utf8_path_policy: Option<NonUtf8PathPolicy>,
+    #[serde(default)]
+    pub overlay_mode: Option<OverlayMode>,
+    #[serde(default)]
+    pub raw_file_globs: Option<Vec<String>>,
+    /// Stops searching parent directories for more configuration files.
+    #[serde(default)]
+    pub root: Option<bool>,
+    /// Relative paths are relative to the `.optify` folder with the configuration file.
     #[serde(default)]
     pub schema_path: Option<PathBuf>,
     #[serde(default)]
+    pub symlink_policy: Option<SymlinkPolicy>,
+    #[serde(default)]
     pub track_file_references: Option<TrackReferenceMode>,
 }
 
 impl BuilderOptionsConfig {
+    /// Uses the values from the configuration of a parent directory for fields that are not set.
+    /// `ignore`, `namespace`, and `root` are not inherited.
+    pub fn inherit_from(self, parent: &BuilderOptionsConfig) -> BuilderOptionsConfig {
+        BuilderOptionsConfig {
+            are_configurable_values_enabled: self
+                .are_configurable_values_enabled
+                .or(parent.are_configurable_values_enabled),
+            ignore: self.ignore,
+            namespace: self.namespace,
+            non_utf8_path_policy: self.non_utf8_path_policy.or(parent.non_utf8_path_policy),
+            overlay_mode: self.overlay_mode.or(parent.overlay_mode),
+            raw_file_globs: self
+                .raw_file_globs
+                .or_else(|| parent.raw_file_globs.clone()),
+            root: self.root,
+            schema_path: self.schema_path.or_else(|| parent.schema_path.clone()),
+            symlink_policy: self.symlink_policy.or(parent.symlink_policy),
+            track_file_references: self.track_file_references.or(parent.track_file_references),
+        }
+    }
+
     /// Provides default values for fields not explicitly set in the builder-level `overrides`.
     /// Fields in `overrides` that differ from `BuilderOptions::default()` take priority.
     pub fn merge_with(self, overrides: &BuilderOptions) -> BuilderOptions {
         let defaults = BuilderOptions::default();
-        let are_configurable_values_enabled = if overrides.are_configurable_values_enabled
+        // Also respect the deprecated flag for callers that have not switched to `are_configurable_values_enabled` yet.
+        let overrides_are_configurable_values_enabled =
+            overrides.are_configurable_values_enabled || overrides.are_configurable_strings_enabled;
+        let are_configurable_values_enabled = if overrides_are_configurable_values_enabled
             != defaults.are_configurable_values_enabled
         {
-            overrides.are_configurable_values_enabled
+            overrides_are_configurable_values_enabled
         } else {
             self.are_configurable_values_enabled
                 .unwrap_or(defaults.are_configurable_values_enabled)
@@ -47,11 +145,42 @@ impl BuilderOptionsConfig {
         BuilderOptions {
             are_configurable_strings_enabled: are_configurable_values_enabled,
             are_configurable_values_enabled,
+            collect_diagnostics: overrides.collect_diagnostics,
+            configurable_value_types: overrides.configurable_value_types.clone(),
+            env_overrides_prefix: overrides.env_overrides_prefix.clone(),
+            exclude_globs: overrides.exclude_globs.clone(),
+            feature_file_parsers: overrides.feature_file_parsers.clone(),
+            include_globs: overrides.include_globs.clone(),
+            liquid_filters: overrides.liquid_filters.clone(),
+            liquid_tags: overrides.liquid_tags.clone(),
+            non_utf8_path_policy: if overrides.non_utf8_path_policy != defaults.non_utf8_path_policy
+            {
+                overrides.non_utf8_path_policy
+            } else {
+                self.non_utf8_path_policy
+                    .unwrap_or(defaults.non_utf8_path_policy)
+            },
+            overlay_mode: if overrides.overlay_mode != defaults.overlay_mode {
+                overrides.overlay_mode
+            } else {
+                self.overlay_mode.unwrap_or(defaults.overlay_mode)
+            },
+            raw_file_globs: if overrides.raw_file_globs != defaults.raw_file_globs {
+                overrides.raw_file_globs.clone()
+            } else {
+                self.raw_file_globs.or(defaults.raw_file_globs)
+            },
             schema_path: if overrides.schema_path != defaults.schema_path {
                 overrides.schema_path.clone()
             } else {
                 self.schema_path.or(defaults.schema_path)
             },
+            secret_resolvers: overrides.secret_resolvers.clone(),
+            symlink_policy: if overrides.symlink_policy != defaults.symlink_policy {
+                overrides.symlink_policy
+            } else {
+                self.symlink_policy.unwrap_or(defaults.symlink_policy)
+            },
             track_file_references: if overrides.track_file_references
                 != defaults.track_file_references
             {
@@ -70,10 +199,72 @@ pub struct BuilderOptions {
     /// DEPRECATED: Use `are_configurable_values_enabled` instead to enable all configurable values.
     pub are_configurable_strings_enabled: bool,
     pub are_configurable_values_enabled: bool,
+    /// Keep loading every file and resolving every import after errors are found
+    /// so that all of the problems can be reported at once by `OptionsProviderBuilder::build_with_report`.
+    pub collect_diagnostics: bool,
+    /// Types for configurable values mapped to their type names.
+    /// Values with one of these names as their `"$type"` are built by the type when options are retrieved with configurable values enabled.
+    pub configurable_value_types: ConfigurableValueTypes,
+    /// When set, environment variables such as `{prefix}__myConfig__myObject__one=5` are read when building
+    /// and override the options from all features and the overrides in `GetOptionsPreferences`.
+    /// Values are parsed as JSON when possible, otherwise they are strings.
+    /// `DEFAULT_ENV_OVERRIDES_PREFIX` is `"OPTIFY"`.
+    pub env_overrides_prefix: Option<String>,
+    /// Globs for paths, relative to each directory, of files that are not loaded.
+    /// `/` is the separator and `*` can match `/`.
+    pub exclude_globs: Vec<String>,
+    /// Parsers for feature files mapped to the file extensions, without the leading `.`, of the files that they parse.
+    /// They replace the built-in parsers for JSON with comments, JSON5, YAML, TOML, INI, and RON files with the same extensions.
+    pub feature_file_parsers: FeatureFileParsers,
+    /// When not empty, only files with paths, relative to each directory, that match one of these globs are loaded.
+    pub include_globs: Vec<String>,
+    /// Custom filters for the Liquid templates in configurable strings mapped to their names,
+    /// such as `{{ name | my_filter }}`, in addition to the filters in Liquid's standard library.
+    pub liquid_filters: LiquidFilters,
+    /// Custom tags for the Liquid templates in configurable strings mapped to their names,
+    /// such as `{% my_tag %}`, in addition to the tags in Liquid's standard library.
+    pub liquid_tags: LiquidTags,
+    pub non_utf8_path_policy: NonUtf8PathPolicy,
+    /// How features in the directories that are added override features with the same canonical feature names that were already added.
+    pub overlay_mode: OverlayMode,
+    /// When set, only files that are not feature files and match one of these globs are loaded,
+    /// such as `["templates/**", "*.liquid"]` for files used by configurable strings.
+    pub raw_file_globs: Option<Vec<String>>,
     pub schema_path: Option<PathBuf>,
+    /// Resolvers for `Optify.Secret` values mapped to the names that secrets use to refer to them.
+    /// Secrets are resolved when options are retrieved with configurable values enabled.
+    pub secret_resolvers: SecretResolvers,
+    pub symlink_policy: SymlinkPolicy,
     pub track_file_references: TrackReferenceMode,
 }
 
+/// Keeps the types, parsers, Liquid filters and tags, and secret resolvers from `previous` that were registered with
+/// `with_configurable_value_type`, `with_feature_file_parser`, `with_liquid_filter`, `with_liquid_tag`, and `with_secret_resolver`
+/// when `options` replace them, unless `options` have a registration with the same name or extension.
+pub(crate) fn keep_registrations(options: &mut BuilderOptions, previous: &mut BuilderOptions) {
+    for (type_name, value_type) in std::mem::take(&mut previous.configurable_value_types) {
+        options
+            .configurable_value_types
+            .entry(type_name)
+            .or_insert(value_type);
+    }
+    for (extension, parser) in std::mem::take(&mut previous.feature_file_parsers) {
+        options
+            .feature_file_parsers
+            .entry(extension)
+            .or_insert(parser);
+    }
+    for (name, filter) in std::mem::take(&mut previous.liquid_filters) {
+        options.liquid_filters.entry(name).or_insert(filter);
+    }
+    for (name, tag) in std::mem::take(&mut previous.liquid_tags) {
+        options.liquid_tags.entry(name).or_insert(tag);
+    }
+    for (name, resolver) in std::mem::take(&mut previous.secret_resolvers) {
+        options.secret_resolvers.entry(name).or_insert(resolver);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -84,12 +275,32 @@ mod tests {
         let overrides = BuilderOptions {
             are_configurable_strings_enabled: true,
             are_configurable_values_enabled: true,
+            collect_diagnostics: false,
+            configurable_value_types: ConfigurableValueTypes::new(),
+            env_overrides_prefix: None,
+            exclude_globs: Vec::new(),
+            feature_file_parsers: FeatureFileParsers::new(),
+            include_globs: Vec::new(),
+            liquid_filters: LiquidFilters::new(),
+            liquid_tags: LiquidTags::new(),
+            non_utf8_path_policy: NonUtf8PathPolicy::Lossy,
+            overlay_mode: OverlayMode::Replace,
+            raw_file_globs: Some(vec!["override/**".to_owned()]),
             schema_path: Some(PathBuf::from("override_schema.json")),
+            secret_resolvers: SecretResolvers::new(),
+            symlink_policy: SymlinkPolicy::Skip,
             track_file_references: TrackReferenceMode::ConfigurableStrings,
         };
         let config = BuilderOptionsConfig {
             are_configurable_values_enabled: Some(false),
+            ignore: None,
+            namespace: None,
+            non_utf8_path_policy: Some(NonUtf8PathPolicy::Error),
+            overlay_mode: Some(OverlayMode::Merge),
+            raw_file_globs: Some(vec!["config/**".to_owned()]),
+            root: None,
             schema_path: Some(PathBuf::from("config_schema.json")),
+            symlink_policy: Some(SymlinkPolicy::Error),
             track_file_references: Some(TrackReferenceMode::None),
         };
 
@@ -105,6 +316,10 @@ mod tests {
             merged.track_file_references,
             TrackReferenceMode::ConfigurableStrings
         );
+        assert_eq!(merged.non_utf8_path_policy, NonUtf8PathPolicy::Lossy);
+        assert_eq!(merged.overlay_mode, OverlayMode::Replace);
+        assert_eq!(merged.raw_file_globs, Some(vec!["override/**".to_owned()]));
+        assert_eq!(merged.symlink_policy, SymlinkPolicy::Skip);
     }
 
     #[test]
@@ -113,7 +328,14 @@ mod tests {
         let overrides = BuilderOptions::default();
         let config = BuilderOptionsConfig {
             are_configurable_values_enabled: Some(true),
+            ignore: None,
+            namespace: None,
+            non_utf8_path_policy: Some(NonUtf8PathPolicy::Skip),
+            overlay_mode: Some(OverlayMode::Merge),
+            raw_file_globs: Some(vec!["config/**".to_owned()]),
+            root: None,
             schema_path: Some(PathBuf::from("config_schema.json")),
+            symlink_policy: Some(SymlinkPolicy::Skip),
             track_file_references: Some(TrackReferenceMode::ConfigurableStrings),
         };
 
@@ -129,6 +351,10 @@ mod tests {
             merged.track_file_references,
             TrackReferenceMode::ConfigurableStrings
         );
+        assert_eq!(merged.non_utf8_path_policy, NonUtf8PathPolicy::Skip);
+        assert_eq!(merged.overlay_mode, OverlayMode::Merge);
+        assert_eq!(merged.raw_file_globs, Some(vec!["config/**".to_owned()]));
+        assert_eq!(merged.symlink_policy, SymlinkPolicy::Skip);
     }
 
     #[test]
@@ -138,13 +364,16 @@ mod tests {
         let overrides = BuilderOptions {
             are_configurable_strings_enabled: false,
             are_configurable_values_enabled: false,
+            collect_diagnostics: false,
             schema_path: None,
             track_file_references: TrackReferenceMode::ConfigurableStrings,
+            ..Default::default()
         };
         let config = BuilderOptionsConfig {
             are_configurable_values_enabled: Some(true),
             schema_path: Some(PathBuf::from("config_schema.json")),
             track_file_references: None,
+            ..Default::default()
         };
 
         let merged = config.merge_with(&overrides);
@@ -197,4 +426,36 @@ mod tests {
         assert_eq!(merged.schema_path, None);
         assert_eq!(merged.track_file_references, TrackReferenceMode::None);
     }
+
+    #[test]
+    fn test_inherit_from_parent_config() {
+        let parent = BuilderOptionsConfig {
+            are_configurable_values_enabled: Some(true),
+            ignore: Some(vec!["parent.json".to_owned()]),
+            namespace: Some("parent".to_owned()),
+            root: Some(true),
+            schema_path: Some(PathBuf::from("parent_schema.json")),
+            track_file_references: Some(TrackReferenceMode::KeyName),
+            ..Default::default()
+        };
+        let config = BuilderOptionsConfig {
+            are_configurable_values_enabled: Some(false),
+            ..Default::default()
+        };
+
+        let inherited = config.inherit_from(&parent);
+
+        assert_eq!(inherited.are_configurable_values_enabled, Some(false));
+        assert_eq!(inherited.ignore, None);
+        assert_eq!(inherited.namespace, None);
+        assert_eq!(inherited.root, None);
+        assert_eq!(
+            inherited.schema_path,
+            Some(PathBuf::from("parent_schema.json"))
+        );
+        assert_eq!(
+            inherited.track_file_references,
+            Some(TrackReferenceMode::KeyName)
+        );
+    }
 }
@@ -1,24 +1,63 @@
 use crate::builder::builder_options::BuilderOptions;
+use crate::builder::feature_file_parser::FeatureFileParser;
+use crate::configurable_values::ConfigurableValueType;
+use crate::error::OptifyError;
+use crate::secret::SecretResolver;
+use liquid_core::{ParseFilter, ParseTag};
 use std::path::Path;
+use std::sync::Arc;
 
 use crate::provider::OptionsRegistry;
 
 /// Trait defining the core functionality for building an options provider.
 pub trait OptionsRegistryBuilder<T: OptionsRegistry> {
     // Adds multiple directories containing feature configurations.
-    fn add_directories(&mut self, directories: &[impl AsRef<Path>]) -> Result<&Self, String>;
+    fn add_directories(&mut self, directories: &[impl AsRef<Path>]) -> Result<&Self, OptifyError>;
 
     /// Adds a directory containing feature configurations.
-    fn add_directory(&mut self, directory: impl AsRef<Path>) -> Result<&Self, String>;
+    fn add_directory(&mut self, directory: impl AsRef<Path>) -> Result<&Self, OptifyError>;
+
+    /// Registers a type for configurable values with `"$type": value_type.type_name()`.
+    /// See `BuilderOptions::configurable_value_types`.
+    /// Must be called before `add_directory` to take effect.
+    fn with_configurable_value_type(
+        &mut self,
+        value_type: Arc<dyn ConfigurableValueType>,
+    ) -> Result<&Self, OptifyError>;
+
+    /// Registers a parser for feature files with the extension, without the leading `.`, such as `"json"`.
+    /// See `BuilderOptions::feature_file_parsers`.
+    /// Must be called before `add_directory` to take effect.
+    fn with_feature_file_parser(
+        &mut self,
+        extension: &str,
+        parser: Arc<dyn FeatureFileParser>,
+    ) -> Result<&Self, OptifyError>;
+
+    /// Registers a custom filter for the Liquid templates in configurable strings with the name in the filter's reflection.
+    /// See `BuilderOptions::liquid_filters`.
+    fn with_liquid_filter(&mut self, filter: Box<dyn ParseFilter>) -> Result<&Self, OptifyError>;
+
+    /// Registers a custom tag for the Liquid templates in configurable strings with the name in the tag's reflection.
+    /// See `BuilderOptions::liquid_tags`.
+    fn with_liquid_tag(&mut self, tag: Box<dyn ParseTag>) -> Result<&Self, OptifyError>;
 
     /// Sets builder options to configure how schemas are built.
-    fn with_options(&mut self, options: BuilderOptions) -> Result<&Self, String>;
+    fn with_options(&mut self, options: BuilderOptions) -> Result<&Self, OptifyError>;
 
     /// Sets a JSON schema for validation.
     /// When provided, files will be validated against this schema during loading when `add_directory` is called.
     /// Must be called before `add_directory` to take effect.
-    fn with_schema(&mut self, schema_path: impl AsRef<Path>) -> Result<&Self, String>;
+    fn with_schema(&mut self, schema_path: impl AsRef<Path>) -> Result<&Self, OptifyError>;
+
+    /// Registers a resolver for `Optify.Secret` values with `"resolver": name`.
+    /// See `BuilderOptions::secret_resolvers`.
+    fn with_secret_resolver(
+        &mut self,
+        name: &str,
+        resolver: Arc<dyn SecretResolver>,
+    ) -> Result<&Self, OptifyError>;
 
     /// Builds the options provider.
-    fn build(&mut self) -> Result<T, String>;
+    fn build(&mut self) -> Result<T, OptifyError>;
 }
@@ -0,0 +1,54 @@
+use std::path::Path;
+
+use crate::builder::builder_options::BuilderOptionsConfig;
+use crate::error::OptifyError;
+use crate::json::reader::read_json_from_file_as;
+
+/// Reads `.optify/config.json` in the directory, if it exists.
+pub(crate) fn read_directory_config(
+    directory: &Path,
+) -> Result<Option<BuilderOptionsConfig>, OptifyError> {
+    let config_folder = directory.join(".optify");
+    let config_path = config_folder.join("config.json");
+    if !config_path.is_file() {
+        return Ok(None);
+    }
+    let mut config = read_json_from_file_as::<BuilderOptionsConfig>(&config_path).map_err(|e| {
+        OptifyError::BuilderConfig {
+            path: config_path.display().to_string(),
+            message: e.to_string(),
+        }
+    })?;
+    if let Some(schema_path) = &config.schema_path {
+        if schema_path.is_relative() {
+            config.schema_path = Some(config_folder.join(schema_path));
+        }
+    }
+    Ok(Some(config))
+}
+
+/// Gets the configuration for a directory that is added to a builder.
+/// Parent directories are searched for `.optify/config.json` files until one has `"root": true`,
+/// like `.editorconfig` files.
+/// Values from nearer configuration files win.
+pub(crate) fn load_directory_config(directory: &Path) -> Result<BuilderOptionsConfig, OptifyError> {
+    let mut config = read_directory_config(directory)?.unwrap_or_default();
+    if config.root == Some(true) {
+        return Ok(config);
+    }
+
+    let directory = dunce::canonicalize(directory).map_err(|e| OptifyError::Io {
+        path: directory.display().to_string(),
+        message: e.to_string(),
+    })?;
+    for ancestor in directory.ancestors().skip(1) {
+        if let Some(parent_config) = read_directory_config(ancestor)? {
+            let is_root = parent_config.root == Some(true);
+            config = config.inherit_from(&parent_config);
+            if is_root {
+                break;
+            }
+        }
+    }
+    Ok(config)
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::panic::RefUnwindSafe;
+use std::sync::Arc;
+
+use crate::json::jsonc::strip_jsonc;
+
+/// Parses the contents of feature files into JSON.
+/// Parsers are registered on the builder for file extensions with `with_feature_file_parser`.
+/// Parsers must be `RefUnwindSafe` so that providers can still be used after a panic is caught, such as in bindings for other languages.
+pub trait FeatureFileParser: RefUnwindSafe + Send + Sync {
+    /// Parses the contents of a file.
+    /// Returns a message describing the problem when the contents are not valid.
+    fn parse(&self, contents: &str) -> Result<serde_json::Value, String>;
+}
+
+/// Parsers mapped to the file extensions, without the leading `.`, of the files that they parse.
+pub type FeatureFileParsers = HashMap<String, Arc<dyn FeatureFileParser>>;
+
+/// Parses JSON that may have comments and trailing commas (JSONC).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsoncFeatureFileParser;
+
+impl FeatureFileParser for JsoncFeatureFileParser {
+    fn parse(&self, contents: &str) -> Result<serde_json::Value, String> {
+        serde_json::from_str(&strip_jsonc(contents)).map_err(|e| e.to_string())
+    }
+}
+
+/// Parses YAML.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct YamlFeatureFileParser;
+
+impl FeatureFileParser for YamlFeatureFileParser {
+    fn parse(&self, contents: &str) -> Result<serde_json::Value, String> {
+        parse_with_config(contents, config::FileFormat::Yaml)
+    }
+}
+
+/// Parses TOML.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TomlFeatureFileParser;
+
+impl FeatureFileParser for TomlFeatureFileParser {
+    fn parse(&self, contents: &str) -> Result<serde_json::Value, String> {
+        parse_with_config(contents, config::FileFormat::Toml)
+    }
+}
+
+/// Parses JSON5.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Json5FeatureFileParser;
+
+impl FeatureFileParser for Json5FeatureFileParser {
+    fn parse(&self, contents: &str) -> Result<serde_json::Value, String> {
+        parse_with_config(contents, config::FileFormat::Json5)
+    }
+}
+
+/// Parses INI files.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IniFeatureFileParser;
+
+impl FeatureFileParser for IniFeatureFileParser {
+    fn parse(&self, contents: &str) -> Result<serde_json::Value, String> {
+        parse_with_config(contents, config::FileFormat::Ini)
+    }
+}
+
+/// Parses RON (Rusty Object Notation).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RonFeatureFileParser;
+
+impl FeatureFileParser for RonFeatureFileParser {
+    fn parse(&self, contents: &str) -> Result<serde_json::Value, String> {
+        parse_with_config(contents, config::FileFormat::Ron)
+    }
+}
+
+fn parse_with_config(
+    contents: &str,
+    format: config::FileFormat,
+) -> Result<serde_json::Value, String> {
+    config::Config::builder()
+        .add_source(config::File::from_str(contents, format))
+        .build()
+        .map_err(|e| e.to_string())?
+        .try_deserialize()
+        .map_err(|e| e.to_string())
+}
+
+/// Gets the built-in parsers with the parsers registered in the builder options.
+/// Registered parsers replace built-in parsers for the same extension.
+pub(crate) fn get_feature_file_parsers(registered: &FeatureFileParsers) -> FeatureFileParsers {
+    let built_in: [(&str, Arc<dyn FeatureFileParser>); 8] = [
+        ("ini", Arc::new(IniFeatureFileParser)),
+        ("json", Arc::new(JsoncFeatureFileParser)),
+        ("json5", Arc::new(Json5FeatureFileParser)),
+        ("jsonc", Arc::new(JsoncFeatureFileParser)),
+        ("ron", Arc::new(RonFeatureFileParser)),
+        ("toml", Arc::new(TomlFeatureFileParser)),
+        ("yaml", Arc::new(YamlFeatureFileParser)),
+        ("yml", Arc::new(YamlFeatureFileParser)),
+    ];
+    let mut parsers: FeatureFileParsers = built_in
+        .into_iter()
+        .map(|(extension, parser)| (extension.to_owned(), parser))
+        .collect();
+    for (extension, parser) in registered {
+        parsers.insert(extension.clone(), parser.clone());
+    }
+    parsers
+}
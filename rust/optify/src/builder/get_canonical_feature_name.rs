@@ -1,12 +1,11 @@
 use std::path::Path;
 
-pub(super) fn get_canonical_feature_name(path: &Path, directory: &Path) -> String {
-    path.strip_prefix(directory)
-        .unwrap()
+/// `relative_path` is the path of the file relative to the directory that was added, with `/` as the separator.
+pub(super) fn get_canonical_feature_name(relative_path: &str) -> String {
+    Path::new(relative_path)
         .with_extension("")
-        .to_str()
-        .expect("path should be valid Unicode")
-        .replace(std::path::MAIN_SEPARATOR, "/")
+        .to_string_lossy()
+        .into_owned()
 }
 
 #[cfg(test)]
@@ -15,11 +14,9 @@ mod tests {
 
     #[test]
     fn test_get_canonical_feature_name() {
-        let directory = std::path::Path::new("wtv");
-        let path = directory.join("dir1").join("dir2").join("feature_B.json");
         assert_eq!(
             "dir1/dir2/feature_B",
-            get_canonical_feature_name(&path, directory)
+            get_canonical_feature_name("dir1/dir2/feature_B.json")
         );
     }
 }
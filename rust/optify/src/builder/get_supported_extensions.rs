@@ -1,16 +1,8 @@
-use config::FileStoredFormat;
 use std::collections::HashSet;
 
-pub(super) fn get_supported_extensions() -> HashSet<&'static str> {
-    [
-        config::FileFormat::Ini.file_extensions(),
-        config::FileFormat::Json.file_extensions(),
-        config::FileFormat::Json5.file_extensions(),
-        config::FileFormat::Ron.file_extensions(),
-        config::FileFormat::Toml.file_extensions(),
-        config::FileFormat::Yaml.file_extensions(),
-    ]
-    .iter()
-    .flat_map(|exts| exts.iter().copied())
-    .collect()
+use crate::builder::feature_file_parser::FeatureFileParsers;
+
+/// Gets the extensions of feature files from the parsers that can parse them.
+pub(super) fn get_supported_extensions(parsers: &FeatureFileParsers) -> HashSet<&str> {
+    parsers.keys().map(|extension| extension.as_str()).collect()
 }
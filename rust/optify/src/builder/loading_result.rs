@@ -1,4 +1,5 @@
 use crate::{
+    builder::builder_options::OverlayMode,
     configurable_values::locator::ConfigurableValuePointers,
     provider::SourceValue,
     schema::{conditions::ConditionExpression, metadata::OptionsMetadata},
@@ -20,6 +21,10 @@ pub(crate) struct FeatureLoadingResult {
     pub configurable_value_pointers: ConfigurableValuePointers,
     pub imports: Option<Vec<String>>,
     pub metadata: OptionsMetadata,
+    /// How the feature overrides a feature with the same canonical feature name that was already added.
+    pub overlay_mode: OverlayMode,
+    /// The prefix of the namespace of the feature, such as `"payments/"`, or an empty string.
+    pub prefix: String,
     pub source: SourceValue,
 }
 
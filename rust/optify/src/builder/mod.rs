@@ -1,14 +1,20 @@
+pub(crate) mod build_report;
 pub(crate) mod builder_impl;
 pub(crate) mod builder_options;
 pub(crate) mod builder_trait;
+mod directory_config;
 mod extract_configurable_string_files_from_config;
 mod extract_files_from_config;
+pub(crate) mod feature_file_parser;
 mod get_canonical_feature_name;
 mod get_supported_extensions;
 pub(crate) mod loading_result;
+mod path_filter;
 pub(crate) mod watcher_builder;
 
+pub use build_report::*;
 pub use builder_impl::*;
 pub use builder_options::*;
 pub use builder_trait::*;
+pub use feature_file_parser::*;
 pub use watcher_builder::*;
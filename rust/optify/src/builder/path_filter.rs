@@ -0,0 +1,147 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::path::{Path, PathBuf};
+
+use crate::builder::builder_options::BuilderOptions;
+use crate::error::OptifyError;
+
+/// The name of the files with gitignore-style patterns for files to ignore in the directory that has the file.
+pub(crate) const IGNORE_FILE_NAME: &str = ".optifyignore";
+
+/// Decides which files in a directory are loaded based on the globs in the builder options.
+pub(crate) struct PathFilter {
+    exclude_globs: Option<GlobSet>,
+    include_globs: Option<GlobSet>,
+    raw_file_globs: Option<GlobSet>,
+}
+
+impl PathFilter {
+    pub(crate) fn new(builder_options: &BuilderOptions) -> Result<Self, OptifyError> {
+        Ok(Self {
+            exclude_globs: build_glob_set(&builder_options.exclude_globs)?,
+            include_globs: build_glob_set(&builder_options.include_globs)?,
+            raw_file_globs: match &builder_options.raw_file_globs {
+                Some(globs) => Some(build_glob_set(globs)?.unwrap_or_else(GlobSet::empty)),
+                None => None,
+            },
+        })
+    }
+
+    /// Indicates if a file should be loaded based on the include and exclude globs.
+    /// `relative_path` uses `/` as the separator.
+    pub(crate) fn is_file_included(&self, relative_path: &str) -> bool {
+        if let Some(exclude_globs) = &self.exclude_globs {
+            if exclude_globs.is_match(relative_path) {
+                return false;
+            }
+        }
+        match &self.include_globs {
+            Some(include_globs) => include_globs.is_match(relative_path),
+            None => true,
+        }
+    }
+
+    /// Indicates if a file that is not a feature file should be loaded.
+    /// `relative_path` uses `/` as the separator.
+    pub(crate) fn is_raw_file_allowed(&self, relative_path: &str) -> bool {
+        match &self.raw_file_globs {
+            Some(raw_file_globs) => raw_file_globs.is_match(relative_path),
+            None => true,
+        }
+    }
+}
+
+/// The gitignore-style patterns for the directories that contain the entry that is being visited while walking a directory.
+/// Like `.gitignore` files, patterns are relative to the directory that has them
+/// and patterns in nearer directories win.
+pub(crate) struct IgnoreStack {
+    ignores: Vec<(PathBuf, Gitignore)>,
+}
+
+impl IgnoreStack {
+    pub(crate) fn new() -> Self {
+        Self {
+            ignores: Vec::new(),
+        }
+    }
+
+    /// Adds the patterns from the `.optifyignore` file in the directory and the `ignore` patterns from the directory's configuration.
+    pub(crate) fn push(
+        &mut self,
+        directory: &Path,
+        ignore_patterns: &[String],
+    ) -> Result<(), OptifyError> {
+        let mut ignore_builder = GitignoreBuilder::new(directory);
+        let ignore_file_path = directory.join(IGNORE_FILE_NAME);
+        if ignore_file_path.is_file() {
+            if let Some(e) = ignore_builder.add(&ignore_file_path) {
+                return Err(OptifyError::BuilderConfig {
+                    path: ignore_file_path.display().to_string(),
+                    message: e.to_string(),
+                });
+            }
+        }
+        let config_path = directory.join(".optify").join("config.json");
+        for pattern in ignore_patterns {
+            ignore_builder
+                .add_line(Some(config_path.clone()), pattern)
+                .map_err(|e| OptifyError::BuilderConfig {
+                    path: config_path.display().to_string(),
+                    message: e.to_string(),
+                })?;
+        }
+        let ignore = ignore_builder
+            .build()
+            .map_err(|e| OptifyError::BuilderConfig {
+                path: directory.display().to_string(),
+                message: e.to_string(),
+            })?;
+        if !ignore.is_empty() {
+            self.ignores.push((directory.to_path_buf(), ignore));
+        }
+        Ok(())
+    }
+
+    /// Removes the patterns for the directories that do not contain `path`.
+    /// Directories are walked depth-first, so they are not visited again.
+    pub(crate) fn leave_directories(&mut self, path: &Path) {
+        while self
+            .ignores
+            .last()
+            .is_some_and(|(directory, _)| !path.starts_with(directory))
+        {
+            self.ignores.pop();
+        }
+    }
+
+    /// Indicates if a file or a directory and everything in it should not be loaded because of the gitignore-style patterns.
+    pub(crate) fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        for (_, ignore) in self.ignores.iter().rev() {
+            match ignore.matched(path, is_dir) {
+                Match::None => {}
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+            }
+        }
+        false
+    }
+}
+
+/// Builds a set from the globs or `None` when there are no globs.
+fn build_glob_set(globs: &[String]) -> Result<Option<GlobSet>, OptifyError> {
+    if globs.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob).map_err(|e| OptifyError::Glob {
+            pattern: glob.clone(),
+            message: e.to_string(),
+        })?);
+    }
+    builder.build().map(Some).map_err(|e| OptifyError::Glob {
+        pattern: globs.join(", "),
+        message: e.to_string(),
+    })
+}
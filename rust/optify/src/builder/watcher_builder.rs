@@ -1,7 +1,13 @@
+use liquid_core::{ParseFilter, ParseTag};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use crate::builder::builder_options::BuilderOptions;
+use crate::builder::builder_options::{keep_registrations, BuilderOptions};
+use crate::builder::feature_file_parser::FeatureFileParser;
+use crate::configurable_values::ConfigurableValueType;
+use crate::error::OptifyError;
 use crate::provider::{OptionsWatcher, WatcherOptions};
+use crate::secret::SecretResolver;
 
 use super::OptionsRegistryBuilder;
 
@@ -37,7 +43,7 @@ impl OptionsWatcherBuilder {
 }
 
 impl OptionsRegistryBuilder<OptionsWatcher> for OptionsWatcherBuilder {
-    fn add_directories(&mut self, directories: &[impl AsRef<Path>]) -> Result<&Self, String> {
+    fn add_directories(&mut self, directories: &[impl AsRef<Path>]) -> Result<&Self, OptifyError> {
         for directory in directories {
             self.watched_directories
                 .push(directory.as_ref().to_path_buf());
@@ -46,23 +52,70 @@ impl OptionsRegistryBuilder<OptionsWatcher> for OptionsWatcherBuilder {
     }
 
     /// Add a directory to watch for changes.
-    fn add_directory(&mut self, directory: impl AsRef<Path>) -> Result<&Self, String> {
+    fn add_directory(&mut self, directory: impl AsRef<Path>) -> Result<&Self, OptifyError> {
         self.watched_directories
             .push(directory.as_ref().to_path_buf());
         Ok(self)
     }
 
-    fn with_options(&mut self, options: BuilderOptions) -> Result<&Self, String> {
+    fn with_configurable_value_type(
+        &mut self,
+        value_type: Arc<dyn ConfigurableValueType>,
+    ) -> Result<&Self, OptifyError> {
+        self.builder_options
+            .configurable_value_types
+            .insert(value_type.type_name().to_owned(), value_type);
+        Ok(self)
+    }
+
+    fn with_feature_file_parser(
+        &mut self,
+        extension: &str,
+        parser: Arc<dyn FeatureFileParser>,
+    ) -> Result<&Self, OptifyError> {
+        self.builder_options
+            .feature_file_parsers
+            .insert(extension.to_owned(), parser);
+        Ok(self)
+    }
+
+    fn with_liquid_filter(&mut self, filter: Box<dyn ParseFilter>) -> Result<&Self, OptifyError> {
+        self.builder_options
+            .liquid_filters
+            .insert(filter.reflection().name().to_owned(), filter);
+        Ok(self)
+    }
+
+    fn with_liquid_tag(&mut self, tag: Box<dyn ParseTag>) -> Result<&Self, OptifyError> {
+        self.builder_options
+            .liquid_tags
+            .insert(tag.reflection().tag().to_owned(), tag);
+        Ok(self)
+    }
+
+    fn with_options(&mut self, mut options: BuilderOptions) -> Result<&Self, OptifyError> {
+        keep_registrations(&mut options, &mut self.builder_options);
         self.builder_options = options;
         Ok(self)
     }
 
-    fn with_schema(&mut self, schema_path: impl AsRef<Path>) -> Result<&Self, String> {
+    fn with_schema(&mut self, schema_path: impl AsRef<Path>) -> Result<&Self, OptifyError> {
         self.builder_options.schema_path = Some(schema_path.as_ref().to_path_buf());
         Ok(self)
     }
 
-    fn build(&mut self) -> Result<OptionsWatcher, String> {
+    fn with_secret_resolver(
+        &mut self,
+        name: &str,
+        resolver: Arc<dyn SecretResolver>,
+    ) -> Result<&Self, OptifyError> {
+        self.builder_options
+            .secret_resolvers
+            .insert(name.to_owned(), resolver);
+        Ok(self)
+    }
+
+    fn build(&mut self) -> Result<OptionsWatcher, OptifyError> {
         OptionsWatcher::new(
             &self.watched_directories,
             self.watcher_options.clone(),
@@ -1,8 +1,13 @@
 use liquid::ObjectView;
 use liquid::ValueView;
 use serde::Deserialize;
-use std::cell::RefCell;
+use std::cell::{OnceCell, RefCell};
 use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::configurable_string::liquid_templates::{
+    LiquidTemplates, CONSTRAINTS_SCOPE, OPTIONS_SCOPE,
+};
 
 #[derive(Deserialize, Debug)]
 #[serde(untagged)]
@@ -29,6 +34,59 @@ pub struct ConfigurableString {
 /// Mapping from relative file paths to their contents.
 pub type LoadedFiles = HashMap<String, String>;
 
+/// Gets the merged options for the `options` scope.
+pub(crate) type GetOptionsForScope<'a> = dyn Fn() -> Result<serde_json::Value, String> + 'a;
+
+/// Values that templates can read in addition to the arguments, such as `{{ constraints.locale }}`.
+/// An argument with the same name as a scope hides the scope.
+/// Values are only converted for Liquid when a template reads them.
+#[derive(Default)]
+pub(crate) struct TemplateScopes<'a> {
+    constraints: Option<&'a serde_json::Value>,
+    get_options: Option<&'a GetOptionsForScope<'a>>,
+    constraints_value: OnceCell<Result<liquid::model::Value, String>>,
+    options_value: OnceCell<Result<liquid::model::Value, String>>,
+}
+
+impl<'a> TemplateScopes<'a> {
+    pub fn new(
+        constraints: Option<&'a serde_json::Value>,
+        get_options: &'a GetOptionsForScope<'a>,
+    ) -> Self {
+        Self {
+            constraints,
+            get_options: Some(get_options),
+            ..Default::default()
+        }
+    }
+
+    fn is_scope(name: &str) -> bool {
+        name == CONSTRAINTS_SCOPE || name == OPTIONS_SCOPE
+    }
+
+    /// Gets the value for the scope or `None` if `name` is not the name of a scope.
+    fn get(&self, name: &str) -> Option<Result<&liquid::model::Value, String>> {
+        let value = match name {
+            CONSTRAINTS_SCOPE => self.constraints_value.get_or_init(|| {
+                // Report missing constraints the same way when no constraints were given.
+                let empty = serde_json::Value::Object(serde_json::Map::new());
+                to_liquid_value(self.constraints.unwrap_or(&empty))
+            }),
+            OPTIONS_SCOPE => self.options_value.get_or_init(|| match self.get_options {
+                Some(get_options) => get_options().and_then(|options| to_liquid_value(&options)),
+                None => Ok(liquid::model::Value::Object(liquid::model::Object::new())),
+            }),
+            _ => return None,
+        };
+        Some(value.as_ref().map_err(|e| e.clone()))
+    }
+}
+
+fn to_liquid_value(value: &serde_json::Value) -> Result<liquid::model::Value, String> {
+    liquid::model::to_value(value)
+        .map_err(|e| format!("Failed to convert the value for Liquid: {e}"))
+}
+
 /// Dynamic object that resolves values on demand.
 struct DynamicArguments<'a> {
     // Cache resolved values so that we can return a reference to the value.
@@ -38,18 +96,25 @@ struct DynamicArguments<'a> {
     cache: RefCell<HashMap<String, liquid::model::Value>>,
     errors: RefCell<Vec<String>>,
     files: &'a LoadedFiles,
-    parser: liquid::Parser,
+    templates: &'a LiquidTemplates,
     arguments: &'a HashMap<String, ReplacementValue>,
+    scopes: &'a TemplateScopes<'a>,
 }
 
 impl<'a> DynamicArguments<'a> {
-    fn new(arguments: &'a HashMap<String, ReplacementValue>, files: &'a LoadedFiles) -> Self {
+    fn new(
+        arguments: &'a HashMap<String, ReplacementValue>,
+        files: &'a LoadedFiles,
+        templates: &'a LiquidTemplates,
+        scopes: &'a TemplateScopes<'a>,
+    ) -> Self {
         Self {
             cache: RefCell::new(HashMap::new()),
             errors: RefCell::new(Vec::new()),
             files,
-            parser: liquid::ParserBuilder::with_stdlib().build().unwrap(),
+            templates,
             arguments,
+            scopes,
         }
     }
 
@@ -83,7 +148,7 @@ impl<'a> DynamicArguments<'a> {
     }
 
     fn render_liquid(&self, liquid: &str) -> Option<String> {
-        match self.parser.parse(liquid) {
+        match self.templates.get(liquid) {
             Ok(template) => match template.render(self) {
                 Ok(result) => Some(result),
                 Err(e) => {
@@ -157,10 +222,20 @@ impl<'a> ObjectView for DynamicArguments<'a> {
     }
 
     fn contains_key(&self, index: &str) -> bool {
-        self.arguments.contains_key(index)
+        self.arguments.contains_key(index) || TemplateScopes::is_scope(index)
     }
 
     fn get<'s>(&'s self, index: &str) -> Option<&'s dyn ValueView> {
+        if !self.arguments.contains_key(index) {
+            return match self.scopes.get(index)? {
+                Ok(value) => Some(value as &dyn ValueView),
+                Err(e) => {
+                    self.errors.borrow_mut().push(e);
+                    None
+                }
+            };
+        }
+
         // Ensure the value is cached
         self.ensure_cached(index);
 
@@ -244,10 +319,14 @@ impl ConfigurableString {
         &self,
         value: &ReplacementValue,
         files: &LoadedFiles,
+        templates: &LiquidTemplates,
+        scopes: &TemplateScopes,
     ) -> Result<String, String> {
         match value {
             ReplacementValue::String(s) => Ok(s.into()),
-            ReplacementValue::Object(obj) => self.process_replacement_object(obj, files),
+            ReplacementValue::Object(obj) => {
+                self.process_replacement_object(obj, files, templates, scopes)
+            }
         }
     }
 
@@ -256,6 +335,8 @@ impl ConfigurableString {
         &self,
         obj: &ReplacementObject,
         files: &LoadedFiles,
+        templates: &LiquidTemplates,
+        scopes: &TemplateScopes,
     ) -> Result<String, String> {
         match obj {
             ReplacementObject::File { file } => {
@@ -263,7 +344,7 @@ impl ConfigurableString {
                     Some(contents) => {
                         if file.ends_with(".liquid") {
                             // File contains a liquid template, render it
-                            self.render_liquid_template(contents, files)
+                            self.render_liquid_template(contents, files, templates, scopes)
                         } else {
                             // Plain file, return contents as-is
                             Ok(contents.clone())
@@ -272,7 +353,9 @@ impl ConfigurableString {
                     None => Err(format!("File '{}' not found.", file)),
                 }
             }
-            ReplacementObject::Liquid { liquid } => self.render_liquid_template(liquid, files),
+            ReplacementObject::Liquid { liquid } => {
+                self.render_liquid_template(liquid, files, templates, scopes)
+            }
         }
     }
 
@@ -281,13 +364,11 @@ impl ConfigurableString {
         &self,
         template_str: &str,
         files: &LoadedFiles,
+        templates: &LiquidTemplates,
+        scopes: &TemplateScopes,
     ) -> Result<String, String> {
-        let parser = liquid::ParserBuilder::with_stdlib()
-            .build()
-            .map_err(|e| format!("Failed to build liquid parser: {}", e))?;
-
-        let template = parser
-            .parse(template_str)
+        let template = templates
+            .get(template_str)
             .map_err(|e| format!("Failed to parse template: {}", e))?;
 
         let empty_context;
@@ -298,7 +379,7 @@ impl ConfigurableString {
                 &empty_context
             }
         };
-        let dynamic_arguments = DynamicArguments::new(context, files);
+        let dynamic_arguments = DynamicArguments::new(context, files, templates, scopes);
 
         let result = template
             .render(&dynamic_arguments)
@@ -316,8 +397,27 @@ impl ConfigurableString {
         }
     }
 
+    /// Builds the string with the standard library for Liquid.
+    /// Custom filters and tags are only used when the string is built by a provider that registered them.
     pub fn build(&self, files: &LoadedFiles) -> Result<String, String> {
-        self.process_replacement_value(&self.base, files)
+        // Creating a parser is slow, so the default templates are shared.
+        static DEFAULT_TEMPLATES: OnceLock<LiquidTemplates> = OnceLock::new();
+        self.build_with_templates(
+            files,
+            DEFAULT_TEMPLATES.get_or_init(LiquidTemplates::new),
+            &TemplateScopes::default(),
+        )
+    }
+
+    /// Builds the string with the Liquid templates that were parsed when building the provider
+    /// and the scopes for the options that are being retrieved.
+    pub(crate) fn build_with_templates(
+        &self,
+        files: &LoadedFiles,
+        templates: &LiquidTemplates,
+        scopes: &TemplateScopes,
+    ) -> Result<String, String> {
+        self.process_replacement_value(&self.base, files, templates, scopes)
     }
 
     /// Finds all directly referenced files.
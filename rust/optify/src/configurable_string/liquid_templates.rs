@@ -0,0 +1,305 @@
+use std::collections::{BTreeSet, HashMap};
+use std::panic::RefUnwindSafe;
+use std::sync::Arc;
+
+use liquid_core::{ParseFilter, ParseTag};
+use serde::Deserialize;
+
+use crate::configurable_string::{LoadedFiles, ReplacementObject, ReplacementValue};
+use crate::configurable_values::locator::{STRING_TYPE, TYPE_KEY};
+use crate::error::OptifyError;
+use crate::provider::constraints::Constraints;
+use crate::provider::{Features, Sources};
+
+/// The name that templates use to read the constraints in the preferences.
+pub(crate) const CONSTRAINTS_SCOPE: &str = "constraints";
+
+/// The name that templates use to read the merged options.
+pub(crate) const OPTIONS_SCOPE: &str = "options";
+
+/// Custom Liquid filters mapped to their names.
+pub type LiquidFilters = HashMap<String, Box<dyn ParseFilter>>;
+
+/// Custom Liquid tags mapped to their names.
+pub type LiquidTags = HashMap<String, Box<dyn ParseTag>>;
+
+/// The constraints that Liquid templates read.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) enum ConstraintReads {
+    #[default]
+    None,
+    /// Only these top-level constraints are read.
+    Names(BTreeSet<String>),
+    All,
+}
+
+impl ConstraintReads {
+    /// Adds the constraints that the template reads, such as `locale` for `{{ constraints.locale }}`.
+    /// This does not parse the template, so names that only look like reads of constraints are also added.
+    /// When the template could read any constraint, such as with `{% for c in constraints %}`, all constraints are read.
+    fn add_reads(&mut self, source: &str) {
+        let is_name_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+        for (index, _) in source.match_indices(CONSTRAINTS_SCOPE) {
+            // Skip other names that contain the scope's name, such as `options.constraints` or `my_constraints`.
+            if source[..index]
+                .chars()
+                .next_back()
+                .is_some_and(|c| is_name_char(c) || c == '.')
+            {
+                continue;
+            }
+            let rest = &source[index + CONSTRAINTS_SCOPE.len()..];
+            if rest.starts_with(is_name_char) {
+                continue;
+            }
+            let name: String = rest
+                .strip_prefix('.')
+                .map(|rest| rest.chars().take_while(|c| is_name_char(*c)).collect())
+                .unwrap_or_default();
+            if name.is_empty() {
+                *self = Self::All;
+                return;
+            }
+            match self {
+                Self::None => *self = Self::Names(BTreeSet::from([name])),
+                Self::Names(names) => {
+                    names.insert(name);
+                }
+                Self::All => return,
+            }
+        }
+    }
+
+    /// Selects the constraints that are read from `constraints`.
+    pub fn select(&self, constraints: &Constraints) -> Option<Constraints> {
+        match self {
+            Self::None => None,
+            Self::Names(names) => {
+                let selected = names
+                    .iter()
+                    .filter_map(|name| {
+                        constraints
+                            .constraints
+                            .get(name)
+                            .map(|value| (name.clone(), value.clone()))
+                    })
+                    .collect();
+                Some(Constraints {
+                    constraints: serde_json::Value::Object(selected),
+                })
+            }
+            Self::All => Some(constraints.clone()),
+        }
+    }
+}
+
+/// Liquid templates for configurable strings mapped to their sources.
+/// Templates are parsed when building so that they are not parsed again each time that options are retrieved.
+/// Templates that were not parsed when building, such as templates in overrides, are parsed when they are rendered.
+#[derive(Clone)]
+pub(crate) struct LiquidTemplates {
+    /// The constraints that the parsed templates read.
+    constraint_reads: ConstraintReads,
+    parser: liquid::Parser,
+    templates: HashMap<String, Arc<liquid::Template>>,
+}
+
+// Templates are not changed after they are parsed,
+// so providers can still be used after a panic is caught, such as in bindings for other languages.
+impl RefUnwindSafe for LiquidTemplates {}
+
+impl Default for LiquidTemplates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LiquidTemplates {
+    pub fn new() -> Self {
+        Self::with_filters_and_tags(&LiquidFilters::new(), &LiquidTags::new())
+    }
+
+    /// Creates templates that are parsed with the standard library for Liquid and the custom filters and tags.
+    /// Custom filters and tags replace the ones in the standard library with the same names.
+    pub fn with_filters_and_tags(filters: &LiquidFilters, tags: &LiquidTags) -> Self {
+        let mut parser_builder = liquid::ParserBuilder::with_stdlib();
+        for filter in filters.values() {
+            parser_builder = parser_builder.filter(filter.clone());
+        }
+        for tag in tags.values() {
+            parser_builder = parser_builder.tag(tag.clone());
+        }
+        Self {
+            constraint_reads: ConstraintReads::None,
+            parser: parser_builder
+                .build()
+                .expect("the parser for Liquid should be valid"),
+            templates: HashMap::new(),
+        }
+    }
+
+    /// Parses the template and keeps it for rendering, unless it was already parsed.
+    pub fn compile(&mut self, source: &str) -> Result<(), liquid::Error> {
+        if !self.templates.contains_key(source) {
+            let template = self.parser.parse(source)?;
+            self.constraint_reads.add_reads(source);
+            self.templates.insert(source.to_owned(), Arc::new(template));
+        }
+        Ok(())
+    }
+
+    /// The constraints that the templates that were parsed when building read.
+    pub fn constraint_reads(&self) -> &ConstraintReads {
+        &self.constraint_reads
+    }
+
+    /// Gets the template that was parsed when building or parses the template.
+    pub fn get(&self, source: &str) -> Result<Arc<liquid::Template>, liquid::Error> {
+        match self.templates.get(source) {
+            Some(template) => Ok(template.clone()),
+            None => self.parser.parse(source).map(Arc::new),
+        }
+    }
+}
+
+/// Parses the Liquid templates of the configurable strings in each feature and the `.liquid` files that they use.
+/// The options from each feature's own file are used so that errors are reported for the feature that has the template.
+/// `pointers` are the pointers to configurable strings in all of the features.
+pub(crate) fn compile_liquid_templates<'a>(
+    templates: &mut LiquidTemplates,
+    pointers: impl IntoIterator<Item = &'a String>,
+    sources: &Sources,
+    sources_without_imports: &Sources,
+    features: &Features,
+    loaded_files: &LoadedFiles,
+) -> Vec<OptifyError> {
+    let mut problems = Vec::new();
+    // Sort to report problems in a consistent order.
+    let mut canonical_feature_names: Vec<&String> = sources.keys().collect();
+    canonical_feature_names.sort_unstable();
+    let mut sorted_pointers: Vec<&String> = pointers.into_iter().collect();
+    sorted_pointers.sort_unstable();
+
+    for canonical_feature_name in canonical_feature_names {
+        let source = &sources[canonical_feature_name];
+        let own_source = sources_without_imports
+            .get(canonical_feature_name)
+            .unwrap_or(source);
+        for pointer in &sorted_pointers {
+            // The type may only be set by an import when the feature only overrides arguments.
+            let is_configurable_string = source
+                .pointer(pointer)
+                .and_then(|value| value.get(TYPE_KEY))
+                .and_then(|type_value| type_value.as_str())
+                == Some(STRING_TYPE);
+            let Some(value) = own_source
+                .pointer(pointer)
+                .filter(|_| is_configurable_string)
+            else {
+                continue;
+            };
+            let arguments = value
+                .get("arguments")
+                .and_then(|arguments| arguments.as_object())
+                .into_iter()
+                .flat_map(|arguments| arguments.values());
+            for replacement in value.get("base").into_iter().chain(arguments) {
+                let Ok(ReplacementValue::Object(replacement)) =
+                    ReplacementValue::deserialize(replacement)
+                else {
+                    continue;
+                };
+                let error = match replacement {
+                    ReplacementObject::Liquid { liquid } => {
+                        templates.compile(&liquid).err().map(|e| {
+                            let file = features
+                                .get(canonical_feature_name)
+                                .and_then(|metadata| metadata.path.clone())
+                                .unwrap_or_else(|| canonical_feature_name.clone());
+                            OptifyError::LiquidTemplate {
+                                file,
+                                pointer: Some(pointer.to_string()),
+                                message: e.to_string(),
+                            }
+                        })
+                    }
+                    // Missing files are reported when the configurable string is built.
+                    ReplacementObject::File { file } if file.ends_with(".liquid") => loaded_files
+                        .get(&file)
+                        .and_then(|contents| templates.compile(contents).err())
+                        .map(|e| OptifyError::LiquidTemplate {
+                            file,
+                            pointer: None,
+                            message: e.to_string(),
+                        }),
+                    ReplacementObject::File { .. } => None,
+                };
+                // A file can be used by many features.
+                if let Some(error) = error.filter(|error| !problems.contains(error)) {
+                    problems.push(error);
+                }
+            }
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_reads(sources: &[&str]) -> ConstraintReads {
+        let mut reads = ConstraintReads::default();
+        for source in sources {
+            reads.add_reads(source);
+        }
+        reads
+    }
+
+    #[test]
+    fn test_constraint_reads() {
+        assert_eq!(
+            get_reads(&[
+                "Hello, {{ name }}!",
+                "{{ options.constraints }} {{ my_constraints }}"
+            ]),
+            ConstraintReads::None
+        );
+        assert_eq!(
+            get_reads(&[
+                "{{ constraints.locale | upcase }}",
+                "{% if constraints.tenant-id == 1 %}{{ constraints.locale }}{% endif %}",
+            ]),
+            ConstraintReads::Names(BTreeSet::from([
+                "locale".to_owned(),
+                "tenant-id".to_owned()
+            ]))
+        );
+        assert_eq!(
+            get_reads(&[
+                "{{ constraints.locale }}",
+                "{% for c in constraints %}{% endfor %}"
+            ]),
+            ConstraintReads::All
+        );
+        assert_eq!(
+            get_reads(&["{{ constraints['locale'] }}"]),
+            ConstraintReads::All
+        );
+    }
+
+    #[test]
+    fn test_select_constraints() {
+        let constraints = Constraints {
+            constraints: serde_json::json!({"locale": "en", "tenant": 1, "other": true}),
+        };
+        let reads =
+            ConstraintReads::Names(BTreeSet::from(["locale".to_owned(), "missing".to_owned()]));
+        assert_eq!(
+            reads.select(&constraints).unwrap().constraints,
+            serde_json::json!({"locale": "en"})
+        );
+        assert_eq!(ConstraintReads::None.select(&constraints), None);
+        assert_eq!(ConstraintReads::All.select(&constraints), Some(constraints));
+    }
+}
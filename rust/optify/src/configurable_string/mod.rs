@@ -1,6 +1,8 @@
 pub mod configurable_string_impl;
+pub(crate) mod liquid_templates;
 
 // Re-export the main types for easier access
 pub use configurable_string_impl::{
     ConfigurableString, LoadedFiles, ReplacementObject, ReplacementValue,
 };
+pub use liquid_templates::{LiquidFilters, LiquidTags};
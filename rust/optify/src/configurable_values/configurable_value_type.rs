@@ -0,0 +1,99 @@
+use std::collections::{HashMap, HashSet};
+use std::panic::RefUnwindSafe;
+use std::sync::Arc;
+
+use crate::configurable_values::locator::TYPE_KEY;
+use crate::error::OptifyError;
+use crate::provider::Sources;
+
+/// A kind of configurable value with a `"$type"` that is defined outside of Optify, such as a duration or a URL built from parts.
+/// Types are registered on the builder with `with_configurable_value_type`.
+/// Types must be `RefUnwindSafe` so that providers can still be used after a panic is caught, such as in bindings for other languages.
+pub trait ConfigurableValueType: RefUnwindSafe + Send + Sync {
+    /// The value of the `"$type"` property for values of this type, such as `"MyTeam.Duration"`.
+    /// The names of the built-in types, which start with `"Optify."`, cannot be used.
+    fn type_name(&self) -> &str;
+
+    /// Checks a value of this type when building, after the imports of each feature are merged into it.
+    /// Returns a message describing the problem when the value is not valid.
+    fn validate(&self, _value: &serde_json::Value) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Builds the value to use in the options from a value of this type when options are retrieved with configurable values enabled.
+    /// Returns a message describing the problem when the value cannot be built.
+    fn build(
+        &self,
+        value: &serde_json::Value,
+        context: &ConfigurableValueContext,
+    ) -> Result<serde_json::Value, String>;
+}
+
+/// Registered configurable value types mapped to their type names.
+pub type ConfigurableValueTypes = HashMap<String, Arc<dyn ConfigurableValueType>>;
+
+/// Information about where a value is being built.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ConfigurableValueContext<'a> {
+    /// The JSON pointer to the value, starting with the top-level key, such as `"/myConfig/timeout"`.
+    pub pointer: &'a str,
+
+    /// The canonical names of the features that options are being retrieved for.
+    pub feature_names: &'a [String],
+}
+
+impl<'a> ConfigurableValueContext<'a> {
+    pub(crate) fn new(pointer: &'a str, feature_names: &'a [String]) -> Self {
+        Self {
+            pointer,
+            feature_names,
+        }
+    }
+}
+
+/// Gets the registered type for the value if its `"$type"` is the name of a registered type.
+pub(crate) fn get_configurable_value_type<'a>(
+    value: &serde_json::Value,
+    value_types: &'a ConfigurableValueTypes,
+) -> Option<&'a Arc<dyn ConfigurableValueType>> {
+    value
+        .get(TYPE_KEY)
+        .and_then(|type_value| type_value.as_str())
+        .and_then(|type_name| value_types.get(type_name))
+}
+
+/// Validates the values of registered types in each feature.
+/// `locations` are the pointers to the values of registered types in all of the features.
+pub(crate) fn find_invalid_configurable_values(
+    sources: &Sources,
+    locations: &HashSet<String>,
+    value_types: &ConfigurableValueTypes,
+) -> Vec<OptifyError> {
+    let mut problems = Vec::new();
+    // Sort to report problems in a consistent order.
+    let mut canonical_feature_names: Vec<&String> = sources.keys().collect();
+    canonical_feature_names.sort_unstable();
+    let mut sorted_locations: Vec<&String> = locations.iter().collect();
+    sorted_locations.sort_unstable();
+
+    for canonical_feature_name in canonical_feature_names {
+        let source = &sources[canonical_feature_name];
+        for location in &sorted_locations {
+            let Some(value) = source.pointer(location) else {
+                continue;
+            };
+            let Some(value_type) = get_configurable_value_type(value, value_types) else {
+                continue;
+            };
+            if let Err(message) = value_type.validate(value) {
+                problems.push(OptifyError::InvalidConfigurableValue {
+                    feature: canonical_feature_name.clone(),
+                    pointer: location.to_string(),
+                    message,
+                });
+            }
+        }
+    }
+    problems
+}
@@ -1,15 +1,25 @@
 use std::collections::HashMap;
 
+use crate::configurable_values::configurable_value_type::ConfigurableValueTypes;
 use crate::json::escape_json_pointer;
 
 pub(crate) const TYPE_KEY: &str = "$type";
 pub(crate) const STRING_TYPE: &str = "Optify.ConfigurableString";
 pub(crate) const LIST_TYPE: &str = "Optify.ConfigurableList";
+pub(crate) const SECRET_TYPE: &str = "Optify.Secret";
+pub(crate) const REFERENCE_TYPE: &str = "Optify.Reference";
 
 pub(crate) struct ConfigurableValuePointers {
     pub configurable_string_pointers: Vec<String>,
+    /// Pointers to values of types registered with `with_configurable_value_type`.
+    pub configurable_custom_pointers: Vec<String>,
     pub configurable_list_pointers: Vec<String>,
+    pub configurable_reference_pointers: Vec<String>,
+    pub configurable_secret_pointers: Vec<String>,
+    pub keyed_configurable_custom_pointers: HashMap<String, Vec<String>>,
     pub keyed_configurable_list_pointers: HashMap<String, Vec<String>>,
+    pub keyed_configurable_reference_pointers: HashMap<String, Vec<String>>,
+    pub keyed_configurable_secret_pointers: HashMap<String, Vec<String>>,
     pub keyed_configurable_string_pointers: HashMap<String, Vec<String>>,
 }
 
@@ -23,22 +33,36 @@ impl ConfigurableValuePointers {
     pub fn new() -> Self {
         ConfigurableValuePointers {
             configurable_string_pointers: Vec::new(),
+            configurable_custom_pointers: Vec::new(),
             configurable_list_pointers: Vec::new(),
+            configurable_reference_pointers: Vec::new(),
+            configurable_secret_pointers: Vec::new(),
+            keyed_configurable_custom_pointers: HashMap::new(),
             keyed_configurable_list_pointers: HashMap::new(),
+            keyed_configurable_reference_pointers: HashMap::new(),
+            keyed_configurable_secret_pointers: HashMap::new(),
             keyed_configurable_string_pointers: HashMap::new(),
         }
     }
 }
 
 /// Finds pointers like JSON pointers to configurable values
-/// that have a `"$type"` property with a supported value.
+/// that have a `"$type"` property with a supported value or the name of one of the registered `value_types`.
 pub(crate) fn find_configurable_values(
     options: Option<&serde_json::Value>,
+    value_types: &ConfigurableValueTypes,
 ) -> ConfigurableValuePointers {
     let mut result = ConfigurableValuePointers::default();
 
     if let Some(value) = options {
-        find_configurable_values_recursive(value, None, "".to_owned(), "".to_owned(), &mut result);
+        find_configurable_values_recursive(
+            value,
+            value_types,
+            None,
+            "".to_owned(),
+            "".to_owned(),
+            &mut result,
+        );
     }
 
     result
@@ -46,6 +70,7 @@ pub(crate) fn find_configurable_values(
 
 fn find_configurable_values_recursive<'a>(
     value: &'a serde_json::Value,
+    value_types: &ConfigurableValueTypes,
     mut top_level_key: Option<&'a str>,
     current_pointer: String,
     current_keyed_pointer: String,
@@ -83,6 +108,48 @@ fn find_configurable_values_recursive<'a>(
                         }
                         // Continue recursing because configurable lists can contain nested configurable values such as strings.
                     }
+                    Some(SECRET_TYPE) => {
+                        result
+                            .configurable_secret_pointers
+                            .push(current_pointer.to_owned());
+                        if let Some(key) = top_level_key {
+                            result
+                                .keyed_configurable_secret_pointers
+                                .entry(key.to_owned())
+                                .or_default()
+                                .push(current_keyed_pointer.to_owned());
+                        }
+                        // Do not recurse because secrets cannot contain nested configurable values.
+                        return;
+                    }
+                    Some(REFERENCE_TYPE) => {
+                        result
+                            .configurable_reference_pointers
+                            .push(current_pointer.to_owned());
+                        if let Some(key) = top_level_key {
+                            result
+                                .keyed_configurable_reference_pointers
+                                .entry(key.to_owned())
+                                .or_default()
+                                .push(current_keyed_pointer.to_owned());
+                        }
+                        // Do not recurse because references are replaced by the values that they point to.
+                        return;
+                    }
+                    Some(type_name) if value_types.contains_key(type_name) => {
+                        result
+                            .configurable_custom_pointers
+                            .push(current_pointer.to_owned());
+                        if let Some(key) = top_level_key {
+                            result
+                                .keyed_configurable_custom_pointers
+                                .entry(key.to_owned())
+                                .or_default()
+                                .push(current_keyed_pointer.to_owned());
+                        }
+                        // Do not recurse because the registered type builds the entire value.
+                        return;
+                    }
                     _ => {}
                 }
             }
@@ -103,6 +170,7 @@ fn find_configurable_values_recursive<'a>(
                 };
                 find_configurable_values_recursive(
                     val,
+                    value_types,
                     top_level_key,
                     next_pointer,
                     next_keyed_pointer,
@@ -128,6 +196,7 @@ fn find_configurable_values_recursive<'a>(
                 };
                 find_configurable_values_recursive(
                     val,
+                    value_types,
                     top_level_key,
                     next_pointer,
                     next_keyed_pointer,
@@ -154,7 +223,7 @@ mod tests {
             "arguments": {}
         });
 
-        let pointers = find_configurable_values(Some(&json_value));
+        let pointers = find_configurable_values(Some(&json_value), &ConfigurableValueTypes::new());
 
         assert_eq!(pointers.configurable_string_pointers, vec!["".to_string()]);
     }
@@ -169,7 +238,7 @@ mod tests {
             }
         });
 
-        let pointers = find_configurable_values(Some(&json_value));
+        let pointers = find_configurable_values(Some(&json_value), &ConfigurableValueTypes::new());
 
         assert_eq!(
             pointers.configurable_string_pointers,
@@ -191,7 +260,7 @@ mod tests {
             }
         });
 
-        let pointers = find_configurable_values(Some(&json_value));
+        let pointers = find_configurable_values(Some(&json_value), &ConfigurableValueTypes::new());
 
         assert_eq!(
             pointers.configurable_string_pointers,
@@ -211,7 +280,7 @@ mod tests {
             ]
         });
 
-        let pointers = find_configurable_values(Some(&json_value));
+        let pointers = find_configurable_values(Some(&json_value), &ConfigurableValueTypes::new());
 
         assert_eq!(
             pointers.configurable_string_pointers,
@@ -254,7 +323,7 @@ mod tests {
             "regular": "not configurable"
         });
 
-        let pointers = find_configurable_values(Some(&json_value));
+        let pointers = find_configurable_values(Some(&json_value), &ConfigurableValueTypes::new());
 
         assert_eq!(
             pointers.configurable_string_pointers,
@@ -269,15 +338,15 @@ mod tests {
 
     #[test]
     fn test_empty_input() {
-        let pointers = find_configurable_values(None);
+        let pointers = find_configurable_values(None, &ConfigurableValueTypes::new());
         assert!(pointers.configurable_list_pointers.is_empty());
         assert!(pointers.configurable_string_pointers.is_empty());
 
-        let pointers = find_configurable_values(Some(&json!({})));
+        let pointers = find_configurable_values(Some(&json!({})), &ConfigurableValueTypes::new());
         assert!(pointers.configurable_list_pointers.is_empty());
         assert!(pointers.configurable_string_pointers.is_empty());
 
-        let pointers = find_configurable_values(Some(&json!([])));
+        let pointers = find_configurable_values(Some(&json!([])), &ConfigurableValueTypes::new());
         assert!(pointers.configurable_list_pointers.is_empty());
         assert!(pointers.configurable_string_pointers.is_empty());
     }
@@ -298,7 +367,7 @@ mod tests {
             }
         });
 
-        let pointers = find_configurable_values(Some(&json_value));
+        let pointers = find_configurable_values(Some(&json_value), &ConfigurableValueTypes::new());
         assert!(pointers.configurable_list_pointers.is_empty());
         assert!(pointers.configurable_string_pointers.is_empty());
     }
@@ -318,7 +387,7 @@ mod tests {
             }
         });
 
-        let pointers = find_configurable_values(Some(&json_value));
+        let pointers = find_configurable_values(Some(&json_value), &ConfigurableValueTypes::new());
         assert!(pointers.configurable_list_pointers.is_empty());
         assert!(pointers.configurable_string_pointers.is_empty());
     }
@@ -350,7 +419,7 @@ mod tests {
             }
         });
 
-        let pointers = find_configurable_values(Some(&json_value));
+        let pointers = find_configurable_values(Some(&json_value), &ConfigurableValueTypes::new());
 
         assert_eq!(
             pointers.configurable_string_pointers,
@@ -384,7 +453,7 @@ mod tests {
             ]
         });
 
-        let pointers = find_configurable_values(Some(&json_value));
+        let pointers = find_configurable_values(Some(&json_value), &ConfigurableValueTypes::new());
 
         assert_eq!(
             pointers.configurable_string_pointers,
@@ -411,7 +480,7 @@ mod tests {
             }
         });
 
-        let pointers = find_configurable_values(Some(&json_value));
+        let pointers = find_configurable_values(Some(&json_value), &ConfigurableValueTypes::new());
 
         // Should only find the top-level configurable string, not the nested one
         assert_eq!(
@@ -420,6 +489,113 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_secrets() {
+        let json_value = json!({
+            "database": {
+                "password": {
+                    TYPE_KEY: SECRET_TYPE,
+                    "resolver": "env",
+                    "name": "DB_PASSWORD"
+                }
+            },
+            "list": {
+                TYPE_KEY: LIST_TYPE,
+                "a": {
+                    "$value": {
+                        TYPE_KEY: SECRET_TYPE,
+                        "resolver": "file",
+                        "name": "token"
+                    }
+                }
+            }
+        });
+
+        let pointers = find_configurable_values(Some(&json_value), &ConfigurableValueTypes::new());
+
+        assert_eq!(
+            pointers.configurable_secret_pointers,
+            vec![
+                "/database/password".to_string(),
+                "/list/a/$value".to_string()
+            ]
+        );
+        assert_eq!(
+            pointers.keyed_configurable_secret_pointers["database"],
+            vec!["/password".to_string()]
+        );
+        assert!(pointers.configurable_string_pointers.is_empty());
+    }
+
+    #[test]
+    fn test_find_references() {
+        let json_value = json!({
+            "endpoints": {"api": "https://api.example.com"},
+            "client": {
+                "url": {TYPE_KEY: REFERENCE_TYPE, "pointer": "/endpoints/api"},
+                "fallbacks": [{TYPE_KEY: REFERENCE_TYPE, "pointer": "/endpoints/api"}]
+            }
+        });
+
+        let mut pointers =
+            find_configurable_values(Some(&json_value), &ConfigurableValueTypes::new());
+        pointers.configurable_reference_pointers.sort();
+
+        assert_eq!(
+            pointers.configurable_reference_pointers,
+            vec!["/client/fallbacks/0".to_string(), "/client/url".to_string()]
+        );
+        assert_eq!(pointers.keyed_configurable_reference_pointers.len(), 1);
+        assert_eq!(
+            pointers.keyed_configurable_reference_pointers["client"].len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_find_registered_types() {
+        struct Duration;
+
+        impl crate::configurable_values::ConfigurableValueType for Duration {
+            fn type_name(&self) -> &str {
+                "Test.Duration"
+            }
+
+            fn build(
+                &self,
+                value: &serde_json::Value,
+                _context: &crate::configurable_values::ConfigurableValueContext,
+            ) -> Result<serde_json::Value, String> {
+                Ok(value["seconds"].clone())
+            }
+        }
+
+        let json_value = json!({
+            "client": {
+                "timeout": {TYPE_KEY: "Test.Duration", "seconds": 5},
+                "other": {TYPE_KEY: "Test.Unknown", "seconds": 5}
+            }
+        });
+        let mut value_types = ConfigurableValueTypes::new();
+        value_types.insert("Test.Duration".to_owned(), std::sync::Arc::new(Duration));
+
+        let pointers = find_configurable_values(Some(&json_value), &value_types);
+
+        assert_eq!(
+            pointers.configurable_custom_pointers,
+            vec!["/client/timeout".to_string()]
+        );
+        assert_eq!(
+            pointers.keyed_configurable_custom_pointers["client"],
+            vec!["/timeout".to_string()]
+        );
+        assert!(
+            find_configurable_values(Some(&json_value), &ConfigurableValueTypes::new())
+                .configurable_custom_pointers
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_with_real_config_structure() {
         // Test with the structure from the test config file
@@ -453,7 +629,7 @@ mod tests {
             "regular_value": "not a configurable string"
         });
 
-        let pointers = find_configurable_values(Some(&json_value));
+        let pointers = find_configurable_values(Some(&json_value), &ConfigurableValueTypes::new());
 
         assert_eq!(
             pointers.configurable_string_pointers,
@@ -1,2 +1,9 @@
 pub(crate) mod configurable_list_impl;
+pub mod configurable_value_type;
 pub(crate) mod locator;
+pub(crate) mod reference;
+
+// Re-export the main types for easier access
+pub use configurable_value_type::{
+    ConfigurableValueContext, ConfigurableValueType, ConfigurableValueTypes,
+};
@@ -0,0 +1,164 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use serde::Deserialize;
+
+use crate::configurable_values::locator::{REFERENCE_TYPE, TYPE_KEY};
+use crate::error::OptifyError;
+use crate::provider::Sources;
+
+/// Points to another value in the options after all of the features are merged.
+/// Parsed from a `serde_json::Value` such as `{"$type": "Optify.Reference", "pointer": "/endpoints/api"}`.
+#[derive(Deserialize, Debug)]
+pub(crate) struct Reference {
+    /// The JSON pointer to the value, starting with the top-level key.
+    pub pointer: String,
+}
+
+impl Reference {
+    /// Parses the reference at `location` if the value has the type for references.
+    pub fn parse(value: &serde_json::Value, location: &str) -> Option<Result<Self, OptifyError>> {
+        if value
+            .get(TYPE_KEY)
+            .and_then(|type_value| type_value.as_str())
+            != Some(REFERENCE_TYPE)
+        {
+            return None;
+        }
+        let result = serde_json::from_value::<Self>(value.clone())
+            .map_err(|e| format!("Failed to deserialize Reference: {e}"))
+            .and_then(|reference| {
+                if reference.pointer.starts_with('/') {
+                    Ok(reference)
+                } else {
+                    Err(format!(
+                        "The pointer {:?} must be a JSON pointer that starts with \"/\".",
+                        reference.pointer
+                    ))
+                }
+            })
+            .map_err(|message| OptifyError::ConfigurableValue {
+                pointer: location.to_owned(),
+                message,
+            });
+        Some(result)
+    }
+}
+
+/// Returns `true` if `pointer` is `other` or a value inside of `other`.
+pub(crate) fn is_same_or_inside(pointer: &str, other: &str) -> bool {
+    pointer
+        .strip_prefix(other)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Finds references that point to values that are not in any feature and references that form cycles.
+/// `locations` are the pointers to the references in all of the features.
+/// Features can be combined in any order, so a cycle is reported if the references in any features could form a cycle.
+pub(crate) fn find_reference_problems(
+    sources: &Sources,
+    locations: &HashSet<String>,
+) -> Vec<OptifyError> {
+    let mut problems = Vec::new();
+    // Sort to report problems in a consistent order.
+    let mut canonical_feature_names: Vec<&String> = sources.keys().collect();
+    canonical_feature_names.sort_unstable();
+    let mut sorted_locations: Vec<&String> = locations.iter().collect();
+    sorted_locations.sort_unstable();
+
+    let mut targets: BTreeMap<&str, BTreeSet<String>> = BTreeMap::new();
+    for canonical_feature_name in canonical_feature_names {
+        let source = &sources[canonical_feature_name];
+        for location in &sorted_locations {
+            let Some(reference) = source
+                .pointer(location)
+                .and_then(|value| Reference::parse(value, location))
+            else {
+                continue;
+            };
+            let reference = match reference {
+                Ok(reference) => reference,
+                Err(error) => {
+                    problems.push(error);
+                    continue;
+                }
+            };
+            // A target inside of another reference is checked when that reference is resolved.
+            let is_found = sources
+                .values()
+                .any(|source| source.pointer(&reference.pointer).is_some())
+                || locations
+                    .iter()
+                    .any(|other| is_same_or_inside(&reference.pointer, other));
+            if !is_found {
+                problems.push(OptifyError::DanglingReference {
+                    feature: canonical_feature_name.clone(),
+                    pointer: location.to_string(),
+                    target: reference.pointer.clone(),
+                });
+            }
+            targets
+                .entry(location.as_str())
+                .or_default()
+                .insert(reference.pointer);
+        }
+    }
+
+    if let Some(cycle) = find_cycle(&targets) {
+        problems.push(OptifyError::ReferenceCycle { pointers: cycle });
+    }
+    problems
+}
+
+/// Finds a cycle of references where resolving a reference needs the value of a reference in the cycle.
+fn find_cycle(targets: &BTreeMap<&str, BTreeSet<String>>) -> Option<Vec<String>> {
+    // Resolving a reference needs the references at, inside of, or containing its targets.
+    let dependencies: BTreeMap<&str, Vec<&str>> = targets
+        .iter()
+        .map(|(location, location_targets)| {
+            let needed = targets
+                .keys()
+                .filter(|other| {
+                    location_targets.iter().any(|target| {
+                        is_same_or_inside(other, target) || is_same_or_inside(target, other)
+                    })
+                })
+                .copied()
+                .collect();
+            (*location, needed)
+        })
+        .collect();
+
+    let mut finished = HashSet::new();
+    for location in dependencies.keys() {
+        let mut path = Vec::new();
+        if let Some(cycle) = visit(location, &dependencies, &mut path, &mut finished) {
+            return Some(cycle);
+        }
+    }
+    None
+}
+
+fn visit<'a>(
+    location: &'a str,
+    dependencies: &BTreeMap<&'a str, Vec<&'a str>>,
+    path: &mut Vec<&'a str>,
+    finished: &mut HashSet<&'a str>,
+) -> Option<Vec<String>> {
+    if let Some(start) = path.iter().position(|other| *other == location) {
+        let mut cycle: Vec<String> = path[start..].iter().map(|p| p.to_string()).collect();
+        cycle.push(location.to_owned());
+        return Some(cycle);
+    }
+    if finished.contains(location) {
+        return None;
+    }
+    path.push(location);
+    for dependency in &dependencies[location] {
+        if let Some(cycle) = visit(dependency, dependencies, path, finished) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    finished.insert(location);
+    None
+}
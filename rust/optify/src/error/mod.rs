@@ -0,0 +1,3 @@
+pub(crate) mod optify_error;
+
+pub use optify_error::*;
@@ -0,0 +1,310 @@
+use std::fmt;
+
+/// Errors that can occur when building an options registry or getting options from it.
+///
+/// Bindings for other languages should match on the variant instead of the message
+/// because messages are meant for people and may change.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OptifyError {
+    /// Builder options could not be loaded from a `.optify/config.json` file.
+    BuilderConfig { path: String, message: String },
+
+    /// A configurable value, such as a configurable string or list, could not be built.
+    ConfigurableValue { pointer: String, message: String },
+
+    /// An `Optify.Reference` points to a value that is not in the options of any feature.
+    DanglingReference {
+        feature: String,
+        pointer: String,
+        target: String,
+    },
+
+    /// A feature file was loaded but it does not have the expected structure.
+    Deserialize { file: String, message: String },
+
+    /// An alias is already mapped to a canonical feature name.
+    DuplicateAlias {
+        alias: String,
+        canonical_feature_name: String,
+        existing_canonical_feature_name: String,
+    },
+
+    /// A canonical feature name was added more than once.
+    DuplicateFeature { name: String },
+
+    /// A non-feature file with the same relative path was already loaded.
+    DuplicateFile { relative_path: String },
+
+    /// A glob in the builder options is invalid.
+    Glob { pattern: String, message: String },
+
+    /// Imports form a cycle.
+    /// `path` holds the features in the resolution path in sorted order.
+    ImportCycle {
+        feature: String,
+        import: String,
+        path: Vec<String>,
+    },
+
+    /// An imported feature has conditions.
+    ImportHasConditions { feature: String, import: String },
+
+    /// A configurable value of a type registered with `with_configurable_value_type` is not valid.
+    InvalidConfigurableValue {
+        feature: String,
+        pointer: String,
+        message: String,
+    },
+
+    /// The given path is not a directory.
+    InvalidDirectory { path: String },
+
+    /// An environment variable for overriding options could not be applied.
+    InvalidEnvironmentVariable { name: String, message: String },
+
+    /// A path in a directory is not allowed by the builder options,
+    /// such as a symbolic link or a path that is not valid UTF-8.
+    InvalidPath { path: String, message: String },
+
+    /// A file could not be read.
+    Io { path: String, message: String },
+
+    /// The key was not found in the options for the features.
+    KeyNotFound { key: String, features: Vec<String> },
+
+    /// A Liquid template for a configurable string could not be parsed.
+    /// `file` is the path of the feature file with the template, or of the `.liquid` file when `pointer` is not set.
+    /// It is the canonical feature name for features that were not loaded from files.
+    LiquidTemplate {
+        file: String,
+        pointer: Option<String>,
+        message: String,
+    },
+
+    /// An import is an alias or an unknown name instead of a canonical feature name.
+    /// `canonical_feature_name` is set when the import is a known alias.
+    NonCanonicalImport {
+        feature: String,
+        import: String,
+        canonical_feature_name: Option<String>,
+    },
+
+    /// Options could not be deserialized into the requested type.
+    OptionsDeserialize {
+        key: String,
+        /// The requested feature names.
+        features: Vec<String>,
+        /// The JSON pointer of the value that could not be deserialized, relative to the options for the key.
+        pointer: String,
+        /// The path of the feature file that provided the value, if it came from a feature file.
+        feature_file: Option<String>,
+        message: String,
+    },
+
+    /// A feature file could not be parsed.
+    Parse { file: String, message: String },
+
+    /// `Optify.Reference` values form a cycle.
+    /// `pointers` holds the pointers to the references in the cycle, starting and ending with the same pointer.
+    ReferenceCycle { pointers: Vec<String> },
+
+    /// A JSON schema could not be loaded.
+    Schema { message: String },
+
+    /// A feature file does not match the JSON schema.
+    SchemaValidation { file: String, errors: Vec<String> },
+
+    /// A snapshot of a provider could not be saved or loaded,
+    /// such as when the snapshot is corrupt or was saved by an incompatible version.
+    Snapshot { message: String },
+
+    /// A feature name is not a canonical feature name or a known alias.
+    UnknownFeature { name: String },
+
+    /// Directories could not be watched for changes.
+    Watcher { message: String },
+}
+
+impl OptifyError {
+    /// A stable name for the variant, such as `"unknown_feature"`,
+    /// for bindings for other languages that cannot match on the variant.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::BuilderConfig { .. } => "builder_config",
+            Self::ConfigurableValue { .. } => "configurable_value",
+            Self::DanglingReference { .. } => "dangling_reference",
+            Self::Deserialize { .. } => "deserialize",
+            Self::DuplicateAlias { .. } => "duplicate_alias",
+            Self::DuplicateFeature { .. } => "duplicate_feature",
+            Self::DuplicateFile { .. } => "duplicate_file",
+            Self::Glob { .. } => "glob",
+            Self::ImportCycle { .. } => "import_cycle",
+            Self::ImportHasConditions { .. } => "import_has_conditions",
+            Self::InvalidConfigurableValue { .. } => "invalid_configurable_value",
+            Self::InvalidDirectory { .. } => "invalid_directory",
+            Self::InvalidEnvironmentVariable { .. } => "invalid_environment_variable",
+            Self::InvalidPath { .. } => "invalid_path",
+            Self::Io { .. } => "io",
+            Self::KeyNotFound { .. } => "key_not_found",
+            Self::LiquidTemplate { .. } => "liquid_template",
+            Self::NonCanonicalImport { .. } => "non_canonical_import",
+            Self::OptionsDeserialize { .. } => "options_deserialize",
+            Self::Parse { .. } => "parse",
+            Self::ReferenceCycle { .. } => "reference_cycle",
+            Self::Schema { .. } => "schema",
+            Self::SchemaValidation { .. } => "schema_validation",
+            Self::Snapshot { .. } => "snapshot",
+            Self::UnknownFeature { .. } => "unknown_feature",
+            Self::Watcher { .. } => "watcher",
+        }
+    }
+}
+
+impl fmt::Display for OptifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BuilderConfig { path, message } => {
+                write!(f, "Error loading builder options from {path}: {message}")
+            }
+            Self::ConfigurableValue { pointer, message } => {
+                write!(
+                    f,
+                    "Error building configurable value at {pointer:?}: {message}"
+                )
+            }
+            Self::DanglingReference {
+                feature,
+                pointer,
+                target,
+            } => write!(
+                f,
+                "The reference at {pointer:?} in '{feature}' points to {target:?}, which is not in the options of any feature."
+            ),
+            Self::Deserialize { file, message } => write!(
+                f,
+                "Error deserializing configuration for file '{file}': {message}"
+            ),
+            Self::DuplicateAlias {
+                alias,
+                canonical_feature_name,
+                existing_canonical_feature_name,
+            } => write!(
+                f,
+                "The alias '{alias}' for canonical feature name '{canonical_feature_name}' is already mapped to '{existing_canonical_feature_name}'."
+            ),
+            Self::DuplicateFeature { name } => write!(
+                f,
+                "Error when loading feature. The canonical feature name '{name}' was already added. It may be an alias for another feature."
+            ),
+            Self::DuplicateFile { relative_path } => write!(
+                f,
+                "File '{relative_path}' is already loaded from another directory."
+            ),
+            Self::Glob { pattern, message } => {
+                write!(f, "Invalid glob {pattern:?}: {message}")
+            }
+            Self::ImportCycle {
+                feature,
+                import,
+                path,
+            } => write!(
+                f,
+                "Error when resolving imports for '{feature}': Cycle detected with import '{import}'. The features in the path (not in order): {path:?}"
+            ),
+            Self::ImportHasConditions { feature, import } => write!(
+                f,
+                "Error when resolving imports for '{feature}': The import '{import}' \
+                 has conditions. Conditions cannot be used in imported features. This helps keep \
+                 retrieving and building configuration options for a list of features fast and more \
+                 predictable because imports do not need to be re-evaluated. Instead, keep each \
+                 feature file as granular and self-contained as possible, then use conditions and \
+                 import the required granular features in a feature file that defines a common \
+                 scenario."
+            ),
+            Self::InvalidConfigurableValue {
+                feature,
+                pointer,
+                message,
+            } => write!(
+                f,
+                "The configurable value at {pointer:?} in '{feature}' is not valid: {message}"
+            ),
+            Self::InvalidDirectory { path } => {
+                write!(f, "Error adding directory: {path:?} is not a directory")
+            }
+            Self::InvalidEnvironmentVariable { name, message } => {
+                write!(f, "Error applying the environment variable {name:?}: {message}")
+            }
+            Self::InvalidPath { path, message } => {
+                write!(f, "Error adding path {path:?}: {message}")
+            }
+            Self::Io { path, message } => write!(f, "Error reading file {path}: {message}"),
+            Self::KeyNotFound { key, features } => write!(
+                f,
+                "Error getting options with features {features:?}: configuration property \"{key}\" not found"
+            ),
+            Self::LiquidTemplate {
+                file,
+                pointer: Some(pointer),
+                message,
+            } => write!(
+                f,
+                "Error parsing the Liquid template at {pointer:?} in '{file}': {message}"
+            ),
+            Self::LiquidTemplate {
+                file,
+                pointer: None,
+                message,
+            } => write!(f, "Error parsing the Liquid template in '{file}': {message}"),
+            Self::NonCanonicalImport {
+                feature,
+                import,
+                canonical_feature_name: Some(canonical_feature_name),
+            } => write!(
+                f,
+                "Error when resolving imports for '{feature}': The import '{import}' is not a canonical feature name. Use '{canonical_feature_name}' instead of '{import}' in order to keep dependencies clear and to help with navigating through files."
+            ),
+            Self::NonCanonicalImport {
+                feature,
+                import,
+                canonical_feature_name: None,
+            } => write!(
+                f,
+                "Error when resolving imports for '{feature}': The import '{import}' is not a canonical feature name and not a recognized alias. Use a canonical feature name in order to keep dependencies clear and to help with navigating through files."
+            ),
+            Self::OptionsDeserialize {
+                key,
+                features,
+                pointer,
+                feature_file,
+                message,
+            } => {
+                write!(
+                    f,
+                    "Error deserializing options for key {key:?} with features {features:?} at {pointer:?}"
+                )?;
+                if let Some(feature_file) = feature_file {
+                    write!(f, " from {feature_file:?}")?;
+                }
+                write!(f, ": {message}")
+            }
+            Self::Parse { file, message } => write!(f, "Error loading file '{file}': {message}"),
+            Self::ReferenceCycle { pointers } => {
+                write!(f, "References form a cycle: {}", pointers.join(" -> "))
+            }
+            Self::Schema { message } => write!(f, "{message}"),
+            Self::SchemaValidation { file, errors } => write!(
+                f,
+                "Schema validation failed for {file:?} : {}",
+                errors.join(", ")
+            ),
+            Self::Snapshot { message } => write!(f, "Error with the options snapshot: {message}"),
+            Self::UnknownFeature { name } => {
+                write!(f, "Feature name {name:?} is not a known feature.")
+            }
+            Self::Watcher { message } => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for OptifyError {}
@@ -0,0 +1,65 @@
+use std::hash::{Hash, Hasher};
+
+/// Hashes a JSON value so that equal values always have the same hash,
+/// regardless of the order of the properties in objects.
+pub(crate) fn hash_json_value<H: Hasher>(value: &serde_json::Value, state: &mut H) {
+    match value {
+        serde_json::Value::Null => 0u8.hash(state),
+        serde_json::Value::Bool(b) => {
+            1u8.hash(state);
+            b.hash(state);
+        }
+        serde_json::Value::Number(n) => {
+            2u8.hash(state);
+            n.hash(state);
+        }
+        serde_json::Value::String(s) => {
+            3u8.hash(state);
+            s.hash(state);
+        }
+        serde_json::Value::Array(items) => {
+            4u8.hash(state);
+            items.len().hash(state);
+            for item in items {
+                hash_json_value(item, state);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            5u8.hash(state);
+            map.len().hash(state);
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+            for (key, value) in entries {
+                key.hash(state);
+                hash_json_value(value, state);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn get_hash(value: &serde_json::Value) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        hash_json_value(value, &mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_property_order_does_not_matter() {
+        let a: serde_json::Value = serde_json::from_str(r#"{"a": 1, "b": {"c": [1, 2]}}"#).unwrap();
+        let b: serde_json::Value = serde_json::from_str(r#"{"b": {"c": [1, 2]}, "a": 1}"#).unwrap();
+        assert_eq!(get_hash(&a), get_hash(&b));
+    }
+
+    #[test]
+    fn test_different_values() {
+        assert_ne!(get_hash(&json!({"a": 1})), get_hash(&json!({"a": 2})));
+        assert_ne!(get_hash(&json!([1, 2])), get_hash(&json!([2, 1])));
+        assert_ne!(get_hash(&json!("1")), get_hash(&json!(1)));
+    }
+}
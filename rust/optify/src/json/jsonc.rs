@@ -0,0 +1,116 @@
+/// Converts JSON with comments and trailing commas (JSONC) to standard JSON.
+/// Comments and trailing commas are replaced with spaces so that the lines and columns in errors match the original contents.
+pub(crate) fn strip_jsonc(contents: &str) -> String {
+    let mut bytes = contents.as_bytes().to_vec();
+    let mut index = 0;
+    let mut in_string = false;
+    // A comma outside of a string that has not been followed by a value yet.
+    let mut pending_comma: Option<usize> = None;
+    while index < bytes.len() {
+        let byte = bytes[index];
+        if in_string {
+            match byte {
+                b'\\' => index += 1,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            index += 1;
+            continue;
+        }
+        match byte {
+            b'"' => {
+                in_string = true;
+                pending_comma = None;
+            }
+            b'/' if bytes.get(index + 1) == Some(&b'/') => {
+                while index < bytes.len() && bytes[index] != b'\n' {
+                    bytes[index] = b' ';
+                    index += 1;
+                }
+                continue;
+            }
+            b'/' if bytes.get(index + 1) == Some(&b'*') => {
+                let end = match bytes[index + 2..]
+                    .windows(2)
+                    .position(|window| window == b"*/")
+                {
+                    Some(position) => index + 2 + position + 2,
+                    // Leave an unterminated comment so that parsing fails.
+                    None => break,
+                };
+                for byte in &mut bytes[index..end] {
+                    if *byte != b'\n' {
+                        *byte = b' ';
+                    }
+                }
+                index = end;
+                continue;
+            }
+            // Keep both commas in `,,` so that parsing fails.
+            b',' => {
+                pending_comma = match pending_comma {
+                    Some(_) => None,
+                    None => Some(index),
+                }
+            }
+            b']' | b'}' => {
+                if let Some(comma) = pending_comma.take() {
+                    bytes[comma] = b' ';
+                }
+            }
+            b' ' | b'\t' | b'\r' | b'\n' => {}
+            _ => pending_comma = None,
+        }
+        index += 1;
+    }
+
+    // Only ASCII bytes outside of multi-byte characters were replaced with ASCII spaces, so the bytes are still valid UTF-8.
+    String::from_utf8(bytes).expect("the contents should still be valid UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> serde_json::Value {
+        serde_json::from_str(&strip_jsonc(contents)).unwrap()
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        let contents = r#"{
+            // A line comment.
+            "a": 1, /* A block
+            comment. */ "b": "// not a comment",
+            "c": "/* not a comment */"
+        }"#;
+        assert_eq!(
+            parse(contents),
+            serde_json::json!({"a": 1, "b": "// not a comment", "c": "/* not a comment */"})
+        );
+    }
+
+    #[test]
+    fn test_strip_trailing_commas() {
+        let contents = r#"{
+            "list": [1, 2, /* the end */ ],
+            "escaped": "quote \", comma,",
+            "object": {"a": "ü",},
+        }"#;
+        assert_eq!(
+            parse(contents),
+            serde_json::json!({"list": [1, 2], "escaped": "quote \", comma,", "object": {"a": "ü"}})
+        );
+    }
+
+    #[test]
+    fn test_errors_keep_positions() {
+        let contents = "{\n  // comment\n  \"a\": 1,,\n}";
+        let error = serde_json::from_str::<serde_json::Value>(&strip_jsonc(contents)).unwrap_err();
+        assert_eq!(error.line(), 3);
+        assert_eq!(
+            strip_jsonc("{} /* unterminated"),
+            "{} /* unterminated".to_owned()
+        );
+    }
+}
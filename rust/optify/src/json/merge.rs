@@ -1,7 +1,133 @@
 use std::collections::HashSet;
 
+use super::pointer::get_at_path;
+
 pub(crate) type FrozenPaths = HashSet<String>;
 
+/// Adds items after the items in the lower-priority array, such as `{"$append": [3]}`.
+pub(crate) const APPEND_DIRECTIVE: &str = "$append";
+/// Removes the lower-priority value for a key with `{"$delete": true}`.
+pub(crate) const DELETE_DIRECTIVE: &str = "$delete";
+/// Uses an object instead of merging lower-priority objects into it with `"$merge": "replace"`.
+pub(crate) const MERGE_DIRECTIVE: &str = "$merge";
+/// Adds items before the items in the lower-priority array, such as `{"$prepend": [0]}`.
+pub(crate) const PREPEND_DIRECTIVE: &str = "$prepend";
+const REPLACE_MERGE_MODE: &str = "replace";
+
+/// How a value changes the values with lower priority when they are merged.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MergeDirective {
+    /// `{"$append": [...], "$prepend": [...]}` with at least one of the properties.
+    Array,
+    /// `{"$delete": true}`
+    Delete,
+    /// An object with `"$merge": "replace"`.
+    Replace,
+    None,
+}
+
+pub(crate) fn get_merge_directive(value: &serde_json::Value) -> MergeDirective {
+    let map = match value {
+        serde_json::Value::Object(map) => map,
+        _ => return MergeDirective::None,
+    };
+    if map.len() == 1 && map.get(DELETE_DIRECTIVE) == Some(&serde_json::Value::Bool(true)) {
+        return MergeDirective::Delete;
+    }
+    if map.get(MERGE_DIRECTIVE).and_then(|mode| mode.as_str()) == Some(REPLACE_MERGE_MODE) {
+        return MergeDirective::Replace;
+    }
+    if !map.is_empty()
+        && map.iter().all(|(key, items)| {
+            (key == APPEND_DIRECTIVE || key == PREPEND_DIRECTIVE) && items.is_array()
+        })
+    {
+        return MergeDirective::Array;
+    }
+    MergeDirective::None
+}
+
+/// Takes the items to prepend and append from a value with `MergeDirective::Array`.
+fn take_array_directive(
+    value: &mut serde_json::Value,
+) -> (Vec<serde_json::Value>, Vec<serde_json::Value>) {
+    let mut take = |directive: &str| match value.get_mut(directive).map(serde_json::Value::take) {
+        Some(serde_json::Value::Array(items)) => items,
+        _ => Vec::new(),
+    };
+    let prepend = take(PREPEND_DIRECTIVE);
+    let append = take(APPEND_DIRECTIVE);
+    (prepend, append)
+}
+
+/// Merges a lower-priority value into a value with `MergeDirective::Array`.
+fn merge_array_directive(target: &mut serde_json::Value, defaults: &serde_json::Value) {
+    let (mut prepend, mut append) = take_array_directive(target);
+    if get_merge_directive(defaults) == MergeDirective::Array {
+        // The result still needs to be applied to a lower-priority array.
+        if let Some(serde_json::Value::Array(items)) = defaults.get(PREPEND_DIRECTIVE) {
+            prepend.extend(items.iter().cloned());
+        }
+        if let Some(serde_json::Value::Array(items)) = defaults.get(APPEND_DIRECTIVE) {
+            append.splice(0..0, items.iter().cloned());
+        }
+        let mut map = serde_json::Map::new();
+        map.insert(
+            PREPEND_DIRECTIVE.to_owned(),
+            serde_json::Value::Array(prepend),
+        );
+        map.insert(
+            APPEND_DIRECTIVE.to_owned(),
+            serde_json::Value::Array(append),
+        );
+        *target = serde_json::Value::Object(map);
+        return;
+    }
+
+    // Values that are not arrays are replaced by the items.
+    if let serde_json::Value::Array(items) = defaults {
+        prepend.extend(items.iter().cloned());
+    }
+    prepend.append(&mut append);
+    *target = serde_json::Value::Array(prepend);
+}
+
+/// Returns `true` if the value has any merge directives, such as `{"$delete": true}`.
+pub(crate) fn contains_merge_directives(value: &serde_json::Value) -> bool {
+    if get_merge_directive(value) != MergeDirective::None {
+        return true;
+    }
+    match value {
+        serde_json::Value::Object(map) => map.values().any(contains_merge_directives),
+        serde_json::Value::Array(items) => items.iter().any(contains_merge_directives),
+        _ => false,
+    }
+}
+
+/// Applies the merge directives that are left after all of the values were merged,
+/// so that the directives are not in the final options.
+/// Arrays are created for `$append` and `$prepend` without a lower-priority array to add to
+/// and `"$merge": "replace"` is removed.
+/// Returns `false` if the value should be removed because it is `{"$delete": true}`.
+pub(crate) fn apply_merge_directives(value: &mut serde_json::Value) -> bool {
+    match get_merge_directive(value) {
+        MergeDirective::Delete => return false,
+        MergeDirective::Array => merge_array_directive(value, &serde_json::Value::Null),
+        MergeDirective::Replace => {
+            if let serde_json::Value::Object(map) = value {
+                map.remove(MERGE_DIRECTIVE);
+            }
+        }
+        MergeDirective::None => {}
+    }
+    match value {
+        serde_json::Value::Object(map) => map.retain(|_, child| apply_merge_directives(child)),
+        serde_json::Value::Array(items) => items.retain_mut(apply_merge_directives),
+        _ => {}
+    }
+    true
+}
+
 /// Merges objects recursively while remembering paths where lower-priority defaults are blocked.
 /// Merges objects recursively with a target for the correct information
 /// and defaults to use if the target is missing a value.
@@ -10,6 +136,8 @@ pub(crate) type FrozenPaths = HashSet<String>;
 /// I.e., values in `target` override values in `defaults`.
 /// If a key is missing from `target`, it is copied from `defaults`.
 /// If the key is present in both `target` and `defaults` and the value is an object, the objects are merged recursively.
+/// Merge directives, such as `{"$append": [...]}`, are kept in `target` so that they can be applied to values with lower priority
+/// and should be removed with `apply_merge_directives` after the last merge.
 #[inline]
 pub(crate) fn merge_json_with_defaults(
     target: &mut serde_json::Value,
@@ -24,6 +152,76 @@ pub(crate) fn merge_json_with_defaults(
     merge_json_with_defaults_at_path(target, defaults, frozen_paths, &mut path);
 }
 
+/// Merges only the values at the path of unescaped `segments` from `sources`,
+/// which are ordered from highest to lowest priority.
+/// The result is the same as merging the entire sources and then getting the value at the path,
+/// but values outside of the path are not copied or merged.
+/// Returns `None` if the merged sources would not have a value at the path.
+pub(crate) fn merge_json_at_path(
+    sources: &[&serde_json::Value],
+    segments: &[String],
+) -> Option<serde_json::Value> {
+    let mut result: Option<serde_json::Value> = None;
+    let mut frozen_paths = FrozenPaths::new();
+    // The deepest prefix of the path where a higher-priority source has an object.
+    let mut object_depth = 0;
+    for source in sources {
+        let mut current = *source;
+        let mut depth = 0;
+        let mut is_missing = false;
+        while depth < segments.len() {
+            match current {
+                serde_json::Value::Object(map) => match map.get(&segments[depth]) {
+                    Some(child) => {
+                        current = child;
+                        depth += 1;
+                    }
+                    None => {
+                        is_missing = true;
+                        break;
+                    }
+                },
+                _ => break,
+            }
+        }
+
+        if is_missing {
+            // The source has an object at this prefix, but nothing at the path.
+            object_depth = object_depth.max(depth);
+            continue;
+        }
+
+        if depth < segments.len() {
+            // The source has a value that is not an object before the end of the path.
+            if depth == 0 {
+                // Sources should always be objects.
+                continue;
+            }
+            if depth > object_depth {
+                // No higher-priority source has a value at this prefix, so this value wins at the prefix.
+                // Objects are not merged into it, so the rest of the path is only looked up in it.
+                result = get_at_path(current, &segments[depth..]).cloned();
+            }
+            // Otherwise, a higher-priority object at this prefix blocks lower-priority values.
+            break;
+        }
+
+        match &mut result {
+            Some(existing) => merge_json_with_defaults(existing, current, &mut frozen_paths),
+            None => {
+                result = Some(current.clone());
+                object_depth = segments.len();
+            }
+        }
+        if !result.as_ref().is_some_and(|r| r.is_object()) {
+            // Lower-priority values are never merged into a value that is not an object.
+            break;
+        }
+    }
+
+    result
+}
+
 #[inline]
 fn merge_json_with_defaults_at_path(
     target: &mut serde_json::Value,
@@ -31,9 +229,28 @@ fn merge_json_with_defaults_at_path(
     frozen_paths: &mut FrozenPaths,
     path: &mut String,
 ) {
+    match get_merge_directive(target) {
+        MergeDirective::Delete | MergeDirective::Replace => {
+            // Lower-priority values are removed or replaced.
+            frozen_paths.insert(path.clone());
+            return;
+        }
+        MergeDirective::Array => {
+            merge_array_directive(target, defaults);
+            return;
+        }
+        MergeDirective::None => {}
+    }
+    let defaults_directive = get_merge_directive(defaults);
     match (target, defaults) {
-        (serde_json::Value::Object(target_map), serde_json::Value::Object(defaults_map)) => {
+        (serde_json::Value::Object(target_map), serde_json::Value::Object(defaults_map))
+            if defaults_directive == MergeDirective::None
+                || defaults_directive == MergeDirective::Replace =>
+        {
             for (key, defaults_value) in defaults_map {
+                if defaults_directive == MergeDirective::Replace && key == MERGE_DIRECTIVE {
+                    continue;
+                }
                 let previous_path_len = path.len();
                 push_json_pointer_segment(path, key);
 
@@ -57,9 +274,17 @@ fn merge_json_with_defaults_at_path(
 
                 path.truncate(previous_path_len);
             }
+            if defaults_directive == MergeDirective::Replace {
+                // Even lower-priority values are replaced by the merged object.
+                target_map.insert(
+                    MERGE_DIRECTIVE.to_owned(),
+                    serde_json::Value::String(REPLACE_MERGE_MODE.to_owned()),
+                );
+                frozen_paths.insert(path.clone());
+            }
         }
         (serde_json::Value::Object(_), _) => {
-            // `defaults` is not an object.
+            // `defaults` is not an object or it removes or changes an array.
             // A lower-priority primitive would have stopped deeper defaults if it had been the target.
             // Remember that lower-priority objects cannot add children at this path later
             // because the default would have overwritten them if we were merge for low to high,
@@ -95,6 +320,75 @@ mod tests {
         merge_json_with_defaults(target, defaults, &mut frozen_paths);
     }
 
+    /// Checks that merging at the path matches merging everything and then getting the path.
+    fn assert_merge_at_path(sources: &[serde_json::Value], pointer: &str) {
+        let sources: Vec<&serde_json::Value> = sources.iter().collect();
+        let mut expected = sources[0].clone();
+        let mut frozen_paths = FrozenPaths::new();
+        for source in &sources[1..] {
+            merge_json_with_defaults(&mut expected, source, &mut frozen_paths);
+        }
+        let segments = crate::json::pointer::split_json_pointer(pointer);
+        assert_eq!(
+            merge_json_at_path(&sources, &segments),
+            expected.pointer(pointer).cloned(),
+            "pointer: {pointer}"
+        );
+    }
+
+    #[test]
+    fn test_merge_json_at_path() {
+        let sources = [
+            json!({"a": {"b": {"c": 1}, "list": [1, {"x": 1}]}}),
+            json!({"a": {"b": {"d": 2}, "other": 3}}),
+            json!({"a": {"b": {"c": 5, "e": 3}, "list": [2]}}),
+        ];
+        for pointer in [
+            "/a",
+            "/a/b",
+            "/a/b/c",
+            "/a/b/d",
+            "/a/b/e",
+            "/a/list",
+            "/a/list/1/x",
+            "/a/list/2",
+            "/a/missing",
+            "/missing/b",
+        ] {
+            assert_merge_at_path(&sources, pointer);
+        }
+    }
+
+    #[test]
+    fn test_merge_json_at_path_with_blocking_values() {
+        let sources = [
+            json!({"a": {"other": 1}}),
+            json!({"a": {"b": 5}}),
+            json!({"a": {"b": {"c": 1}}}),
+        ];
+        for pointer in ["/a", "/a/b", "/a/b/c"] {
+            assert_merge_at_path(&sources, pointer);
+        }
+
+        let sources = [
+            json!({"a": {"b": {"c": 1}}}),
+            json!({"a": {"b": 5}}),
+            json!({"a": {"b": {"d": 1}}}),
+        ];
+        for pointer in ["/a", "/a/b", "/a/b/c", "/a/b/d"] {
+            assert_merge_at_path(&sources, pointer);
+        }
+
+        let sources = [
+            json!({"a": {"other": 1}}),
+            json!({"a": {"b": [{"c": 1}]}}),
+            json!({"a": {"b": {"0": {"d": 1}}}}),
+        ];
+        for pointer in ["/a/b", "/a/b/0", "/a/b/0/c", "/a/b/0/d"] {
+            assert_merge_at_path(&sources, pointer);
+        }
+    }
+
     #[test]
     fn test_merge_with_defaults() {
         let mut target = json!({"a": 1, "b": 2});
@@ -207,6 +501,71 @@ mod tests {
         assert_eq!(target, json!({"a": 1, "b": 2}));
     }
 
+    /// Merges the sources, which are ordered from highest to lowest priority, and applies the directives.
+    fn merge_all(sources: &[serde_json::Value]) -> serde_json::Value {
+        let mut result = sources[0].clone();
+        let mut frozen_paths = FrozenPaths::new();
+        for source in &sources[1..] {
+            merge_json_with_defaults(&mut result, source, &mut frozen_paths);
+        }
+        apply_merge_directives(&mut result);
+        result
+    }
+
+    #[test]
+    fn test_merge_directives_replace() {
+        let sources = [
+            json!({"a": {"$merge": "replace", "b": 1}}),
+            json!({"a": {"c": 2}}),
+        ];
+        assert_eq!(merge_all(&sources), json!({"a": {"b": 1}}));
+
+        // A replacing object in the middle still merges over higher-priority objects.
+        let sources = [
+            json!({"a": {"b": 1}}),
+            json!({"a": {"$merge": "replace", "c": 2}}),
+            json!({"a": {"d": 3}}),
+        ];
+        assert_eq!(merge_all(&sources), json!({"a": {"b": 1, "c": 2}}));
+    }
+
+    #[test]
+    fn test_merge_directives_arrays() {
+        let sources = [
+            json!({"list": {"$append": [3]}}),
+            json!({"list": {"$prepend": [0], "$append": [2]}}),
+            json!({"list": [1]}),
+            json!({"list": [-1]}),
+        ];
+        assert_eq!(merge_all(&sources), json!({"list": [0, 1, 2, 3]}));
+
+        // Without a lower-priority array, the items are used.
+        let sources = [json!({"list": {"$append": [1]}}), json!({"other": 2})];
+        assert_eq!(merge_all(&sources), json!({"list": [1], "other": 2}));
+
+        // Higher-priority arrays still win.
+        let sources = [json!({"list": [5]}), json!({"list": {"$append": [1]}})];
+        assert_eq!(merge_all(&sources), json!({"list": [5]}));
+    }
+
+    #[test]
+    fn test_merge_directives_delete() {
+        let sources = [
+            json!({"a": {"$delete": true}, "b": {"c": {"$delete": true}}}),
+            json!({"a": 1, "b": {"c": 2, "d": 3}}),
+        ];
+        assert_eq!(merge_all(&sources), json!({"b": {"d": 3}}));
+
+        // A higher-priority value can add the key again, but lower-priority values are still removed.
+        let sources = [
+            json!({"a": {"x": 1}}),
+            json!({"a": {"$delete": true}}),
+            json!({"a": {"y": 2}}),
+        ];
+        assert_eq!(merge_all(&sources), json!({"a": {"x": 1}}));
+        assert!(!contains_merge_directives(&merge_all(&sources)));
+    }
+
     #[test]
     fn test_merge_with_defaults_arrays_not_merged() {
         let mut target = json!({"arr": [1, 2, 3]});
@@ -1,5 +1,8 @@
 pub(crate) mod escape_json_pointer;
+pub(crate) mod hash;
+pub(crate) mod jsonc;
 pub(crate) mod merge;
+pub(crate) mod pointer;
 pub(crate) mod reader;
 
 pub(crate) use escape_json_pointer::escape_json_pointer;
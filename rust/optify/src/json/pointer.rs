@@ -0,0 +1,50 @@
+/// Splits a JSON pointer such as `/a/b~1c` into its unescaped segments: `["a", "b/c"]`.
+pub(crate) fn split_json_pointer(pointer: &str) -> Vec<String> {
+    pointer
+        .split('/')
+        .skip(1)
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
+
+/// Gets the value at the path of unescaped segments.
+/// Segments are property names for objects and indices for arrays.
+pub(crate) fn get_at_path<'a>(
+    value: &'a serde_json::Value,
+    segments: &[String],
+) -> Option<&'a serde_json::Value> {
+    segments
+        .iter()
+        .try_fold(value, |current, segment| match current {
+            serde_json::Value::Object(map) => map.get(segment),
+            serde_json::Value::Array(items) => {
+                segment.parse::<usize>().ok().and_then(|i| items.get(i))
+            }
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_split_json_pointer() {
+        assert_eq!(split_json_pointer(""), Vec::<String>::new());
+        assert_eq!(split_json_pointer("/a"), vec!["a"]);
+        assert_eq!(split_json_pointer("/a/b~1c/d~0e"), vec!["a", "b/c", "d~e"]);
+        assert_eq!(split_json_pointer("/a/"), vec!["a", ""]);
+    }
+
+    #[test]
+    fn test_get_at_path() {
+        let value = json!({"a": {"b": [1, {"c": 2}]}});
+        let path = |pointer: &str| split_json_pointer(pointer);
+        assert_eq!(get_at_path(&value, &path("")), Some(&value));
+        assert_eq!(get_at_path(&value, &path("/a/b/1/c")), Some(&json!(2)));
+        assert_eq!(get_at_path(&value, &path("/a/b/2")), None);
+        assert_eq!(get_at_path(&value, &path("/a/b/x")), None);
+        assert_eq!(get_at_path(&value, &path("/a/b/0/c")), None);
+    }
+}
@@ -1,9 +1,12 @@
 pub mod builder;
 pub mod configurable_string;
-pub(crate) mod configurable_values;
+pub mod configurable_values;
+pub mod error;
 pub(crate) mod json;
 pub mod provider;
 pub mod schema;
+pub mod secret;
 
+pub use error::OptifyError;
 pub use provider::OptionsProvider;
 pub use provider::OptionsWatcher;
@@ -0,0 +1,405 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    io::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Instant,
+};
+
+use super::{CacheEvictionPolicy, CacheOptions};
+
+struct CacheEntry<V> {
+    value: V,
+    size: usize,
+    expires_at: Option<Instant>,
+    /// Identifies the entry in the usage order.
+    id: u64,
+    /// The logical time of the most recent access.
+    last_access: AtomicU64,
+    access_count: AtomicU64,
+}
+
+impl<V> CacheEntry<V> {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+/// Orders the entries by how they were used so that the next entry to evict can be found without scanning every entry.
+struct UsageOrder<K> {
+    clock: u64,
+    keys: HashMap<u64, K>,
+    /// The last access of each entry mapped to the entry's ID.
+    by_recency: BTreeMap<u64, u64>,
+    /// The number of accesses and the last access of each entry mapped to the entry's ID.
+    by_frequency: BTreeMap<(u64, u64), u64>,
+}
+
+impl<K> UsageOrder<K> {
+    fn new() -> Self {
+        Self {
+            clock: 0,
+            keys: HashMap::new(),
+            by_recency: BTreeMap::new(),
+            by_frequency: BTreeMap::new(),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.keys.clear();
+        self.by_recency.clear();
+        self.by_frequency.clear();
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn add<V>(&mut self, entry: &CacheEntry<V>) {
+        let last_access = entry.last_access.load(Ordering::Relaxed);
+        let access_count = entry.access_count.load(Ordering::Relaxed);
+        self.by_recency.insert(last_access, entry.id);
+        self.by_frequency
+            .insert((access_count, last_access), entry.id);
+    }
+
+    fn remove<V>(&mut self, entry: &CacheEntry<V>) {
+        let last_access = entry.last_access.load(Ordering::Relaxed);
+        let access_count = entry.access_count.load(Ordering::Relaxed);
+        self.by_recency.remove(&last_access);
+        self.by_frequency.remove(&(access_count, last_access));
+    }
+
+    fn next_to_evict(&self, eviction_policy: CacheEvictionPolicy) -> Option<&K> {
+        let id = match eviction_policy {
+            CacheEvictionPolicy::LeastRecentlyUsed => self.by_recency.values().next(),
+            CacheEvictionPolicy::LeastFrequentlyUsed => self.by_frequency.values().next(),
+        }?;
+        self.keys.get(id)
+    }
+}
+
+/// A cache with optional limits on the number of entries and their total size.
+/// Lookups only need shared access so that concurrent reads do not block each other
+/// beyond briefly locking the usage order to record the access.
+pub(crate) struct BoundedCache<K, V = serde_json::Value> {
+    entries: HashMap<K, CacheEntry<V>>,
+    usage: Mutex<UsageOrder<K>>,
+    total_bytes: usize,
+    expiring_entry_count: usize,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> BoundedCache<K, V> {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            usage: Mutex::new(UsageOrder::new()),
+            total_bytes: 0,
+            expiring_entry_count: 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.usage_mut().clear();
+        self.total_bytes = 0;
+        self.expiring_entry_count = 0;
+    }
+
+    /// Returns a copy of the cached value if it exists and has not expired.
+    pub fn get(&self, key: &K) -> Option<V> {
+        let entry = self.entries.get(key)?;
+        if entry.is_expired(Instant::now()) {
+            return None;
+        }
+        let mut usage = self
+            .usage
+            .lock()
+            .expect("the cache usage order lock should be held");
+        usage.remove(entry);
+        let now = usage.tick();
+        entry.last_access.store(now, Ordering::Relaxed);
+        entry.access_count.fetch_add(1, Ordering::Relaxed);
+        usage.add(entry);
+        Some(entry.value.clone())
+    }
+
+    /// Adds a value to the cache, evicting entries as needed to respect the limits in `options`.
+    /// `get_size` gives the approximate size of the value in bytes.
+    /// It is only called when `options` has `max_bytes`, otherwise the entry counts as 0 bytes.
+    pub fn insert(
+        &mut self,
+        key: K,
+        value: V,
+        get_size: impl FnOnce() -> usize,
+        options: &CacheOptions,
+    ) {
+        self.remove(&key);
+        if options.max_entries == Some(0) {
+            return;
+        }
+        let size = if options.max_bytes.is_some() {
+            get_size()
+        } else {
+            0
+        };
+        if options.max_bytes.is_some_and(|max_bytes| size > max_bytes) {
+            return;
+        }
+
+        self.remove_expired();
+        while !self.entries.is_empty()
+            && (options
+                .max_entries
+                .is_some_and(|max_entries| self.entries.len() >= max_entries)
+                || options
+                    .max_bytes
+                    .is_some_and(|max_bytes| self.total_bytes + size > max_bytes))
+        {
+            self.evict(options.eviction_policy);
+        }
+
+        let expires_at = options
+            .time_to_live
+            .map(|time_to_live| Instant::now() + time_to_live);
+        if expires_at.is_some() {
+            self.expiring_entry_count += 1;
+        }
+        self.total_bytes += size;
+        let usage = self
+            .usage
+            .get_mut()
+            .expect("the cache usage order should be valid");
+        let now = usage.tick();
+        let entry = CacheEntry {
+            value,
+            size,
+            expires_at,
+            id: now,
+            last_access: AtomicU64::new(now),
+            access_count: AtomicU64::new(0),
+        };
+        usage.add(&entry);
+        usage.keys.insert(entry.id, key.clone());
+        self.entries.insert(key, entry);
+    }
+
+    fn evict(&mut self, eviction_policy: CacheEvictionPolicy) {
+        if let Some(key) = self.usage_mut().next_to_evict(eviction_policy).cloned() {
+            self.remove(&key);
+        }
+    }
+
+    fn remove(&mut self, key: &K) {
+        if let Some(entry) = self.entries.remove(key) {
+            let usage = self
+                .usage
+                .get_mut()
+                .expect("the cache usage order should be valid");
+            usage.remove(&entry);
+            usage.keys.remove(&entry.id);
+            self.total_bytes -= entry.size;
+            if entry.expires_at.is_some() {
+                self.expiring_entry_count -= 1;
+            }
+        }
+    }
+
+    fn remove_expired(&mut self) {
+        if self.expiring_entry_count == 0 {
+            return;
+        }
+        let now = Instant::now();
+        let expired_keys: Vec<K> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.is_expired(now))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired_keys {
+            self.remove(&key);
+        }
+    }
+
+    fn usage_mut(&mut self) -> &mut UsageOrder<K> {
+        self.usage
+            .get_mut()
+            .expect("the cache usage order should be valid")
+    }
+}
+
+/// Counts the bytes written without storing them.
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Gets the length of the serialized JSON for the value.
+pub(crate) fn get_serialized_size(value: &serde_json::Value) -> usize {
+    let mut counter = ByteCounter(0);
+    // Serializing a `serde_json::Value` to a writer that never fails cannot fail.
+    let _ = serde_json::to_writer(&mut counter, value);
+    counter.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn insert(
+        cache: &mut BoundedCache<&'static str>,
+        key: &'static str,
+        value: serde_json::Value,
+        options: &CacheOptions,
+    ) {
+        let size = get_serialized_size(&value);
+        cache.insert(key, value, || size, options);
+    }
+
+    fn options(max_entries: Option<usize>, eviction_policy: CacheEvictionPolicy) -> CacheOptions {
+        CacheOptions {
+            eviction_policy,
+            max_entries,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_least_recently_used_eviction() {
+        let options = options(Some(2), CacheEvictionPolicy::LeastRecentlyUsed);
+        let mut cache = BoundedCache::new();
+        insert(&mut cache, "a", json!(1), &options);
+        insert(&mut cache, "b", json!(2), &options);
+        assert_eq!(cache.get(&"a"), Some(json!(1)));
+
+        insert(&mut cache, "c", json!(3), &options);
+
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.get(&"a"), Some(json!(1)));
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"c"), Some(json!(3)));
+    }
+
+    #[test]
+    fn test_least_frequently_used_eviction() {
+        let options = options(Some(2), CacheEvictionPolicy::LeastFrequentlyUsed);
+        let mut cache = BoundedCache::new();
+        insert(&mut cache, "a", json!(1), &options);
+        insert(&mut cache, "b", json!(2), &options);
+        cache.get(&"a");
+        cache.get(&"a");
+        cache.get(&"b");
+
+        insert(&mut cache, "c", json!(3), &options);
+
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.get(&"a"), Some(json!(1)));
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"c"), Some(json!(3)));
+    }
+
+    #[test]
+    fn test_max_bytes() {
+        let options = CacheOptions {
+            max_bytes: Some(10),
+            ..Default::default()
+        };
+        let mut cache = BoundedCache::new();
+        // Each value is 5 bytes when serialized.
+        insert(&mut cache, "a", json!("abc"), &options);
+        insert(&mut cache, "b", json!("def"), &options);
+        assert_eq!(cache.entries.len(), 2);
+
+        insert(&mut cache, "c", json!("ghi"), &options);
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.get(&"a"), None);
+
+        insert(&mut cache, "d", json!("too long to cache"), &options);
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.get(&"d"), None);
+    }
+
+    #[test]
+    fn test_replacing_entry_updates_size() {
+        let options = CacheOptions {
+            max_bytes: Some(5),
+            ..Default::default()
+        };
+        let mut cache = BoundedCache::new();
+        insert(&mut cache, "a", json!("abc"), &options);
+        insert(&mut cache, "a", json!("def"), &options);
+
+        assert_eq!(cache.entries.len(), 1);
+        assert_eq!(cache.total_bytes, 5);
+        assert_eq!(cache.get(&"a"), Some(json!("def")));
+    }
+
+    #[test]
+    fn test_size_only_computed_with_max_bytes() {
+        let mut cache = BoundedCache::new();
+        cache.insert(
+            "a",
+            json!(1),
+            || panic!("the size should not be needed"),
+            &CacheOptions::default(),
+        );
+
+        assert_eq!(cache.total_bytes, 0);
+        assert_eq!(cache.get(&"a"), Some(json!(1)));
+    }
+
+    #[test]
+    fn test_eviction_after_removals() {
+        let options = options(Some(2), CacheEvictionPolicy::LeastRecentlyUsed);
+        let mut cache = BoundedCache::new();
+        insert(&mut cache, "a", json!(1), &options);
+        insert(&mut cache, "b", json!(2), &options);
+        insert(&mut cache, "a", json!(3), &options);
+
+        insert(&mut cache, "c", json!(4), &options);
+
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.get(&"a"), Some(json!(3)));
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.usage_mut().keys.len(), 2);
+    }
+
+    #[test]
+    fn test_time_to_live() {
+        let options = CacheOptions {
+            time_to_live: Some(std::time::Duration::ZERO),
+            ..Default::default()
+        };
+        let mut cache = BoundedCache::new();
+        insert(&mut cache, "a", json!(1), &options);
+        assert_eq!(cache.get(&"a"), None);
+
+        insert(&mut cache, "b", json!(2), &CacheOptions::default());
+        assert_eq!(cache.entries.len(), 1);
+        assert_eq!(cache.get(&"b"), Some(json!(2)));
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut cache = BoundedCache::new();
+        insert(&mut cache, "a", json!(1), &CacheOptions::default());
+        cache.clear();
+
+        assert_eq!(cache.entries.len(), 0);
+        assert_eq!(cache.total_bytes, 0);
+        assert_eq!(cache.get(&"a"), None);
+    }
+}
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+/// Determines which entry is removed when a cache is full.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CacheEvictionPolicy {
+    /// Evict the least recently used entry.
+    #[default]
+    LeastRecentlyUsed,
+    /// Evict the least frequently used entry.
+    /// Ties are broken by evicting the least recently used entry.
+    LeastFrequentlyUsed,
+}
+
+/// Determines which results are cached.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CacheScope {
+    /// Cache the results for a specific key from `get_options_with_preferences`.
+    Options,
+    /// Cache the results for the entire configuration from `get_all_options`.
+    EntireConfig,
+    /// Cache the results for both specific keys and the entire configuration.
+    #[default]
+    Both,
+}
+
+impl CacheScope {
+    pub(crate) fn includes_options(&self) -> bool {
+        matches!(self, CacheScope::Options | CacheScope::Both)
+    }
+
+    pub(crate) fn includes_entire_config(&self) -> bool {
+        matches!(self, CacheScope::EntireConfig | CacheScope::Both)
+    }
+}
+
+/// Options for caching results.
+/// Pass an instance to enable caching.
+/// The limits are enforced when results are added to the cache, so they apply to each cache in the provider separately.
+/// By default, the caches are unbounded and entries never expire.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheOptions {
+    /// Allows caching options with resolved `Optify.Secret` values.
+    /// By default, options for keys with secrets are not cached when the secrets are resolved
+    /// so that secrets are not kept in memory longer than needed and changes to secrets are used.
+    pub are_secrets_cached: bool,
+    /// The policy to use to pick which entry to evict when a limit is reached.
+    pub eviction_policy: CacheEvictionPolicy,
+    /// The maximum approximate size of the cached values in bytes.
+    /// The size of a value is the length of its serialized JSON.
+    /// A value larger than this limit is not cached.
+    pub max_bytes: Option<usize>,
+    /// The maximum number of entries to keep in a cache.
+    pub max_entries: Option<usize>,
+    /// Determines which results are cached.
+    pub scope: CacheScope,
+    /// How long an entry stays valid after it was added to the cache.
+    pub time_to_live: Option<Duration>,
+}
+
+impl CacheOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
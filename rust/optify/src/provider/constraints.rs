@@ -1,6 +1,8 @@
 use serde::Deserialize;
 use std::hash::{Hash, Hasher};
 
+use crate::json::hash::hash_json_value;
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct Constraints {
     pub constraints: serde_json::Value,
@@ -8,8 +10,7 @@ pub struct Constraints {
 
 impl Hash for Constraints {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        // Hash the JSON value as a string for consistency
-        self.constraints.to_string().hash(state);
+        hash_json_value(&self.constraints, state);
     }
 }
 
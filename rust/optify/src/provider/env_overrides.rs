@@ -0,0 +1,207 @@
+use serde::Serialize;
+
+use crate::error::OptifyError;
+use crate::json::escape_json_pointer;
+use crate::provider::SourceValue;
+
+/// The prefix for environment variables that override options when `BuilderOptions::env_overrides_prefix` is set to it.
+pub const DEFAULT_ENV_OVERRIDES_PREFIX: &str = "OPTIFY";
+
+/// Separates the prefix and the keys in the name of an environment variable, such as `OPTIFY__myConfig__myObject__one`.
+pub const ENV_OVERRIDES_SEPARATOR: &str = "__";
+
+/// An override from an environment variable that was applied when the provider was built.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EnvOverride {
+    /// The JSON pointer to the value in the options, such as `/myConfig/myObject/one`.
+    pub pointer: String,
+
+    pub value: serde_json::Value,
+
+    /// The name of the environment variable, such as `OPTIFY__myConfig__myObject__one`.
+    pub variable: String,
+}
+
+/// The overrides from environment variables for a provider.
+#[derive(Clone, Debug)]
+pub(crate) struct EnvOverrides {
+    /// Sorted by the name of the variable.
+    pub(crate) overrides: Vec<EnvOverride>,
+    /// The overrides merged into one value with the same structure as the options.
+    pub(crate) value: SourceValue,
+}
+
+/// Reads the overrides from the environment variables of the process that start with `"{prefix}__"`.
+pub(crate) fn read_env_overrides(prefix: &str) -> Result<Option<EnvOverrides>, OptifyError> {
+    // Variables that are not valid Unicode cannot have a valid prefix or keys.
+    let variables = std::env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)));
+    parse_env_overrides(prefix, variables)
+}
+
+/// Parses the overrides from the variables that start with `"{prefix}__"`.
+/// Values are parsed as JSON when possible, so `5` is a number and `{"a": 1}` is an object.
+/// Other values, such as `hello`, are strings.
+fn parse_env_overrides(
+    prefix: &str,
+    variables: impl IntoIterator<Item = (String, String)>,
+) -> Result<Option<EnvOverrides>, OptifyError> {
+    let full_prefix = format!("{prefix}{ENV_OVERRIDES_SEPARATOR}");
+    let mut variables: Vec<(String, String)> = variables
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(&full_prefix))
+        .collect();
+    if variables.is_empty() {
+        return Ok(None);
+    }
+    // Sort so that conflicts are reported consistently.
+    variables.sort_unstable();
+
+    let mut overrides = Vec::with_capacity(variables.len());
+    let mut merged = serde_json::Value::Object(serde_json::Map::new());
+    for (name, raw_value) in variables {
+        let keys: Vec<&str> = name[full_prefix.len()..]
+            .split(ENV_OVERRIDES_SEPARATOR)
+            .collect();
+        if keys.iter().any(|key| key.is_empty()) {
+            return Err(OptifyError::InvalidEnvironmentVariable {
+                name,
+                message: format!(
+                    "Keys must not be empty. Separate keys with {ENV_OVERRIDES_SEPARATOR:?}."
+                ),
+            });
+        }
+        let value =
+            serde_json::from_str(&raw_value).unwrap_or(serde_json::Value::String(raw_value));
+
+        let (last_key, parent_keys) = keys.split_last().expect("there should be a key");
+        let mut pointer = String::new();
+        for key in parent_keys {
+            push_json_pointer_segment(&mut pointer, key);
+            if merged.pointer(&pointer).is_some_and(|v| !v.is_object()) {
+                return Err(OptifyError::InvalidEnvironmentVariable {
+                    name,
+                    message: format!(
+                        "Another variable already sets a value that is not an object at {pointer:?}."
+                    ),
+                });
+            }
+        }
+        push_json_pointer_segment(&mut pointer, last_key);
+        if merged.pointer(&pointer).is_some() {
+            return Err(OptifyError::InvalidEnvironmentVariable {
+                name,
+                message: format!("Another variable already sets a value at {pointer:?}."),
+            });
+        }
+
+        let mut current = &mut merged;
+        for key in parent_keys {
+            current = current
+                .as_object_mut()
+                .expect("the parents of the value should be objects")
+                .entry(*key)
+                .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+        }
+        current
+            .as_object_mut()
+            .expect("the parent of the value should be an object")
+            .insert((*last_key).to_owned(), value.clone());
+        overrides.push(EnvOverride {
+            pointer,
+            value,
+            variable: name,
+        });
+    }
+
+    Ok(Some(EnvOverrides {
+        overrides,
+        value: merged,
+    }))
+}
+
+fn push_json_pointer_segment(pointer: &mut String, key: &str) {
+    escape_json_pointer!(key);
+    pointer.push('/');
+    pointer.push_str(&key);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn variables(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_env_overrides() {
+        let env_overrides = parse_env_overrides(
+            "OPTIFY",
+            variables(&[
+                ("OPTIFY__myConfig__myObject__one", "5"),
+                ("OPTIFY__myConfig__name", "hello"),
+                ("OPTIFY__myConfig__list", "[1, 2]"),
+                ("OPTIFY__other__a/b", "true"),
+                ("OPTIFYX__myConfig__ignored", "1"),
+                ("PATH", "/usr/bin"),
+            ]),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            env_overrides.value,
+            json!({
+                "myConfig": {"list": [1, 2], "myObject": {"one": 5}, "name": "hello"},
+                "other": {"a/b": true}
+            })
+        );
+        let pointers: Vec<&str> = env_overrides
+            .overrides
+            .iter()
+            .map(|o| o.pointer.as_str())
+            .collect();
+        assert_eq!(
+            pointers,
+            vec![
+                "/myConfig/list",
+                "/myConfig/myObject/one",
+                "/myConfig/name",
+                "/other/a~1b"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_env_overrides_without_variables() {
+        assert!(
+            parse_env_overrides("OPTIFY", variables(&[("HOME", "/root")]))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_parse_env_overrides_errors() {
+        let error =
+            parse_env_overrides("OPTIFY", variables(&[("OPTIFY__a____b", "1")])).unwrap_err();
+        assert!(matches!(
+            error,
+            OptifyError::InvalidEnvironmentVariable { name, .. } if name == "OPTIFY__a____b"
+        ));
+
+        let error = parse_env_overrides(
+            "OPTIFY",
+            variables(&[("OPTIFY__a", "1"), ("OPTIFY__a__b", "2")]),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            OptifyError::InvalidEnvironmentVariable { name, .. } if name == "OPTIFY__a__b"
+        ));
+    }
+}
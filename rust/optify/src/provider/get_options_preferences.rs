@@ -12,6 +12,9 @@ pub struct GetOptionsPreferences {
     pub constraints: Option<Constraints>,
     /// Overrides to apply after the built configuration.
     pub overrides: Option<SourceValue>,
+    /// Replaces `Optify.Secret` values with `REDACTED_SECRET` instead of resolving them,
+    /// such as when showing options to people.
+    pub redact_secrets: bool,
     /// Determines if the feature names should be converted to canonical feature names.
     /// Defaults to false: given features names will be converted to canonical feature names before looking for features or options.
     pub skip_feature_name_conversion: bool,
@@ -30,6 +33,7 @@ impl GetOptionsPreferences {
             are_configurable_values_enabled: false,
             constraints: None,
             overrides: None,
+            redact_secrets: false,
             skip_feature_name_conversion: false,
         }
     }
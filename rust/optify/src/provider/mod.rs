@@ -1,11 +1,21 @@
+pub(crate) mod bounded_cache;
+pub(crate) mod cache_options;
 pub mod constraints;
+pub(crate) mod env_overrides;
 pub(crate) mod get_options_preferences;
+pub(crate) mod options_context;
+pub(crate) mod options_explanation;
 pub(crate) mod provider_impl;
 pub(crate) mod provider_trait;
+pub(crate) mod snapshot;
 pub(crate) mod watcher;
 pub(crate) mod watcher_options;
 
+pub use cache_options::*;
+pub use env_overrides::{EnvOverride, DEFAULT_ENV_OVERRIDES_PREFIX, ENV_OVERRIDES_SEPARATOR};
 pub use get_options_preferences::*;
+pub use options_context::*;
+pub use options_explanation::*;
 pub use provider_impl::*;
 pub use provider_trait::*;
 pub use watcher::*;
@@ -0,0 +1,114 @@
+use std::{ops::Deref, sync::Arc};
+
+use serde::de::DeserializeOwned;
+
+use crate::error::OptifyError;
+use crate::provider::{GetOptionsPreferences, OptionsProvider, OptionsRegistry};
+
+/// The provider that a context reads from.
+enum ProviderHandle<'a> {
+    Borrowed(&'a OptionsProvider),
+    /// A snapshot of an `OptionsWatcher`'s provider so that reloads do not affect the context.
+    Shared(Arc<OptionsProvider>),
+}
+
+impl Deref for ProviderHandle<'_> {
+    type Target = OptionsProvider;
+
+    fn deref(&self) -> &OptionsProvider {
+        match self {
+            ProviderHandle::Borrowed(provider) => provider,
+            ProviderHandle::Shared(provider) => provider,
+        }
+    }
+}
+
+/// Options for a set of features and preferences that are resolved once, such as for a request.
+/// The feature names are converted to canonical feature names and filtered by the constraints when the context is created.
+///
+/// When created from an `OptionsWatcher`, the context keeps using the configuration that was loaded when it was created,
+/// even if the watcher reloads the files.
+pub struct OptionsContext<'a> {
+    provider: ProviderHandle<'a>,
+    /// The feature names given when the context was created, for error messages.
+    original_feature_names: Vec<String>,
+    feature_names: Vec<String>,
+    preferences: Option<GetOptionsPreferences>,
+}
+
+impl<'a> OptionsContext<'a> {
+    fn new(
+        provider: ProviderHandle<'a>,
+        feature_names: &[impl AsRef<str>],
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<Self, OptifyError> {
+        let filtered_feature_names =
+            provider.get_filtered_feature_names(feature_names, preferences)?;
+        Ok(Self {
+            provider,
+            original_feature_names: feature_names
+                .iter()
+                .map(|f| f.as_ref().to_owned())
+                .collect(),
+            feature_names: filtered_feature_names,
+            preferences: preferences.cloned(),
+        })
+    }
+
+    pub(crate) fn from_provider(
+        provider: &'a OptionsProvider,
+        feature_names: &[impl AsRef<str>],
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<Self, OptifyError> {
+        Self::new(
+            ProviderHandle::Borrowed(provider),
+            feature_names,
+            preferences,
+        )
+    }
+
+    pub(crate) fn from_shared_provider(
+        provider: Arc<OptionsProvider>,
+        feature_names: &[impl AsRef<str>],
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<Self, OptifyError> {
+        Self::new(ProviderHandle::Shared(provider), feature_names, preferences)
+    }
+
+    /// Returns the canonical feature names that passed the constraints, in order of increasing priority.
+    pub fn feature_names(&self) -> &[String] {
+        &self.feature_names
+    }
+
+    /// Gets the options for a key.
+    /// The key can also be a JSON pointer, such as `/myConfig/myObject/one`, to get a nested value.
+    pub fn get(&self, key: &str) -> Result<serde_json::Value, OptifyError> {
+        self.provider.get_options_for_filtered_features(
+            key,
+            &self.feature_names,
+            &self.original_feature_names,
+            None,
+            self.preferences.as_ref(),
+        )
+    }
+
+    /// Gets all of the options.
+    pub fn get_all(&self) -> Result<serde_json::Value, OptifyError> {
+        self.provider.get_all_options_for_filtered_features(
+            &self.feature_names,
+            None,
+            self.preferences.as_ref(),
+        )
+    }
+
+    /// Gets the options for a key and deserializes them into `T`.
+    pub fn get_as<T: DeserializeOwned>(&self, key: &str) -> Result<T, OptifyError> {
+        let value = self.get(key)?;
+        self.provider.deserialize_options(
+            key,
+            &value,
+            &self.original_feature_names,
+            self.preferences.as_ref(),
+        )
+    }
+}
@@ -0,0 +1,356 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::json::escape_json_pointer;
+use crate::json::merge::{
+    get_merge_directive, MergeDirective, APPEND_DIRECTIVE, MERGE_DIRECTIVE, PREPEND_DIRECTIVE,
+};
+
+/// Where a value in the options came from.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ValueSource {
+    /// The name of the environment variable that the value came from.
+    /// See `BuilderOptions::env_overrides_prefix`.
+    /// It is left out when serialized if the value did not come from an environment variable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment_variable: Option<String>,
+
+    /// The canonical name of the feature with the value in its file.
+    /// `None` when the value came from the overrides in the preferences or from an environment variable.
+    pub feature: Option<String>,
+
+    /// The canonical names of the features that imported `feature`,
+    /// starting with the feature that was requested.
+    /// Empty when `feature` was requested directly.
+    pub imported_by: Vec<String>,
+}
+
+/// A value that was replaced by a value from a source with a higher priority.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ShadowedValue {
+    /// The JSON pointer to the value, relative to the options for the key.
+    pub pointer: String,
+
+    pub source: ValueSource,
+
+    pub value: serde_json::Value,
+}
+
+/// Explains where a leaf in the options came from.
+/// Leaves are values that are not merged, such as strings, numbers, arrays, and empty objects.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ExplainedValue {
+    /// The JSON pointer to the value, relative to the options for the key.
+    pub pointer: String,
+
+    /// The values that were replaced by this value, from the highest to the lowest priority.
+    pub shadowed: Vec<ShadowedValue>,
+
+    /// The source of the value that was used.
+    pub source: ValueSource,
+
+    pub value: serde_json::Value,
+}
+
+/// Tracks where each leaf came from while values are applied from the lowest to the highest priority.
+#[derive(Default)]
+pub(crate) struct ExplanationBuilder {
+    leaves: BTreeMap<String, ExplainedValue>,
+}
+
+impl ExplanationBuilder {
+    /// Applies a value with a higher priority than all of the values that were already applied.
+    pub(crate) fn apply(&mut self, value: &serde_json::Value, source: &ValueSource) {
+        let mut pointer = String::new();
+        self.apply_at(&mut pointer, value, source);
+    }
+
+    /// Merge directives are applied like they are when the options are merged:
+    /// `{"$delete": true}` removes the leaves at the pointer,
+    /// `"$merge": "replace"` shadows the leaves at the pointer instead of merging with them,
+    /// and `$append` or `$prepend` make a leaf for the array with the items of the lower-priority array,
+    /// which is kept as a shadowed value.
+    fn apply_at(&mut self, pointer: &mut String, value: &serde_json::Value, source: &ValueSource) {
+        match get_merge_directive(value) {
+            MergeDirective::Delete => {
+                self.take_leaves_at_or_under(pointer);
+                return;
+            }
+            MergeDirective::Array => {
+                let replaced = self.take_leaves_at_or_under(pointer);
+                let directive_items = |directive: &str| match value.get(directive) {
+                    Some(serde_json::Value::Array(items)) => items.clone(),
+                    _ => Vec::new(),
+                };
+                let mut items = directive_items(PREPEND_DIRECTIVE);
+                // The first replaced value is the leaf that was used, followed by the values that it shadowed.
+                if let Some(ShadowedValue {
+                    pointer: replaced_pointer,
+                    value: serde_json::Value::Array(lower_items),
+                    ..
+                }) = replaced.first()
+                {
+                    if replaced_pointer == pointer {
+                        items.extend(lower_items.iter().cloned());
+                    }
+                }
+                items.extend(directive_items(APPEND_DIRECTIVE));
+                self.insert_leaf(pointer, serde_json::Value::Array(items), source, replaced);
+                return;
+            }
+            MergeDirective::Replace => {
+                let replaced = self.take_leaves_at_or_under(pointer);
+                let mut map = value.as_object().cloned().unwrap_or_default();
+                map.remove(MERGE_DIRECTIVE);
+                self.apply_at(pointer, &serde_json::Value::Object(map), source);
+                for leaf in self.leaves.values_mut() {
+                    if is_at_or_under(&leaf.pointer, pointer) {
+                        leaf.shadowed.extend(replaced.iter().cloned());
+                    }
+                }
+                return;
+            }
+            MergeDirective::None => {}
+        }
+
+        match value {
+            serde_json::Value::Object(map) if !map.is_empty() => {
+                // A leaf at this pointer is replaced by the object.
+                let replaced = self.leaves.remove(pointer.as_str());
+                for (key, child) in map {
+                    let previous_len = pointer.len();
+                    escape_json_pointer!(key);
+                    pointer.push('/');
+                    pointer.push_str(&key);
+                    self.apply_at(pointer, child, source);
+                    pointer.truncate(previous_len);
+                }
+                if let Some(replaced) = replaced {
+                    let shadowed = to_shadowed_values(replaced);
+                    for leaf in self.leaves.values_mut() {
+                        if is_at_or_under(&leaf.pointer, pointer) {
+                            leaf.shadowed.extend(shadowed.iter().cloned());
+                        }
+                    }
+                }
+            }
+            _ => {
+                // The value replaces everything at and under this pointer.
+                let shadowed = self.take_leaves_at_or_under(pointer);
+                self.insert_leaf(pointer, value.clone(), source, shadowed);
+            }
+        }
+    }
+
+    /// Removes the leaves at or under `pointer` and returns them as shadowed values.
+    fn take_leaves_at_or_under(&mut self, pointer: &str) -> Vec<ShadowedValue> {
+        let replaced_pointers: Vec<String> = self
+            .leaves
+            .keys()
+            .filter(|leaf_pointer| is_at_or_under(leaf_pointer, pointer))
+            .cloned()
+            .collect();
+        replaced_pointers
+            .iter()
+            .filter_map(|leaf_pointer| self.leaves.remove(leaf_pointer))
+            .flat_map(to_shadowed_values)
+            .collect()
+    }
+
+    fn insert_leaf(
+        &mut self,
+        pointer: &str,
+        value: serde_json::Value,
+        source: &ValueSource,
+        shadowed: Vec<ShadowedValue>,
+    ) {
+        self.leaves.insert(
+            pointer.to_owned(),
+            ExplainedValue {
+                pointer: pointer.to_owned(),
+                shadowed,
+                source: source.clone(),
+                value,
+            },
+        );
+    }
+
+    /// Gets the explanations for the values at or under `pointer`, ordered by their pointers.
+    /// The pointers in the result are relative to `pointer`.
+    pub(crate) fn build_at(mut self, pointer: &str) -> Vec<ExplainedValue> {
+        // The pointer may be inside of a leaf, such as an array.
+        let enclosing_leaf_pointer = self
+            .leaves
+            .keys()
+            .find(|leaf_pointer| {
+                leaf_pointer.len() < pointer.len() && is_at_or_under(pointer, leaf_pointer)
+            })
+            .cloned();
+        if let Some(leaf_pointer) = enclosing_leaf_pointer {
+            let leaf = self.leaves.remove(&leaf_pointer).unwrap();
+            return leaf
+                .value
+                .pointer(&pointer[leaf_pointer.len()..])
+                .map(|value| ExplainedValue {
+                    pointer: String::new(),
+                    shadowed: rebase_shadowed_values(leaf.shadowed, pointer),
+                    source: leaf.source,
+                    value: value.clone(),
+                })
+                .into_iter()
+                .collect();
+        }
+
+        self.leaves
+            .into_values()
+            .filter(|leaf| is_at_or_under(&leaf.pointer, pointer))
+            .map(|leaf| ExplainedValue {
+                pointer: leaf.pointer[pointer.len()..].to_owned(),
+                shadowed: rebase_shadowed_values(leaf.shadowed, pointer),
+                source: leaf.source,
+                value: leaf.value,
+            })
+            .collect()
+    }
+}
+
+/// Indicates if `pointer` is `base_pointer` or a descendant of it.
+fn is_at_or_under(pointer: &str, base_pointer: &str) -> bool {
+    pointer
+        .strip_prefix(base_pointer)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+fn to_shadowed_values(leaf: ExplainedValue) -> Vec<ShadowedValue> {
+    let mut result = vec![ShadowedValue {
+        pointer: leaf.pointer,
+        source: leaf.source,
+        value: leaf.value,
+    }];
+    result.extend(leaf.shadowed);
+    result
+}
+
+/// Makes the pointers of the shadowed values relative to `pointer`.
+/// Shadowed values that enclose `pointer` are narrowed to the value at `pointer`.
+/// Shadowed values without a value at `pointer` are removed.
+fn rebase_shadowed_values(shadowed: Vec<ShadowedValue>, pointer: &str) -> Vec<ShadowedValue> {
+    shadowed
+        .into_iter()
+        .filter_map(|shadowed_value| {
+            if is_at_or_under(&shadowed_value.pointer, pointer) {
+                Some(ShadowedValue {
+                    pointer: shadowed_value.pointer[pointer.len()..].to_owned(),
+                    ..shadowed_value
+                })
+            } else if is_at_or_under(pointer, &shadowed_value.pointer) {
+                let value = shadowed_value
+                    .value
+                    .pointer(&pointer[shadowed_value.pointer.len()..])?
+                    .clone();
+                Some(ShadowedValue {
+                    pointer: String::new(),
+                    source: shadowed_value.source,
+                    value,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn source(feature: &str) -> ValueSource {
+        ValueSource {
+            environment_variable: None,
+            feature: Some(feature.to_owned()),
+            imported_by: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_leaves_track_winner_and_shadowed_values() {
+        let mut builder = ExplanationBuilder::default();
+        builder.apply(&json!({"a": 1, "b": {"c": [1]}}), &source("low"));
+        builder.apply(&json!({"a": 2, "b": 3}), &source("high"));
+
+        let explanation = builder.build_at("");
+        assert_eq!(
+            explanation,
+            vec![
+                ExplainedValue {
+                    pointer: "/a".to_owned(),
+                    shadowed: vec![ShadowedValue {
+                        pointer: "/a".to_owned(),
+                        source: source("low"),
+                        value: json!(1),
+                    }],
+                    source: source("high"),
+                    value: json!(2),
+                },
+                ExplainedValue {
+                    pointer: "/b".to_owned(),
+                    shadowed: vec![ShadowedValue {
+                        pointer: "/b/c".to_owned(),
+                        source: source("low"),
+                        value: json!([1]),
+                    }],
+                    source: source("high"),
+                    value: json!(3),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_object_replaces_leaf() {
+        let mut builder = ExplanationBuilder::default();
+        builder.apply(&json!({"a": "text"}), &source("low"));
+        builder.apply(&json!({"a": {"b": true}}), &source("high"));
+
+        let explanation = builder.build_at("/a");
+        assert_eq!(explanation.len(), 1);
+        assert_eq!(explanation[0].pointer, "/b");
+        assert_eq!(explanation[0].source, source("high"));
+        assert_eq!(
+            explanation[0].shadowed,
+            vec![ShadowedValue {
+                pointer: String::new(),
+                source: source("low"),
+                value: json!("text"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_build_at_pointer_inside_leaf() {
+        let mut builder = ExplanationBuilder::default();
+        builder.apply(&json!({"list": [1, {"x": 2}]}), &source("low"));
+        builder.apply(&json!({"list": [3, {"x": 4}]}), &source("high"));
+
+        let explanation = builder.build_at("/list/1/x");
+        assert_eq!(
+            explanation,
+            vec![ExplainedValue {
+                pointer: String::new(),
+                shadowed: vec![ShadowedValue {
+                    pointer: String::new(),
+                    source: source("low"),
+                    value: json!(2),
+                }],
+                source: source("high"),
+                value: json!(4),
+            }]
+        );
+        assert_eq!(
+            ExplanationBuilder::default().build_at("/missing"),
+            Vec::new()
+        );
+    }
+}
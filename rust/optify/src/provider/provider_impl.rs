@@ -1,18 +1,49 @@
-use std::{collections::HashMap, path::Path, sync::RwLock};
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use serde::de::DeserializeOwned;
 
 use crate::builder::builder_options::BuilderOptions;
 use crate::configurable_values::configurable_list_impl::ConfigurableList;
+use crate::configurable_values::configurable_value_type::get_configurable_value_type;
+use crate::configurable_values::{ConfigurableValueContext, ConfigurableValueTypes};
 
 use crate::{
     builder::{OptionsProviderBuilder, OptionsRegistryBuilder},
     configurable_string::LoadedFiles,
-    json::merge::{merge_json_with_defaults, FrozenPaths},
-    provider::GetOptionsPreferences,
+    error::OptifyError,
+    json::{
+        escape_json_pointer,
+        hash::hash_json_value,
+        merge::{
+            apply_merge_directives, contains_merge_directives, merge_json_at_path,
+            merge_json_with_defaults, FrozenPaths,
+        },
+        pointer::{get_at_path, split_json_pointer},
+    },
+    provider::{constraints::Constraints, GetOptionsPreferences},
     schema::{conditions::ConditionExpression, metadata::OptionsMetadata},
 };
 
-use super::OptionsRegistry;
+use super::bounded_cache::{get_serialized_size, BoundedCache};
+use super::env_overrides::{read_env_overrides, EnvOverride, EnvOverrides};
+use super::options_explanation::ExplanationBuilder;
+use super::snapshot::{
+    check_snapshot_registrations, from_snapshot_sources, from_snapshot_value, to_snapshot_sources,
+    SnapshotContents, SnapshotValue,
+};
+use super::{CacheOptions, ExplainedValue, OptionsContext, OptionsRegistry, ValueSource};
+use crate::configurable_string::configurable_string_impl::TemplateScopes;
+use crate::configurable_string::liquid_templates::{compile_liquid_templates, LiquidTemplates};
 use crate::configurable_string::ConfigurableString;
+use crate::configurable_values::reference::Reference;
+use crate::secret::{Secret, SecretResolvers, REDACTED_SECRET};
 
 // Replicating https://github.com/juharris/dotnet-OptionsProvider/blob/main/src/OptionsProvider/OptionsProvider/IOptionsProvider.cs
 // and https://github.com/juharris/dotnet-OptionsProvider/blob/main/src/OptionsProvider/OptionsProvider/OptionsProviderWithDefaults.cs
@@ -22,92 +53,372 @@ pub(crate) type SourceValue = serde_json::Value;
 pub(crate) type Aliases = HashMap<unicase::UniCase<String>, String>;
 pub(crate) type Conditions = HashMap<String, ConditionExpression>;
 pub(crate) type Features = HashMap<String, OptionsMetadata>;
+/// The canonical feature names imported by each feature, in order of increasing priority.
+pub(crate) type Imports = HashMap<String, Vec<String>>;
 pub(crate) type ReferencedFileToFeatureNames = HashMap<String, Vec<String>>;
 pub(crate) type Sources = HashMap<String, SourceValue>;
 
-pub(crate) type EntireConfigCache = HashMap<Vec<String>, serde_json::Value>;
-pub(crate) type OptionsCache = HashMap<(String, Vec<String>, bool), serde_json::Value>;
+/// The parts of the preferences that can change a cached result.
+/// Constraints change which features are used, which is already in the cache keys,
+/// so only the constraints that Liquid templates could read are included.
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct PreferencesCacheKey {
+    constraints: Option<Constraints>,
+    overrides: Option<SourceValue>,
+    redact_secrets: bool,
+}
+
+impl PreferencesCacheKey {
+    fn new(preferences: Option<&GetOptionsPreferences>, constraints: Option<Constraints>) -> Self {
+        Self {
+            constraints,
+            overrides: preferences.and_then(|p| p.overrides.clone()),
+            redact_secrets: preferences.is_some_and(|p| p.redact_secrets),
+        }
+    }
+}
 
-pub struct CacheOptions {}
+impl Hash for PreferencesCacheKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.constraints.hash(state);
+        // Hash the overrides independently of the order of their properties.
+        match &self.overrides {
+            Some(overrides) => {
+                true.hash(state);
+                hash_json_value(overrides, state);
+            }
+            None => false.hash(state),
+        }
+        self.redact_secrets.hash(state);
+    }
+}
+
+pub(crate) type EntireConfigCache = BoundedCache<(Vec<String>, PreferencesCacheKey)>;
+/// The key, the filtered canonical feature names, whether configurable values are enabled, and the preferences.
+pub(crate) type OptionsCacheKey = (String, Vec<String>, bool, PreferencesCacheKey);
+pub(crate) type OptionsCache = BoundedCache<OptionsCacheKey>;
+/// Deserialized options for each requested type.
+pub(crate) type TypedOptionsCache =
+    BoundedCache<(TypeId, OptionsCacheKey), Arc<dyn Any + Send + Sync>>;
 
 pub struct OptionsProvider {
     // Configurable Values
+    /// Pointers to values of types registered with `with_configurable_value_type`.
+    all_configurable_custom_pointers: Vec<String>,
     all_configurable_list_pointers: Vec<String>,
+    all_configurable_reference_pointers: Vec<String>,
+    all_configurable_secret_pointers: Vec<String>,
     all_configurable_string_pointers: Vec<String>,
+    keyed_configurable_custom_pointers: HashMap<String, Vec<String>>,
     keyed_configurable_list_pointers: HashMap<String, Vec<String>>,
+    /// Pointers to references, which can point to values in other top-level keys.
+    keyed_configurable_reference_pointers: HashMap<String, Vec<String>>,
+    keyed_configurable_secret_pointers: HashMap<String, Vec<String>>,
     keyed_configurable_string_pointers: HashMap<String, Vec<String>>,
+    /// Types for configurable values registered with `with_configurable_value_type`. These are not saved in snapshots.
+    configurable_value_types: ConfigurableValueTypes,
+    /// Resolvers for `Optify.Secret` values. These are not saved in snapshots.
+    secret_resolvers: SecretResolvers,
 
     aliases: Aliases,
     conditions: Conditions,
     features: Features,
+    imports: Imports,
     /// A map of files to their referencing features.
     /// The keys are relative file paths and the values are lists of canonical feature names.
     /// This allows fast lookup of features when a specific file is modified.
     /// This is only populated if the `BuilderOptions` enable file reference tracking.
     referenced_file_to_feature_names: Option<ReferencedFileToFeatureNames>,
+    /// The Liquid templates of configurable strings, which are parsed when building. These are parsed again when loading snapshots.
+    liquid_templates: LiquidTemplates,
     loaded_files: LoadedFiles,
     sources: Sources,
+    /// Whether any of the sources or the overrides from environment variables have merge directives, such as `{"$delete": true}`, that need to be applied to the results.
+    /// See `needs_merge_directives` for the overrides in the preferences.
+    has_merge_directives: bool,
+    /// The options from the files of features with imports before the imports were merged in.
+    /// Used to explain where values came from.
+    sources_without_imports: Sources,
+    /// The overrides from environment variables that were read when the provider was built.
+    env_overrides: Option<EnvOverrides>,
 
     // Caches - using RwLock for thread-safe interior mutability
     entire_config_cache: RwLock<EntireConfigCache>,
     options_cache: RwLock<OptionsCache>,
+    typed_options_cache: RwLock<TypedOptionsCache>,
 }
 
 impl OptionsProvider {
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         aliases: Aliases,
+        all_configurable_custom_pointers: Vec<String>,
         all_configurable_list_pointers: Vec<String>,
+        all_configurable_reference_pointers: Vec<String>,
+        all_configurable_secret_pointers: Vec<String>,
         all_configurable_string_pointers: Vec<String>,
+        keyed_configurable_custom_pointers: HashMap<String, Vec<String>>,
         keyed_configurable_list_pointers: HashMap<String, Vec<String>>,
+        keyed_configurable_reference_pointers: HashMap<String, Vec<String>>,
+        keyed_configurable_secret_pointers: HashMap<String, Vec<String>>,
         keyed_configurable_string_pointers: HashMap<String, Vec<String>>,
         conditions: Conditions,
         features: Features,
+        imports: Imports,
         referenced_file_to_feature_names: Option<ReferencedFileToFeatureNames>,
+        liquid_templates: LiquidTemplates,
         loaded_files: LoadedFiles,
         sources: Sources,
+        sources_without_imports: Sources,
+        env_overrides: Option<EnvOverrides>,
+        configurable_value_types: ConfigurableValueTypes,
+        secret_resolvers: SecretResolvers,
     ) -> Self {
+        let has_merge_directives = sources.values().any(contains_merge_directives)
+            || env_overrides
+                .as_ref()
+                .is_some_and(|env_overrides| contains_merge_directives(&env_overrides.value));
         OptionsProvider {
+            all_configurable_custom_pointers,
             all_configurable_list_pointers,
+            all_configurable_reference_pointers,
+            all_configurable_secret_pointers,
             all_configurable_string_pointers,
+            keyed_configurable_custom_pointers,
             keyed_configurable_list_pointers,
+            keyed_configurable_reference_pointers,
+            keyed_configurable_secret_pointers,
             keyed_configurable_string_pointers,
+            configurable_value_types,
+            secret_resolvers,
             aliases,
             conditions,
             features,
+            imports,
             referenced_file_to_feature_names,
+            liquid_templates,
             loaded_files,
             sources,
+            has_merge_directives,
+            sources_without_imports,
+            env_overrides,
             entire_config_cache: RwLock::new(EntireConfigCache::new()),
             options_cache: RwLock::new(OptionsCache::new()),
+            typed_options_cache: RwLock::new(TypedOptionsCache::new()),
+        }
+    }
+
+    pub(crate) fn from_snapshot_contents(
+        contents: SnapshotContents,
+        options: BuilderOptions,
+    ) -> Result<Self, OptifyError> {
+        let aliases = contents
+            .aliases
+            .into_iter()
+            .map(|(alias, canonical_feature_name)| {
+                (
+                    unicase::UniCase::new(alias.to_owned()),
+                    canonical_feature_name.to_owned(),
+                )
+            })
+            .collect();
+        let conditions: Conditions = from_snapshot_value(contents.conditions)?;
+        let features: Features = from_snapshot_value(contents.features)?;
+        let loaded_files = contents.loaded_files.into_owned();
+        let sources = from_snapshot_sources(contents.sources);
+        let sources_without_imports = from_snapshot_sources(contents.sources_without_imports);
+        check_snapshot_registrations(
+            &sources,
+            &contents.all_configurable_custom_pointers,
+            &contents.all_configurable_secret_pointers,
+            &options,
+        )?;
+        let mut liquid_templates =
+            LiquidTemplates::with_filters_and_tags(&options.liquid_filters, &options.liquid_tags);
+        // Templates can fail to parse if they use filters or tags that were not given.
+        if let Some(error) = compile_liquid_templates(
+            &mut liquid_templates,
+            contents.all_configurable_string_pointers.iter(),
+            &sources,
+            &sources_without_imports,
+            &features,
+            &loaded_files,
+        )
+        .into_iter()
+        .next()
+        {
+            return Err(error);
+        }
+        // Overrides from environment variables are read by the process that loads the snapshot.
+        let env_overrides = match &options.env_overrides_prefix {
+            Some(prefix) => read_env_overrides(prefix)?,
+            None => None,
+        };
+        Ok(Self::new(
+            aliases,
+            contents.all_configurable_custom_pointers.into_owned(),
+            contents.all_configurable_list_pointers.into_owned(),
+            contents.all_configurable_reference_pointers.into_owned(),
+            contents.all_configurable_secret_pointers.into_owned(),
+            contents.all_configurable_string_pointers.into_owned(),
+            contents.keyed_configurable_custom_pointers.into_owned(),
+            contents.keyed_configurable_list_pointers.into_owned(),
+            contents.keyed_configurable_reference_pointers.into_owned(),
+            contents.keyed_configurable_secret_pointers.into_owned(),
+            contents.keyed_configurable_string_pointers.into_owned(),
+            conditions,
+            features,
+            contents.imports.into_owned(),
+            contents
+                .referenced_file_to_feature_names
+                .map(|names| names.into_owned()),
+            liquid_templates,
+            loaded_files,
+            sources,
+            sources_without_imports,
+            env_overrides,
+            options.configurable_value_types,
+            options.secret_resolvers,
+        ))
+    }
+
+    pub(crate) fn conditions_and_features_to_json(
+        &self,
+    ) -> Result<(serde_json::Value, serde_json::Value), serde_json::Error> {
+        Ok((
+            serde_json::to_value(&self.conditions)?,
+            serde_json::to_value(&self.features)?,
+        ))
+    }
+
+    /// `conditions` and `features` are from `conditions_and_features_to_json`.
+    pub(crate) fn to_snapshot_contents<'a>(
+        &'a self,
+        conditions: &'a serde_json::Value,
+        features: &'a serde_json::Value,
+    ) -> SnapshotContents<'a> {
+        SnapshotContents {
+            aliases: self
+                .aliases
+                .iter()
+                .map(|(alias, canonical_feature_name)| {
+                    (alias.as_str(), canonical_feature_name.as_str())
+                })
+                .collect(),
+            all_configurable_custom_pointers: Cow::Borrowed(&self.all_configurable_custom_pointers),
+            all_configurable_list_pointers: Cow::Borrowed(&self.all_configurable_list_pointers),
+            all_configurable_reference_pointers: Cow::Borrowed(
+                &self.all_configurable_reference_pointers,
+            ),
+            all_configurable_secret_pointers: Cow::Borrowed(&self.all_configurable_secret_pointers),
+            all_configurable_string_pointers: Cow::Borrowed(&self.all_configurable_string_pointers),
+            conditions: SnapshotValue::from(conditions),
+            features: SnapshotValue::from(features),
+            imports: Cow::Borrowed(&self.imports),
+            keyed_configurable_custom_pointers: Cow::Borrowed(
+                &self.keyed_configurable_custom_pointers,
+            ),
+            keyed_configurable_list_pointers: Cow::Borrowed(&self.keyed_configurable_list_pointers),
+            keyed_configurable_reference_pointers: Cow::Borrowed(
+                &self.keyed_configurable_reference_pointers,
+            ),
+            keyed_configurable_secret_pointers: Cow::Borrowed(
+                &self.keyed_configurable_secret_pointers,
+            ),
+            keyed_configurable_string_pointers: Cow::Borrowed(
+                &self.keyed_configurable_string_pointers,
+            ),
+            loaded_files: Cow::Borrowed(&self.loaded_files),
+            referenced_file_to_feature_names: self
+                .referenced_file_to_feature_names
+                .as_ref()
+                .map(Cow::Borrowed),
+            sources: to_snapshot_sources(&self.sources),
+            sources_without_imports: to_snapshot_sources(&self.sources_without_imports),
         }
     }
 
+    /// Creates a context that resolves and filters the feature names once
+    /// so that options can be retrieved for them many times, such as for a request.
+    pub fn context(
+        &self,
+        feature_names: &[impl AsRef<str>],
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<OptionsContext<'_>, OptifyError> {
+        OptionsContext::from_provider(self, feature_names, preferences)
+    }
+
+    /// Indicates if the options for `key` may have resolved secrets,
+    /// so they should only be cached when `CacheOptions::are_secrets_cached` is set.
+    fn may_resolve_secrets(&self, key: &str, preferences: Option<&GetOptionsPreferences>) -> bool {
+        let Some(preferences) = preferences else {
+            return false;
+        };
+        if !preferences.are_configurable_values_enabled() || preferences.redact_secrets {
+            return false;
+        }
+        let key = if key.starts_with('/') {
+            split_json_pointer(key).swap_remove(0)
+        } else {
+            key.to_owned()
+        };
+        // References may point to secrets in other keys.
+        self.keyed_configurable_secret_pointers.contains_key(&key)
+            || (self
+                .keyed_configurable_reference_pointers
+                .contains_key(&key)
+                && !self.keyed_configurable_secret_pointers.is_empty())
+    }
+
+    /// Gets the overrides to apply over the options from the features.
+    /// Overrides from environment variables have the highest priority,
+    /// so they are merged over the overrides in the preferences.
+    fn get_overrides<'a>(
+        &'a self,
+        preferences: Option<&'a GetOptionsPreferences>,
+    ) -> Option<Cow<'a, SourceValue>> {
+        let overrides = preferences.and_then(|p| p.overrides.as_ref());
+        match (&self.env_overrides, overrides) {
+            (None, overrides) => overrides.map(Cow::Borrowed),
+            (Some(env_overrides), None) => Some(Cow::Borrowed(&env_overrides.value)),
+            (Some(env_overrides), Some(overrides)) => {
+                let mut result = env_overrides.value.clone();
+                merge_json_with_defaults(&mut result, overrides, &mut FrozenPaths::new());
+                Some(Cow::Owned(result))
+            }
+        }
+    }
+
+    /// Indicates if merge directives need to be applied to results that were merged with `overrides`.
+    fn needs_merge_directives(&self, overrides: Option<&SourceValue>) -> bool {
+        self.has_merge_directives || overrides.is_some_and(contains_merge_directives)
+    }
+
     fn get_entire_config(
         &self,
         feature_names: &[String],
         cache_options: Option<&CacheOptions>,
         preferences: Option<&GetOptionsPreferences>,
-    ) -> Result<serde_json::Value, String> {
+    ) -> Result<serde_json::Value, OptifyError> {
+        let cache_options = cache_options.filter(|o| o.scope.includes_entire_config());
         if cache_options.is_some() {
-            match self.get_entire_config_from_cache(feature_names, preferences) {
-                Ok(Some(config)) => return Ok(config),
-                Ok(None) => (),
-                Err(e) => return Err(e),
+            if let Some(config) = self.get_entire_config_from_cache(feature_names, preferences) {
+                return Ok(config);
             }
         };
 
-        let overrides = preferences.and_then(|p| p.overrides.as_ref());
+        let overrides = self.get_overrides(preferences);
+        let overrides = overrides.as_deref();
 
-        let result = match (overrides, feature_names.len()) {
+        let mut result = match (overrides, feature_names.len()) {
             (None, 0) => serde_json::Value::Object(serde_json::Map::new()),
             (None, 1) => {
                 // Avoid merging to an empty object and eagerly take the right configuration.
                 let feature_name = &feature_names[0];
                 self.sources
                     .get(feature_name)
-                    .ok_or_else(|| {
-                        format!("Feature name {feature_name:?} is not a known feature.")
+                    .ok_or_else(|| OptifyError::UnknownFeature {
+                        name: feature_name.clone(),
                     })?
                     .clone()
             }
@@ -125,7 +436,9 @@ impl OptionsProvider {
                                 // Should not happen.
                                 // All canonical feature names are included as keys in the sources map.
                                 // It could happen in the future if we allow aliases to be added directly, but we should try to validate them when the provider is built.
-                                format!("Feature name {canonical_feature_name:?} is not a known feature.")
+                                OptifyError::UnknownFeature {
+                                    name: canonical_feature_name.clone(),
+                                }
                             })?
                             .clone()
                     }
@@ -140,7 +453,9 @@ impl OptionsProvider {
                         // Should not happen.
                         // All canonical feature names are included as keys in the sources map.
                         // It could happen in the future if we allow aliases to be added directly, but we should try to validate them when the provider is built.
-                        format!("Feature name {canonical_feature_name:?} is not a known feature.")
+                        OptifyError::UnknownFeature {
+                            name: canonical_feature_name.clone(),
+                        }
                     })?;
                     merge_json_with_defaults(&mut result, source, &mut frozen_paths);
                 }
@@ -148,13 +463,24 @@ impl OptionsProvider {
                 result
             }
         };
+        if self.needs_merge_directives(overrides) {
+            apply_merge_directives(&mut result);
+        }
 
-        if cache_options.is_some() {
-            let cache_key = feature_names.to_owned();
+        if let Some(cache_options) = cache_options {
+            let cache_key = (
+                feature_names.to_owned(),
+                self.get_preferences_cache_key(preferences, false),
+            );
             self.entire_config_cache
                 .write()
                 .expect("the entire config cache lock should be held")
-                .insert(cache_key, result.clone());
+                .insert(
+                    cache_key,
+                    result.clone(),
+                    || get_serialized_size(&result),
+                    cache_options,
+                );
         }
 
         Ok(result)
@@ -168,10 +494,9 @@ impl OptionsProvider {
         filtered_feature_names: &[String],
         original_feature_names: &[impl AsRef<str>],
         preferences: Option<&GetOptionsPreferences>,
-    ) -> Result<serde_json::Value, String> {
-        let override_for_key = preferences
-            .and_then(|p| p.overrides.as_ref())
-            .and_then(|o| o.get(key));
+    ) -> Result<serde_json::Value, OptifyError> {
+        let overrides = self.get_overrides(preferences);
+        let override_for_key = overrides.as_deref().and_then(|o| o.get(key));
 
         let result: Option<serde_json::Value> =
             match (override_for_key, filtered_feature_names.len()) {
@@ -179,7 +504,9 @@ impl OptionsProvider {
                 (None, 1) => {
                     let feature_name = &filtered_feature_names[0];
                     let source = self.sources.get(feature_name).ok_or_else(|| {
-                        format!("Feature name {feature_name:?} is not a known feature.")
+                        OptifyError::UnknownFeature {
+                            name: feature_name.clone(),
+                        }
                     })?;
                     source.get(key).cloned()
                 }
@@ -190,9 +517,9 @@ impl OptionsProvider {
                     let mut frozen_paths = FrozenPaths::new();
                     for canonical_feature_name in filtered_feature_names.iter().rev() {
                         let source = self.sources.get(canonical_feature_name).ok_or_else(|| {
-                            format!(
-                                "Feature name {canonical_feature_name:?} is not a known feature."
-                            )
+                            OptifyError::UnknownFeature {
+                                name: canonical_feature_name.clone(),
+                            }
                         })?;
 
                         if let Some(source_value) = source.get(key) {
@@ -216,9 +543,9 @@ impl OptionsProvider {
                     let mut frozen_paths = FrozenPaths::new();
                     for canonical_feature_name in filtered_feature_names.iter().rev() {
                         let source = self.sources.get(canonical_feature_name).ok_or_else(|| {
-                            format!(
-                                "Feature name {canonical_feature_name:?} is not a known feature."
-                            )
+                            OptifyError::UnknownFeature {
+                                name: canonical_feature_name.clone(),
+                            }
                         })?;
 
                         if let Some(source_value) = source.get(key) {
@@ -228,80 +555,415 @@ impl OptionsProvider {
                     Some(result)
                 }
             };
+        let result = if self.needs_merge_directives(override_for_key) {
+            result.and_then(|mut value| apply_merge_directives(&mut value).then_some(value))
+        } else {
+            result
+        };
 
-        result.ok_or_else(|| {
-            format!(
-                "Error getting options with features {:?}: configuration property \"{}\" not found",
-                original_feature_names
-                    .iter()
-                    .map(|f| f.as_ref())
-                    .collect::<Vec<&str>>(),
-                key
-            )
+        result.ok_or_else(|| OptifyError::KeyNotFound {
+            key: key.to_owned(),
+            features: original_feature_names
+                .iter()
+                .map(|f| f.as_ref().to_owned())
+                .collect(),
         })
     }
 
-    fn get_entire_config_from_cache(
+    /// Applies the values for `key` from a feature and the features it imports, from the lowest to the highest priority.
+    fn explain_feature(
         &self,
-        feature_names: &[String],
+        key: &str,
+        canonical_feature_name: &str,
+        importers: &mut Vec<String>,
+        builder: &mut ExplanationBuilder,
+    ) -> Result<(), OptifyError> {
+        if let Some(imports) = self.imports.get(canonical_feature_name) {
+            importers.push(canonical_feature_name.to_owned());
+            for import in imports {
+                self.explain_feature(key, import, importers, builder)?;
+            }
+            importers.pop();
+        }
+
+        // Features without imports only have the options from their files.
+        let source = self
+            .sources_without_imports
+            .get(canonical_feature_name)
+            .or_else(|| self.sources.get(canonical_feature_name))
+            .ok_or_else(|| OptifyError::UnknownFeature {
+                name: canonical_feature_name.to_owned(),
+            })?;
+        if let Some(value) = source.get(key) {
+            builder.apply(
+                value,
+                &ValueSource {
+                    environment_variable: None,
+                    feature: Some(canonical_feature_name.to_owned()),
+                    imported_by: importers.clone(),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Gets all options when the feature names have already been filtered.
+    pub(crate) fn get_all_options_for_filtered_features(
+        &self,
+        filtered_feature_names: &[String],
+        cache_options: Option<&CacheOptions>,
         preferences: Option<&GetOptionsPreferences>,
-    ) -> Result<Option<serde_json::Value>, String> {
-        if let Some(preferences) = preferences {
-            if preferences.overrides.is_some() {
-                return Err("Caching when overrides are given is not supported.".to_owned());
+    ) -> Result<serde_json::Value, OptifyError> {
+        let mut value =
+            self.get_entire_config(filtered_feature_names, cache_options, preferences)?;
+        self.process_configurable_values(
+            &mut value,
+            None,
+            filtered_feature_names,
+            preferences,
+            &mut Vec::new(),
+        )?;
+        Ok(value)
+    }
+
+    /// Gets options for a key when the feature names have already been filtered.
+    pub(crate) fn get_options_for_filtered_features(
+        &self,
+        key: &str,
+        filtered_feature_names: &[String],
+        original_feature_names: &[impl AsRef<str>],
+        cache_options: Option<&CacheOptions>,
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<serde_json::Value, OptifyError> {
+        let cache_options = cache_options.filter(|o| {
+            o.scope.includes_options()
+                && (o.are_secrets_cached || !self.may_resolve_secrets(key, preferences))
+        });
+        let cache_key = cache_options.map(|_| {
+            self.get_options_cache_key(key, filtered_feature_names.to_owned(), preferences)
+        });
+        if let Some(cache_key) = &cache_key {
+            if let Some(options) = self
+                .options_cache
+                .read()
+                .expect("the options cache should be readable")
+                .get(cache_key)
+            {
+                return Ok(options);
             }
         }
-        let cache_key = feature_names.to_owned();
-        if let Some(config) = self
-            .entire_config_cache
+
+        let value = if key.starts_with('/') {
+            self.get_options_at_pointer(
+                key,
+                filtered_feature_names,
+                original_feature_names,
+                preferences,
+                &mut Vec::new(),
+            )?
+        } else {
+            let mut value = self.get_options_for_key(
+                key,
+                filtered_feature_names,
+                original_feature_names,
+                preferences,
+            )?;
+            self.process_configurable_values(
+                &mut value,
+                Some((key, "")),
+                filtered_feature_names,
+                preferences,
+                &mut Vec::new(),
+            )?;
+            value
+        };
+
+        if let (Some(cache_options), Some(cache_key)) = (cache_options, cache_key) {
+            self.options_cache
+                .write()
+                .expect("the options cache lock should be held")
+                .insert(
+                    cache_key,
+                    value.clone(),
+                    || get_serialized_size(&value),
+                    cache_options,
+                );
+        }
+        Ok(value)
+    }
+
+    /// Get options at a JSON pointer, such as `/myConfig/myObject/one`, by merging only the values along the pointer.
+    /// `resolving` has the pointers of the references that are being resolved.
+    fn get_options_at_pointer(
+        &self,
+        pointer: &str,
+        filtered_feature_names: &[String],
+        original_feature_names: &[impl AsRef<str>],
+        preferences: Option<&GetOptionsPreferences>,
+        resolving: &mut Vec<String>,
+    ) -> Result<serde_json::Value, OptifyError> {
+        let segments = split_json_pointer(pointer);
+        let key = &segments[0];
+        let pointer_in_key = pointer[1..].find('/').map_or("", |i| &pointer[i + 1..]);
+        let are_configurable_values_enabled = preferences
+            .map(|p| p.are_configurable_values_enabled())
+            .unwrap_or(false);
+
+        // A configurable value must be built from its entire definition,
+        // so merge from the outermost configurable value that contains the pointer.
+        let base_pointer = if are_configurable_values_enabled {
+            self.find_enclosing_configurable_pointer(key, pointer_in_key)
+                .unwrap_or(pointer_in_key)
+        } else {
+            pointer_in_key
+        };
+        let merge_depth = 1 + split_json_pointer(base_pointer).len();
+
+        // Sources are ordered from highest to lowest priority.
+        let mut sources = Vec::with_capacity(filtered_feature_names.len() + 1);
+        let overrides = self.get_overrides(preferences);
+        if let Some(overrides) = overrides.as_deref() {
+            sources.push(overrides);
+        }
+        for canonical_feature_name in filtered_feature_names.iter().rev() {
+            let source = self.sources.get(canonical_feature_name).ok_or_else(|| {
+                OptifyError::UnknownFeature {
+                    name: canonical_feature_name.clone(),
+                }
+            })?;
+            sources.push(source);
+        }
+
+        let mut result = if self.needs_merge_directives(overrides.as_deref()) {
+            // Directives can change how any of the values along the pointer are merged, so merge the entire value for the key.
+            let mut value_for_key: Option<serde_json::Value> = None;
+            let mut frozen_paths = FrozenPaths::new();
+            for source_value in sources.iter().filter_map(|source| source.get(key)) {
+                match &mut value_for_key {
+                    Some(existing) => {
+                        merge_json_with_defaults(existing, source_value, &mut frozen_paths)
+                    }
+                    None => value_for_key = Some(source_value.clone()),
+                }
+            }
+            value_for_key
+                .and_then(|mut value| apply_merge_directives(&mut value).then_some(value))
+                .and_then(|value| get_at_path(&value, &segments[1..merge_depth]).cloned())
+        } else {
+            merge_json_at_path(&sources, &segments[..merge_depth])
+        };
+        if let Some(value) = &mut result {
+            self.process_configurable_values(
+                value,
+                Some((key, base_pointer)),
+                filtered_feature_names,
+                preferences,
+                resolving,
+            )?;
+        }
+        if merge_depth < segments.len() {
+            result =
+                result.and_then(|value| get_at_path(&value, &segments[merge_depth..]).cloned());
+        }
+
+        result.ok_or_else(|| OptifyError::KeyNotFound {
+            key: pointer.to_owned(),
+            features: original_feature_names
+                .iter()
+                .map(|f| f.as_ref().to_owned())
+                .collect(),
+        })
+    }
+
+    /// Finds the pointer, relative to `key`, of the outermost configurable value that contains `pointer`.
+    fn find_enclosing_configurable_pointer<'a>(
+        &'a self,
+        key: &str,
+        pointer: &str,
+    ) -> Option<&'a str> {
+        [
+            &self.keyed_configurable_custom_pointers,
+            &self.keyed_configurable_reference_pointers,
+            &self.keyed_configurable_secret_pointers,
+            &self.keyed_configurable_string_pointers,
+            &self.keyed_configurable_list_pointers,
+        ]
+        .into_iter()
+        .filter_map(|keyed_pointers| keyed_pointers.get(key))
+        .flatten()
+        .filter(|configurable_pointer| {
+            pointer
+                .strip_prefix(configurable_pointer.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+        .min_by_key(|configurable_pointer| configurable_pointer.len())
+        .map(|configurable_pointer| configurable_pointer.as_str())
+    }
+
+    fn get_entire_config_from_cache(
+        &self,
+        feature_names: &[String],
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Option<serde_json::Value> {
+        let cache_key = (
+            feature_names.to_owned(),
+            self.get_preferences_cache_key(preferences, false),
+        );
+        self.entire_config_cache
             .read()
             .expect("the entire config cache should be readable")
             .get(&cache_key)
-        {
-            return Ok(Some(config.clone()));
-        }
+    }
 
-        Ok(None)
+    fn get_options_cache_key(
+        &self,
+        key: &str,
+        filtered_feature_names: Vec<String>,
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> OptionsCacheKey {
+        let are_configurable_values_enabled = preferences
+            .map(|p| p.are_configurable_values_enabled())
+            .unwrap_or(false);
+        (
+            key.to_owned(),
+            filtered_feature_names,
+            are_configurable_values_enabled,
+            self.get_preferences_cache_key(preferences, are_configurable_values_enabled),
+        )
+    }
+
+    /// Gets the parts of the preferences that can change a cached result.
+    /// `are_templates_rendered` is `true` when the result has configurable strings that were built.
+    fn get_preferences_cache_key(
+        &self,
+        preferences: Option<&GetOptionsPreferences>,
+        are_templates_rendered: bool,
+    ) -> PreferencesCacheKey {
+        let constraints = preferences
+            .and_then(|p| p.constraints.as_ref())
+            .filter(|_| are_templates_rendered)
+            .and_then(|constraints| {
+                // Templates in overrides were not parsed when building, so they could read any constraint.
+                if preferences.is_some_and(|p| p.overrides.is_some())
+                    || self.env_overrides.is_some()
+                {
+                    Some(constraints.clone())
+                } else {
+                    self.liquid_templates.constraint_reads().select(constraints)
+                }
+            });
+        PreferencesCacheKey::new(preferences, constraints)
     }
 
     pub fn get_options_from_cache(
         &self,
         key: &str,
         feature_names: &[impl AsRef<str>],
-        _cache_options: Option<&CacheOptions>,
+        cache_options: Option<&CacheOptions>,
         preferences: Option<&GetOptionsPreferences>,
-    ) -> Result<Option<serde_json::Value>, String> {
+    ) -> Result<Option<serde_json::Value>, OptifyError> {
+        if cache_options.is_some_and(|o| !o.scope.includes_options()) {
+            return Ok(None);
+        }
         let filtered_feature_names = self.get_filtered_feature_names(feature_names, preferences)?;
-        let are_configurable_strings_enabled = preferences
-            .map(|p| p.are_configurable_strings_enabled)
-            .unwrap_or(false);
-        let cache_key = (
-            key.to_owned(),
-            filtered_feature_names,
-            are_configurable_strings_enabled,
-        );
-        if let Some(options) = self
+        let cache_key = self.get_options_cache_key(key, filtered_feature_names, preferences);
+        Ok(self
             .options_cache
             .read()
             .expect("the options cache should be readable")
-            .get(&cache_key)
+            .get(&cache_key))
+    }
+
+    pub(crate) fn deserialize_options<T: DeserializeOwned>(
+        &self,
+        key: &str,
+        value: &serde_json::Value,
+        feature_names: &[impl AsRef<str>],
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<T, OptifyError> {
+        serde_path_to_error::deserialize(value).map_err(|e| {
+            let pointer = to_json_pointer(e.path());
+            let feature_file = self.find_feature_file(key, &pointer, feature_names, preferences);
+            OptifyError::OptionsDeserialize {
+                key: key.to_owned(),
+                features: feature_names
+                    .iter()
+                    .map(|f| f.as_ref().to_owned())
+                    .collect(),
+                pointer,
+                feature_file,
+                message: e.into_inner().to_string(),
+            }
+        })
+    }
+
+    /// Finds the path of the highest priority feature file with a value at `pointer` in the options for `key`.
+    /// Returns `None` if the value came from the overrides or could not be found.
+    fn find_feature_file(
+        &self,
+        key: &str,
+        pointer: &str,
+        feature_names: &[impl AsRef<str>],
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Option<String> {
+        let full_pointer = if key.starts_with('/') {
+            format!("{key}{pointer}")
+        } else {
+            escape_json_pointer!(key);
+            format!("/{key}{pointer}")
+        };
+        if self
+            .get_overrides(preferences)
+            .is_some_and(|overrides| overrides.pointer(&full_pointer).is_some())
         {
-            return Ok(Some(options.clone()));
+            return None;
         }
+        let filtered_feature_names = self
+            .get_filtered_feature_names(feature_names, preferences)
+            .ok()?;
+        filtered_feature_names
+            .iter()
+            .rev()
+            .find_map(|name| self.find_feature_with_value(name, &full_pointer))
+            .and_then(|name| self.features.get(name))
+            .and_then(|metadata| metadata.path.clone())
+    }
 
-        Ok(None)
+    /// Finds the feature that supplied the value at `full_pointer` for a feature, following its imports.
+    /// A feature's own options have a higher priority than its imports and later imports have a higher priority than earlier ones.
+    fn find_feature_with_value<'a>(
+        &'a self,
+        canonical_feature_name: &'a str,
+        full_pointer: &str,
+    ) -> Option<&'a str> {
+        // Features without imports only have the options from their files.
+        let source = self
+            .sources_without_imports
+            .get(canonical_feature_name)
+            .or_else(|| self.sources.get(canonical_feature_name))?;
+        if source.pointer(full_pointer).is_some() {
+            return Some(canonical_feature_name);
+        }
+        self.imports
+            .get(canonical_feature_name)?
+            .iter()
+            .rev()
+            .find_map(|import| self.find_feature_with_value(import, full_pointer))
     }
 
+    /// `key` is the key and the pointer within the key where `value` is located.
     fn process_configurable_lists(
         &self,
         value: &mut serde_json::Value,
-        key: Option<&str>,
-    ) -> Result<(), String> {
+        key: Option<(&str, &str)>,
+    ) -> Result<(), OptifyError> {
         match key {
-            Some(key) => match self.keyed_configurable_list_pointers.get(key) {
+            Some((key, base_pointer)) => match self.keyed_configurable_list_pointers.get(key) {
                 Some(pointers) => {
                     for pointer in pointers {
-                        self.handle_configurable_list_pointer(value, pointer)?;
+                        if let Some(pointer) = get_relative_pointer(pointer, base_pointer) {
+                            self.handle_configurable_list_pointer(value, pointer)?;
+                        }
                     }
                 }
                 _ => {
@@ -322,8 +984,8 @@ impl OptionsProvider {
     fn handle_configurable_list_pointer(
         &self,
         value: &mut serde_json::Value,
-        pointer: &String,
-    ) -> Result<(), String> {
+        pointer: &str,
+    ) -> Result<(), OptifyError> {
         if let Some(configurable_value) = value.pointer_mut(pointer) {
             // Only continue if it has the right indicator property because it may have been overridden.
             if let Some(type_value) =
@@ -344,32 +1006,340 @@ impl OptionsProvider {
                 match serde_json::from_value(configurable_value.clone()) {
                     Ok(cl) => cl,
                     Err(e) => {
-                        return Err(format!(
-                            "Failed to deserialize ConfigurableList at {}: {}",
-                            pointer, e
-                        ));
+                        return Err(OptifyError::ConfigurableValue {
+                            pointer: pointer.to_owned(),
+                            message: format!("Failed to deserialize ConfigurableList: {e}"),
+                        });
                     }
                 };
 
             // Replace the value at the pointer location with the built list.
-            let built_list = configurable_list.build()?;
+            let built_list =
+                configurable_list
+                    .build()
+                    .map_err(|message| OptifyError::ConfigurableValue {
+                        pointer: pointer.to_owned(),
+                        message,
+                    })?;
             *configurable_value = serde_json::Value::Array(built_list);
         }
 
         Ok(())
     }
 
+    /// Builds the configurable values in the JSON value when configurable values are enabled in the preferences.
+    /// `key` is the key and the pointer within the key where `value` is located.
+    /// `resolving` has the pointers of the references that are being resolved.
+    fn process_configurable_values(
+        &self,
+        value: &mut serde_json::Value,
+        key: Option<(&str, &str)>,
+        filtered_feature_names: &[String],
+        preferences: Option<&GetOptionsPreferences>,
+        resolving: &mut Vec<String>,
+    ) -> Result<(), OptifyError> {
+        // Configurable values are disabled by default.
+        let Some(preferences) = preferences.filter(|p| p.are_configurable_values_enabled()) else {
+            return Ok(());
+        };
+        // References are resolved first because the values that they point to are already built.
+        self.process_configurable_references(
+            value,
+            key,
+            filtered_feature_names,
+            preferences,
+            resolving,
+        )?;
+        self.process_configurable_secrets(value, key, preferences.redact_secrets)?;
+        self.process_configurable_custom_values(value, key, filtered_feature_names)?;
+        // Templates in configurable strings can read the constraints and the merged options.
+        let get_options = || {
+            self.get_entire_config(filtered_feature_names, None, Some(preferences))
+                .map_err(|e| e.to_string())
+        };
+        let scopes = TemplateScopes::new(
+            preferences.constraints.as_ref().map(|c| &c.constraints),
+            &get_options,
+        );
+        // Strings need to be processed before lists because lists may contain strings.
+        self.process_configurable_strings(value, key, &scopes)?;
+        self.process_configurable_lists(value, key)?;
+        Ok(())
+    }
+
+    /// Replaces references in the JSON value with the values that they point to in the options for the features.
+    /// `key` is the key and the pointer within the key where `value` is located.
+    fn process_configurable_references(
+        &self,
+        value: &mut serde_json::Value,
+        key: Option<(&str, &str)>,
+        filtered_feature_names: &[String],
+        preferences: &GetOptionsPreferences,
+        resolving: &mut Vec<String>,
+    ) -> Result<(), OptifyError> {
+        match key {
+            Some((key, base_pointer)) => {
+                if let Some(pointers) = self.keyed_configurable_reference_pointers.get(key) {
+                    escape_json_pointer!(key);
+                    for pointer in pointers {
+                        if let Some(relative_pointer) = get_relative_pointer(pointer, base_pointer)
+                        {
+                            self.handle_configurable_reference_pointer(
+                                value,
+                                relative_pointer,
+                                &format!("/{key}{pointer}"),
+                                filtered_feature_names,
+                                preferences,
+                                resolving,
+                            )?;
+                        }
+                    }
+                }
+            }
+            None => {
+                // There is no key prefix when the entire configuration is requested.
+                for pointer in &self.all_configurable_reference_pointers {
+                    self.handle_configurable_reference_pointer(
+                        value,
+                        pointer,
+                        pointer,
+                        filtered_feature_names,
+                        preferences,
+                        resolving,
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `location` is the pointer to the reference from the top of the options, which is used to find cycles and in errors.
+    fn handle_configurable_reference_pointer(
+        &self,
+        value: &mut serde_json::Value,
+        pointer: &str,
+        location: &str,
+        filtered_feature_names: &[String],
+        preferences: &GetOptionsPreferences,
+        resolving: &mut Vec<String>,
+    ) -> Result<(), OptifyError> {
+        let Some(configurable_value) = value.pointer_mut(pointer) else {
+            return Ok(());
+        };
+        // Only continue if it has the right indicator property because it may have been overridden.
+        let reference = match Reference::parse(configurable_value, location) {
+            Some(reference) => reference?,
+            None => return Ok(()),
+        };
+        // Cycles are rejected when building, but overrides can still create them.
+        if resolving.iter().any(|other| other == location) {
+            let mut pointers = resolving.clone();
+            pointers.push(location.to_owned());
+            return Err(OptifyError::ReferenceCycle { pointers });
+        }
+
+        resolving.push(location.to_owned());
+        let result = self.get_options_at_pointer(
+            &reference.pointer,
+            filtered_feature_names,
+            filtered_feature_names,
+            Some(preferences),
+            resolving,
+        );
+        resolving.pop();
+        *configurable_value = result.map_err(|error| match error {
+            OptifyError::KeyNotFound { .. } => OptifyError::ConfigurableValue {
+                pointer: location.to_owned(),
+                message: format!(
+                    "The reference points to {:?}, which is not in the options for the features.",
+                    reference.pointer
+                ),
+            },
+            _ => error,
+        })?;
+        Ok(())
+    }
+
+    /// Builds the values of types registered with `with_configurable_value_type` in the JSON value.
+    /// `key` is the key and the pointer within the key where `value` is located.
+    fn process_configurable_custom_values(
+        &self,
+        value: &mut serde_json::Value,
+        key: Option<(&str, &str)>,
+        filtered_feature_names: &[String],
+    ) -> Result<(), OptifyError> {
+        match key {
+            Some((key, base_pointer)) => {
+                if let Some(pointers) = self.keyed_configurable_custom_pointers.get(key) {
+                    escape_json_pointer!(key);
+                    for pointer in pointers {
+                        if let Some(relative_pointer) = get_relative_pointer(pointer, base_pointer)
+                        {
+                            self.handle_configurable_custom_pointer(
+                                value,
+                                relative_pointer,
+                                &format!("/{key}{pointer}"),
+                                filtered_feature_names,
+                            )?;
+                        }
+                    }
+                }
+            }
+            None => {
+                // There is no key prefix when the entire configuration is requested.
+                for pointer in &self.all_configurable_custom_pointers {
+                    self.handle_configurable_custom_pointer(
+                        value,
+                        pointer,
+                        pointer,
+                        filtered_feature_names,
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `location` is the pointer to the value from the top of the options, which is given to the type and used in errors.
+    fn handle_configurable_custom_pointer(
+        &self,
+        value: &mut serde_json::Value,
+        pointer: &str,
+        location: &str,
+        filtered_feature_names: &[String],
+    ) -> Result<(), OptifyError> {
+        let Some(configurable_value) = value.pointer_mut(pointer) else {
+            return Ok(());
+        };
+        // Only continue if it has the type of a registered type because it may have been overridden.
+        let Some(value_type) =
+            get_configurable_value_type(configurable_value, &self.configurable_value_types)
+        else {
+            return Ok(());
+        };
+        let context = ConfigurableValueContext::new(location, filtered_feature_names);
+        *configurable_value =
+            value_type
+                .build(configurable_value, &context)
+                .map_err(|message| OptifyError::ConfigurableValue {
+                    pointer: location.to_owned(),
+                    message,
+                })?;
+        Ok(())
+    }
+
+    /// Replaces secrets in the JSON value with their resolved values, or with `REDACTED_SECRET` when `redact_secrets` is set.
+    /// `key` is the key and the pointer within the key where `value` is located.
+    fn process_configurable_secrets(
+        &self,
+        value: &mut serde_json::Value,
+        key: Option<(&str, &str)>,
+        redact_secrets: bool,
+    ) -> Result<(), OptifyError> {
+        match key {
+            Some((key, base_pointer)) => {
+                if let Some(pointers) = self.keyed_configurable_secret_pointers.get(key) {
+                    escape_json_pointer!(key);
+                    for pointer in pointers {
+                        if let Some(relative_pointer) = get_relative_pointer(pointer, base_pointer)
+                        {
+                            self.handle_configurable_secret_pointer(
+                                value,
+                                relative_pointer,
+                                &format!("/{key}{pointer}"),
+                                redact_secrets,
+                            )?;
+                        }
+                    }
+                }
+            }
+            None => {
+                // There is no key prefix when the entire configuration is requested.
+                for pointer in &self.all_configurable_secret_pointers {
+                    self.handle_configurable_secret_pointer(
+                        value,
+                        pointer,
+                        pointer,
+                        redact_secrets,
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `location` is the pointer to the secret from the top of the options, which is used in errors.
+    fn handle_configurable_secret_pointer(
+        &self,
+        value: &mut serde_json::Value,
+        pointer: &str,
+        location: &str,
+        redact_secrets: bool,
+    ) -> Result<(), OptifyError> {
+        if let Some(configurable_value) = value.pointer_mut(pointer) {
+            // Only continue if it has the right indicator property because it may have been overridden.
+            if configurable_value
+                .get(crate::configurable_values::locator::TYPE_KEY)
+                .and_then(|type_value| type_value.as_str())
+                != Some(crate::configurable_values::locator::SECRET_TYPE)
+            {
+                return Ok(());
+            }
+
+            if redact_secrets {
+                *configurable_value = serde_json::Value::String(REDACTED_SECRET.to_owned());
+                return Ok(());
+            }
+
+            let secret: Secret =
+                serde_json::from_value(configurable_value.clone()).map_err(|e| {
+                    OptifyError::ConfigurableValue {
+                        pointer: location.to_owned(),
+                        message: format!("Failed to deserialize Secret: {e}"),
+                    }
+                })?;
+            let resolver = self.secret_resolvers.get(&secret.resolver).ok_or_else(|| {
+                OptifyError::ConfigurableValue {
+                    pointer: location.to_owned(),
+                    message: format!(
+                        "No secret resolver is registered with the name {:?}.",
+                        secret.resolver
+                    ),
+                }
+            })?;
+            let resolved =
+                resolver
+                    .resolve(&secret)
+                    .map_err(|message| OptifyError::ConfigurableValue {
+                        pointer: location.to_owned(),
+                        message: format!(
+                            "Failed to resolve the secret {:?}: {message}",
+                            secret.name
+                        ),
+                    })?;
+            *configurable_value = serde_json::Value::String(resolved);
+        }
+        Ok(())
+    }
+
     /// Process configurable strings in the JSON value based on the pointers.
+    /// `key` is the key and the pointer within the key where `value` is located.
     fn process_configurable_strings(
         &self,
         value: &mut serde_json::Value,
-        key: Option<&str>,
-    ) -> Result<(), String> {
+        key: Option<(&str, &str)>,
+        scopes: &TemplateScopes,
+    ) -> Result<(), OptifyError> {
         match key {
-            Some(key) => match self.keyed_configurable_string_pointers.get(key) {
+            Some((key, base_pointer)) => match self.keyed_configurable_string_pointers.get(key) {
                 Some(pointers) => {
                     for pointer in pointers {
-                        self.handle_configurable_string_pointer(value, pointer)?;
+                        if let Some(pointer) = get_relative_pointer(pointer, base_pointer) {
+                            self.handle_configurable_string_pointer(value, pointer, scopes)?;
+                        }
                     }
                 }
                 _ => {
@@ -379,7 +1349,7 @@ impl OptionsProvider {
             None => {
                 // There is no key prefix when the entire configuration is requested.
                 for pointer in &self.all_configurable_string_pointers {
-                    self.handle_configurable_string_pointer(value, pointer)?;
+                    self.handle_configurable_string_pointer(value, pointer, scopes)?;
                 }
             }
         }
@@ -390,8 +1360,9 @@ impl OptionsProvider {
     fn handle_configurable_string_pointer(
         &self,
         value: &mut serde_json::Value,
-        pointer: &String,
-    ) -> Result<(), String> {
+        pointer: &str,
+        scopes: &TemplateScopes,
+    ) -> Result<(), OptifyError> {
         if let Some(configurable_value) = value.pointer_mut(pointer) {
             // Only continue if it has the right indicator property because it may have been overridden.
             if let Some(type_value) =
@@ -412,15 +1383,20 @@ impl OptionsProvider {
                 match serde_json::from_value(configurable_value.clone()) {
                     Ok(cs) => cs,
                     Err(e) => {
-                        return Err(format!(
-                            "Failed to deserialize ConfigurableString at {}: {}",
-                            pointer, e
-                        ));
+                        return Err(OptifyError::ConfigurableValue {
+                            pointer: pointer.to_owned(),
+                            message: format!("Failed to deserialize ConfigurableString: {e}"),
+                        });
                     }
                 };
 
             // Replace the value at the pointer location with the built string.
-            let built_string = configurable_string.build(&self.loaded_files)?;
+            let built_string = configurable_string
+                .build_with_templates(&self.loaded_files, &self.liquid_templates, scopes)
+                .map_err(|message| OptifyError::ConfigurableValue {
+                    pointer: pointer.to_owned(),
+                    message,
+                })?;
             *configurable_value = serde_json::Value::String(built_string);
         }
         Ok(())
@@ -428,13 +1404,15 @@ impl OptionsProvider {
 }
 
 impl OptionsRegistry for OptionsProvider {
-    fn build(directory: impl AsRef<Path>) -> Result<OptionsProvider, String> {
+    fn build(directory: impl AsRef<Path>) -> Result<OptionsProvider, OptifyError> {
         let mut builder = OptionsProviderBuilder::new();
         builder.add_directory(directory.as_ref())?;
         builder.build_and_clear()
     }
 
-    fn build_from_directories(directories: &[impl AsRef<Path>]) -> Result<OptionsProvider, String> {
+    fn build_from_directories(
+        directories: &[impl AsRef<Path>],
+    ) -> Result<OptionsProvider, OptifyError> {
         let mut builder = OptionsProviderBuilder::new();
         builder.add_directories(directories)?;
         builder.build_and_clear()
@@ -443,7 +1421,7 @@ impl OptionsRegistry for OptionsProvider {
     fn build_from_directories_with_options(
         directories: &[impl AsRef<Path>],
         options: BuilderOptions,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, OptifyError> {
         let mut builder = OptionsProviderBuilder::new();
         builder.with_options(options)?;
         builder.add_directories(directories)?;
@@ -453,13 +1431,108 @@ impl OptionsRegistry for OptionsProvider {
     fn build_with_options(
         directory: impl AsRef<Path>,
         options: BuilderOptions,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, OptifyError> {
         let mut builder = OptionsProviderBuilder::new();
         builder.with_options(options)?;
         builder.add_directory(directory.as_ref())?;
         builder.build_and_clear()
     }
 
+    fn clear_cache(&self) {
+        self.entire_config_cache
+            .write()
+            .expect("the entire config cache lock should be held")
+            .clear();
+        self.options_cache
+            .write()
+            .expect("the options cache lock should be held")
+            .clear();
+        self.typed_options_cache
+            .write()
+            .expect("the typed options cache lock should be held")
+            .clear();
+    }
+
+    fn explain_options(
+        &self,
+        key: &str,
+        feature_names: &[impl AsRef<str>],
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<Vec<ExplainedValue>, OptifyError> {
+        let filtered_feature_names = self.get_filtered_feature_names(feature_names, preferences)?;
+        // Values are merged for the top-level key and then narrowed to the rest of the pointer.
+        let (top_level_key, pointer) = match key.strip_prefix('/') {
+            Some(rest) => {
+                let end = rest.find('/').map_or(key.len(), |i| i + 1);
+                (split_json_pointer(&key[..end]).remove(0), &key[end..])
+            }
+            None => (key.to_owned(), ""),
+        };
+
+        let mut builder = ExplanationBuilder::default();
+        let mut importers = Vec::new();
+        for canonical_feature_name in &filtered_feature_names {
+            self.explain_feature(
+                &top_level_key,
+                canonical_feature_name,
+                &mut importers,
+                &mut builder,
+            )?;
+        }
+        if let Some(override_value) = preferences
+            .and_then(|p| p.overrides.as_ref())
+            .and_then(|o| o.get(&top_level_key))
+        {
+            builder.apply(
+                override_value,
+                &ValueSource {
+                    environment_variable: None,
+                    feature: None,
+                    imported_by: Vec::new(),
+                },
+            );
+        }
+        if let Some(env_overrides) = &self.env_overrides {
+            for env_override in &env_overrides.overrides {
+                let segments = split_json_pointer(&env_override.pointer);
+                if segments[0] != top_level_key {
+                    continue;
+                }
+                let value = segments[1..].iter().rev().fold(
+                    env_override.value.clone(),
+                    |value, segment| serde_json::json!({ segment: value }),
+                );
+                builder.apply(
+                    &value,
+                    &ValueSource {
+                        environment_variable: Some(env_override.variable.clone()),
+                        feature: None,
+                        imported_by: Vec::new(),
+                    },
+                );
+            }
+        }
+
+        let explanation = builder.build_at(pointer);
+        if explanation.is_empty() {
+            return Err(OptifyError::KeyNotFound {
+                key: key.to_owned(),
+                features: feature_names
+                    .iter()
+                    .map(|f| f.as_ref().to_owned())
+                    .collect(),
+            });
+        }
+        Ok(explanation)
+    }
+
+    fn get_env_overrides(&self) -> Vec<EnvOverride> {
+        self.env_overrides
+            .as_ref()
+            .map(|env_overrides| env_overrides.overrides.clone())
+            .unwrap_or_default()
+    }
+
     fn get_aliases(&self) -> Vec<String> {
         self.features
             .values()
@@ -478,36 +1551,26 @@ impl OptionsRegistry for OptionsProvider {
         feature_names: &[impl AsRef<str>],
         cache_options: Option<&CacheOptions>,
         preferences: Option<&GetOptionsPreferences>,
-    ) -> Result<serde_json::Value, String> {
+    ) -> Result<serde_json::Value, OptifyError> {
         let feature_names = self.get_filtered_feature_names(feature_names, preferences)?;
-        let mut value = self.get_entire_config(&feature_names, cache_options, preferences)?;
-        if preferences
-            .map(|p| p.are_configurable_values_enabled())
-            // Configurable strings are disabled by default.
-            .unwrap_or(false)
-        {
-            // Strings need to be processed before lists because lists may contain strings.
-            self.process_configurable_strings(&mut value, None)?;
-            self.process_configurable_lists(&mut value, None)?;
-        }
-        Ok(value)
+        self.get_all_options_for_filtered_features(&feature_names, cache_options, preferences)
     }
 
-    fn get_canonical_feature_name(&self, feature_name: &str) -> Result<String, String> {
+    fn get_canonical_feature_name(&self, feature_name: &str) -> Result<String, OptifyError> {
         // Canonical feature names are also included as keys in the aliases map.
         let feature_name = unicase::UniCase::new(feature_name.to_owned());
         match self.aliases.get(&feature_name) {
             Some(canonical_name) => Ok(canonical_name.to_owned()),
-            None => Err(format!(
-                "Feature name {feature_name:?} is not a known feature."
-            )),
+            None => Err(OptifyError::UnknownFeature {
+                name: feature_name.into_inner(),
+            }),
         }
     }
 
     fn get_canonical_feature_names(
         &self,
         feature_names: &[impl AsRef<str>],
-    ) -> Result<Vec<String>, String> {
+    ) -> Result<Vec<String>, OptifyError> {
         feature_names
             .iter()
             .map(|name| self.get_canonical_feature_name(name.as_ref()))
@@ -536,7 +1599,7 @@ impl OptionsRegistry for OptionsProvider {
         &self,
         feature_names: &[impl AsRef<str>],
         preferences: Option<&GetOptionsPreferences>,
-    ) -> Result<Vec<String>, String> {
+    ) -> Result<Vec<String>, OptifyError> {
         let mut skip_feature_name_conversion = false;
         let mut constraints = None;
         if let Some(preferences) = preferences {
@@ -572,54 +1635,110 @@ impl OptionsRegistry for OptionsProvider {
         &self,
         key: &str,
         feature_names: &[impl AsRef<str>],
-    ) -> Result<serde_json::Value, String> {
+    ) -> Result<serde_json::Value, OptifyError> {
         self.get_options_with_preferences(key, feature_names, None, None)
     }
 
-    fn get_options_with_preferences(
+    fn get_options_as<T: DeserializeOwned>(
         &self,
         key: &str,
         feature_names: &[impl AsRef<str>],
         cache_options: Option<&CacheOptions>,
         preferences: Option<&GetOptionsPreferences>,
-    ) -> Result<serde_json::Value, String> {
-        if cache_options.is_some() {
-            match self.get_options_from_cache(key, feature_names, cache_options, preferences) {
-                Ok(Some(options)) => return Ok(options),
-                Ok(None) => (),
-                Err(e) => return Err(e),
-            }
-        }
+    ) -> Result<T, OptifyError> {
+        let value =
+            self.get_options_with_preferences(key, feature_names, cache_options, preferences)?;
+        self.deserialize_options(key, &value, feature_names, preferences)
+    }
 
-        let filtered_feature_names = self.get_filtered_feature_names(feature_names, preferences)?;
-        let mut value =
-            self.get_options_for_key(key, &filtered_feature_names, feature_names, preferences)?;
+    fn get_options_as_arc<T: DeserializeOwned + Send + Sync + 'static>(
+        &self,
+        key: &str,
+        feature_names: &[impl AsRef<str>],
+        cache_options: Option<&CacheOptions>,
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<Arc<T>, OptifyError> {
+        let Some(cache_options) = cache_options.filter(|o| {
+            o.scope.includes_options()
+                && (o.are_secrets_cached || !self.may_resolve_secrets(key, preferences))
+        }) else {
+            return self
+                .get_options_as(key, feature_names, None, preferences)
+                .map(Arc::new);
+        };
 
-        if preferences
-            .map(|p| p.are_configurable_values_enabled())
-            // Configurable strings are disabled by default.
-            .unwrap_or(false)
-        {
-            // Strings need to be processed before lists because lists may contain strings.
-            self.process_configurable_strings(&mut value, Some(key))?;
-            self.process_configurable_lists(&mut value, Some(key))?;
+        let filtered_feature_names = self.get_filtered_feature_names(feature_names, preferences)?;
+        let cache_key = (
+            TypeId::of::<T>(),
+            self.get_options_cache_key(key, filtered_feature_names, preferences),
+        );
+        let cached = self
+            .typed_options_cache
+            .read()
+            .expect("the typed options cache should be readable")
+            .get(&cache_key);
+        if let Some(Ok(options)) = cached.map(|options| options.downcast::<T>()) {
+            return Ok(options);
         }
 
-        if cache_options.is_some() {
-            let are_configurable_strings_enabled = preferences
-                .map(|p| p.are_configurable_strings_enabled)
-                .unwrap_or(false);
-            let cache_key = (
-                key.to_owned(),
-                filtered_feature_names.clone(),
-                are_configurable_strings_enabled,
+        let value = self.get_options_with_preferences(
+            key,
+            feature_names,
+            Some(cache_options),
+            preferences,
+        )?;
+        let options: Arc<T> =
+            Arc::new(self.deserialize_options(key, &value, feature_names, preferences)?);
+        self.typed_options_cache
+            .write()
+            .expect("the typed options cache lock should be held")
+            .insert(
+                cache_key,
+                options.clone(),
+                || get_serialized_size(&value),
+                cache_options,
             );
-            self.options_cache
-                .write()
-                .expect("the options cache lock should be held")
-                .insert(cache_key, value.clone());
-        }
-        Ok(value)
+        Ok(options)
+    }
+
+    fn get_options_batch(
+        &self,
+        keys: &[impl AsRef<str>],
+        feature_names: &[impl AsRef<str>],
+        cache_options: Option<&CacheOptions>,
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<HashMap<String, serde_json::Value>, OptifyError> {
+        let filtered_feature_names = self.get_filtered_feature_names(feature_names, preferences)?;
+        keys.iter()
+            .map(|key| {
+                let key = key.as_ref();
+                let options = self.get_options_for_filtered_features(
+                    key,
+                    &filtered_feature_names,
+                    feature_names,
+                    cache_options,
+                    preferences,
+                )?;
+                Ok((key.to_owned(), options))
+            })
+            .collect()
+    }
+
+    fn get_options_with_preferences(
+        &self,
+        key: &str,
+        feature_names: &[impl AsRef<str>],
+        cache_options: Option<&CacheOptions>,
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<serde_json::Value, OptifyError> {
+        let filtered_feature_names = self.get_filtered_feature_names(feature_names, preferences)?;
+        self.get_options_for_filtered_features(
+            key,
+            &filtered_feature_names,
+            feature_names,
+            cache_options,
+            preferences,
+        )
     }
 
     fn has_conditions(&self, canonical_feature_name: &str) -> bool {
@@ -630,7 +1749,7 @@ impl OptionsRegistry for OptionsProvider {
         &self,
         feature_names: &[impl AsRef<str>],
         preferences: Option<&GetOptionsPreferences>,
-    ) -> Result<Vec<Option<String>>, String> {
+    ) -> Result<Vec<Option<String>>, OptifyError> {
         let mut skip_feature_name_conversion = false;
         let mut constraints = None;
         if let Some(preferences) = preferences {
@@ -663,3 +1782,35 @@ impl OptionsRegistry for OptionsProvider {
         Ok(result)
     }
 }
+
+/// Gets `pointer` relative to `base_pointer` if `pointer` is at or below `base_pointer`.
+fn get_relative_pointer<'a>(pointer: &'a str, base_pointer: &str) -> Option<&'a str> {
+    pointer
+        .strip_prefix(base_pointer)
+        .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Converts a path from deserialization to a JSON pointer.
+fn to_json_pointer(path: &serde_path_to_error::Path) -> String {
+    let mut pointer = String::new();
+    for segment in path.iter() {
+        match segment {
+            serde_path_to_error::Segment::Seq { index } => {
+                pointer.push('/');
+                pointer.push_str(&index.to_string());
+            }
+            serde_path_to_error::Segment::Map { key } => {
+                escape_json_pointer!(key);
+                pointer.push('/');
+                pointer.push_str(&key);
+            }
+            serde_path_to_error::Segment::Enum { variant } => {
+                escape_json_pointer!(variant);
+                pointer.push('/');
+                pointer.push_str(&variant);
+            }
+            serde_path_to_error::Segment::Unknown => {}
+        }
+    }
+    pointer
+}
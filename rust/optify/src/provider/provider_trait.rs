@@ -1,35 +1,37 @@
-use std::path::Path;
+use std::{collections::HashMap, path::Path, sync::Arc};
 
+use serde::de::DeserializeOwned;
 use serde_json::Value;
 
 use crate::builder::builder_options::BuilderOptions;
+use crate::error::OptifyError;
 
 use crate::{
-    provider::{CacheOptions, Features, GetOptionsPreferences},
+    provider::{CacheOptions, EnvOverride, ExplainedValue, Features, GetOptionsPreferences},
     schema::metadata::OptionsMetadata,
 };
 
 /// Trait defining the core functionality for an options provider
 pub trait OptionsRegistry {
-    fn build(directory: impl AsRef<Path>) -> Result<Self, String>
+    fn build(directory: impl AsRef<Path>) -> Result<Self, OptifyError>
     where
         Self: Sized;
 
-    fn build_from_directories(directories: &[impl AsRef<Path>]) -> Result<Self, String>
+    fn build_from_directories(directories: &[impl AsRef<Path>]) -> Result<Self, OptifyError>
     where
         Self: Sized;
 
     fn build_from_directories_with_options(
         directories: &[impl AsRef<Path>],
         options: BuilderOptions,
-    ) -> Result<Self, String>
+    ) -> Result<Self, OptifyError>
     where
         Self: Sized;
 
     fn build_from_directories_with_schema(
         directories: &[impl AsRef<Path>],
         schema_path: impl AsRef<Path>,
-    ) -> Result<Self, String>
+    ) -> Result<Self, OptifyError>
     where
         Self: Sized,
     {
@@ -43,14 +45,14 @@ pub trait OptionsRegistry {
     fn build_with_options(
         directory: impl AsRef<Path>,
         options: BuilderOptions,
-    ) -> Result<Self, String>
+    ) -> Result<Self, OptifyError>
     where
         Self: Sized;
 
     fn build_with_schema(
         directory: impl AsRef<Path>,
         schema_path: impl AsRef<Path>,
-    ) -> Result<Self, String>
+    ) -> Result<Self, OptifyError>
     where
         Self: Sized,
     {
@@ -61,9 +63,30 @@ pub trait OptionsRegistry {
         Self::build_with_options(directory, options)
     }
 
+    /// Removes all cached results to reclaim memory.
+    fn clear_cache(&self);
+
+    /// Explains where each leaf in the options for a key came from.
+    /// For each leaf, the result has the feature that supplied the value, including features that were imported,
+    /// and the values from lower-priority features that were replaced.
+    /// The key can also be a JSON pointer, such as `/myConfig/myObject`.
+    /// Values are explained as they are in the files, before configurable values are processed.
+    /// Merge directives, such as `{"$append": [...]}`, are applied, so the leaves match the merged options.
+    fn explain_options(
+        &self,
+        key: &str,
+        feature_names: &[impl AsRef<str>],
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<Vec<ExplainedValue>, OptifyError>;
+
     /// Gets all alias names.
     fn get_aliases(&self) -> Vec<String>;
 
+    /// Gets the overrides from environment variables that were applied when the options were built,
+    /// sorted by the names of the variables.
+    /// Empty unless `BuilderOptions::env_overrides_prefix` was set.
+    fn get_env_overrides(&self) -> Vec<EnvOverride>;
+
     /// Gets all feature names and alias names.
     fn get_features_and_aliases(&self) -> Vec<String>;
 
@@ -73,13 +96,13 @@ pub trait OptionsRegistry {
         feature_names: &[impl AsRef<str>],
         cache_options: Option<&CacheOptions>,
         preferences: Option<&GetOptionsPreferences>,
-    ) -> Result<Value, String>;
+    ) -> Result<Value, OptifyError>;
 
     /// Map an alias or canonical feature name (perhaps derived from a file name) to a canonical feature name.
     /// Canonical feature names map to themselves.
     ///
     /// Returns the canonical feature name.
-    fn get_canonical_feature_name(&self, feature_name: &str) -> Result<String, String>;
+    fn get_canonical_feature_name(&self, feature_name: &str) -> Result<String, OptifyError>;
 
     /// Map aliases or canonical feature names (perhaps derived from a file names) to the canonical feature names.
     /// Canonical feature names map to themselves.
@@ -88,7 +111,7 @@ pub trait OptionsRegistry {
     fn get_canonical_feature_names(
         &self,
         feature_names: &[impl AsRef<str>],
-    ) -> Result<Vec<String>, String>;
+    ) -> Result<Vec<String>, OptifyError>;
 
     fn get_feature_metadata(&self, canonical_feature_name: &str) -> Option<OptionsMetadata>;
 
@@ -108,19 +131,58 @@ pub trait OptionsRegistry {
         &self,
         feature_names: &[impl AsRef<str>],
         preferences: Option<&GetOptionsPreferences>,
-    ) -> Result<Vec<String>, String>;
+    ) -> Result<Vec<String>, OptifyError>;
 
-    /// Gets options for a specific key and feature names
-    fn get_options(&self, key: &str, feature_names: &[impl AsRef<str>]) -> Result<Value, String>;
+    /// Gets options for a specific key and feature names.
+    /// The key can also be a JSON pointer, such as `/myConfig/myObject/one`, to get a nested value.
+    fn get_options(
+        &self,
+        key: &str,
+        feature_names: &[impl AsRef<str>],
+    ) -> Result<Value, OptifyError>;
+
+    /// Gets options for a specific key and feature names and deserializes them into `T`.
+    /// If deserialization fails, the error includes the JSON pointer of the failing value
+    /// and the feature file that provided it.
+    fn get_options_as<T: DeserializeOwned>(
+        &self,
+        key: &str,
+        feature_names: &[impl AsRef<str>],
+        cache_options: Option<&CacheOptions>,
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<T, OptifyError>;
+
+    /// Like `get_options_as`, but when `cache_options` are given,
+    /// the deserialized value is cached for the type `T` and shared by later calls with the same inputs.
+    fn get_options_as_arc<T: DeserializeOwned + Send + Sync + 'static>(
+        &self,
+        key: &str,
+        feature_names: &[impl AsRef<str>],
+        cache_options: Option<&CacheOptions>,
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<Arc<T>, OptifyError>;
+
+    /// Gets options for many keys with the same feature names and preferences.
+    /// The feature names are only resolved and filtered once.
+    /// Returns a map of each key to its options.
+    fn get_options_batch(
+        &self,
+        keys: &[impl AsRef<str>],
+        feature_names: &[impl AsRef<str>],
+        cache_options: Option<&CacheOptions>,
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<HashMap<String, Value>, OptifyError>;
 
-    /// Gets options with preferences for a specific key and feature names
+    /// Gets options with preferences for a specific key and feature names.
+    /// The key can also be a JSON pointer, such as `/myConfig/myObject/one`, to get a nested value.
+    /// Only the values along the pointer are merged.
     fn get_options_with_preferences(
         &self,
         key: &str,
         feature_names: &[impl AsRef<str>],
         cache_options: Option<&CacheOptions>,
         preferences: Option<&GetOptionsPreferences>,
-    ) -> Result<Value, String>;
+    ) -> Result<Value, OptifyError>;
 
     /// Indicates if the feature has conditions.
     fn has_conditions(&self, canonical_feature_name: &str) -> bool;
@@ -135,5 +197,5 @@ pub trait OptionsRegistry {
         &self,
         feature_names: &[impl AsRef<str>],
         preferences: Option<&GetOptionsPreferences>,
-    ) -> Result<Vec<Option<String>>, String>;
+    ) -> Result<Vec<Option<String>>, OptifyError>;
 }
@@ -0,0 +1,339 @@
+use std::{borrow::Cow, collections::HashMap, fs::File, path::Path};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::builder::BuilderOptions;
+use crate::configurable_string::LoadedFiles;
+use crate::configurable_values::locator::TYPE_KEY;
+use crate::error::OptifyError;
+use crate::provider::{Imports, OptionsProvider, ReferencedFileToFeatureNames, Sources};
+
+/// The bytes at the start of every snapshot.
+const SNAPSHOT_MAGIC: &[u8; 8] = b"OPTIFYSS";
+
+/// The version of the format of snapshots.
+/// Increment it when the contents of snapshots change so that old snapshots are rejected.
+const SNAPSHOT_FORMAT_VERSION: u32 = 5;
+
+/// The magic bytes, the format version, the checksum of the contents, and the length of the contents.
+const SNAPSHOT_HEADER_LEN: usize = SNAPSHOT_MAGIC.len() + 4 + 8 + 8;
+
+/// A JSON value in a snapshot.
+/// `serde_json::Value` can only be read from formats that describe their own types,
+/// so values are stored with their type in the binary encoding.
+/// Strings are borrowed from the provider when saving and from the bytes of the snapshot when loading.
+#[derive(Deserialize, Serialize)]
+pub(crate) enum SnapshotValue<'a> {
+    Null,
+    Bool(bool),
+    PositiveInteger(u64),
+    NegativeInteger(i64),
+    Float(f64),
+    String(&'a str),
+    Array(Vec<SnapshotValue<'a>>),
+    Object(Vec<(&'a str, SnapshotValue<'a>)>),
+}
+
+impl<'a> From<&'a serde_json::Value> for SnapshotValue<'a> {
+    fn from(value: &'a serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Self::Null,
+            serde_json::Value::Bool(b) => Self::Bool(*b),
+            serde_json::Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+                (Some(n), _) => Self::PositiveInteger(n),
+                (None, Some(n)) => Self::NegativeInteger(n),
+                // Numbers in JSON are always finite, so they can be converted back.
+                _ => Self::Float(n.as_f64().unwrap_or_default()),
+            },
+            serde_json::Value::String(s) => Self::String(s),
+            serde_json::Value::Array(items) => Self::Array(items.iter().map(Self::from).collect()),
+            serde_json::Value::Object(map) => Self::Object(
+                map.iter()
+                    .map(|(key, value)| (key.as_str(), Self::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<SnapshotValue<'_>> for serde_json::Value {
+    fn from(value: SnapshotValue<'_>) -> Self {
+        match value {
+            SnapshotValue::Null => Self::Null,
+            SnapshotValue::Bool(b) => Self::Bool(b),
+            SnapshotValue::PositiveInteger(n) => Self::from(n),
+            SnapshotValue::NegativeInteger(n) => Self::from(n),
+            SnapshotValue::Float(n) => Self::from(n),
+            SnapshotValue::String(s) => Self::String(s.to_owned()),
+            SnapshotValue::Array(items) => Self::Array(items.into_iter().map(Self::from).collect()),
+            SnapshotValue::Object(entries) => Self::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.to_owned(), Self::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+pub(crate) fn from_snapshot_value<T: DeserializeOwned>(
+    value: SnapshotValue<'_>,
+) -> Result<T, OptifyError> {
+    serde_json::from_value(serde_json::Value::from(value)).map_err(|e| OptifyError::Snapshot {
+        message: format!("The contents of the snapshot could not be read: {e}"),
+    })
+}
+
+/// Converts the sources for a snapshot without copying their strings.
+pub(crate) fn to_snapshot_sources(sources: &Sources) -> HashMap<&str, SnapshotValue<'_>> {
+    sources
+        .iter()
+        .map(|(name, value)| (name.as_str(), SnapshotValue::from(value)))
+        .collect()
+}
+
+pub(crate) fn from_snapshot_sources(sources: HashMap<&str, SnapshotValue<'_>>) -> Sources {
+    sources
+        .into_iter()
+        .map(|(name, value)| (name.to_owned(), serde_json::Value::from(value)))
+        .collect()
+}
+
+/// Everything needed to create an `OptionsProvider` without loading files.
+/// Values are borrowed from the provider when saving.
+/// When loading, the strings in the aliases and options are borrowed from the bytes of the snapshot.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct SnapshotContents<'a> {
+    /// Aliases and canonical feature names mapped to canonical feature names.
+    #[serde(borrow)]
+    pub(crate) aliases: HashMap<&'a str, &'a str>,
+    pub(crate) all_configurable_custom_pointers: Cow<'a, [String]>,
+    pub(crate) all_configurable_list_pointers: Cow<'a, [String]>,
+    pub(crate) all_configurable_reference_pointers: Cow<'a, [String]>,
+    pub(crate) all_configurable_secret_pointers: Cow<'a, [String]>,
+    pub(crate) all_configurable_string_pointers: Cow<'a, [String]>,
+    /// The conditions, as they are in files, because they are only read from JSON.
+    #[serde(borrow)]
+    pub(crate) conditions: SnapshotValue<'a>,
+    /// The metadata of the features, as it is in files, because the details can be any JSON value.
+    #[serde(borrow)]
+    pub(crate) features: SnapshotValue<'a>,
+    pub(crate) imports: Cow<'a, Imports>,
+    pub(crate) keyed_configurable_custom_pointers: Cow<'a, HashMap<String, Vec<String>>>,
+    pub(crate) keyed_configurable_list_pointers: Cow<'a, HashMap<String, Vec<String>>>,
+    pub(crate) keyed_configurable_reference_pointers: Cow<'a, HashMap<String, Vec<String>>>,
+    pub(crate) keyed_configurable_secret_pointers: Cow<'a, HashMap<String, Vec<String>>>,
+    pub(crate) keyed_configurable_string_pointers: Cow<'a, HashMap<String, Vec<String>>>,
+    pub(crate) loaded_files: Cow<'a, LoadedFiles>,
+    pub(crate) referenced_file_to_feature_names: Option<Cow<'a, ReferencedFileToFeatureNames>>,
+    #[serde(borrow)]
+    pub(crate) sources: HashMap<&'a str, SnapshotValue<'a>>,
+    #[serde(borrow)]
+    pub(crate) sources_without_imports: HashMap<&'a str, SnapshotValue<'a>>,
+}
+
+impl OptionsProvider {
+    /// Loads a provider from a snapshot file that was created by `save_snapshot`.
+    /// Files are not loaded and imports are not resolved again.
+    /// See `load_snapshot_with_options` to use registered types for configurable values, secret resolvers, and Liquid filters and tags.
+    pub fn load_snapshot(path: impl AsRef<Path>) -> Result<Self, OptifyError> {
+        Self::load_snapshot_with_options(path, BuilderOptions::default())
+    }
+
+    /// Loads a provider from a snapshot file with the registrations in `options`,
+    /// such as the types for configurable values, the secret resolvers, and the Liquid filters and tags,
+    /// because they are not saved in snapshots.
+    /// `env_overrides_prefix` is also used to read overrides from the environment variables of this process.
+    /// The other options are not used because the files were already loaded.
+    ///
+    /// The file is memory-mapped and decoded in place instead of being read into a buffer first,
+    /// so processes that load the same snapshot share the pages of the file.
+    /// The file must not be changed while it is being loaded.
+    pub fn load_snapshot_with_options(
+        path: impl AsRef<Path>,
+        options: BuilderOptions,
+    ) -> Result<Self, OptifyError> {
+        let path = path.as_ref();
+        let to_io_error = |e: std::io::Error| OptifyError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        };
+        let file = File::open(path).map_err(to_io_error)?;
+        // SAFETY: The mapping is only read while the provider is created and it is dropped before returning.
+        // Changing the file while it is mapped is not supported, as documented above.
+        let bytes = unsafe { memmap2::Mmap::map(&file) }.map_err(to_io_error)?;
+        Self::from_snapshot_bytes_with_options(&bytes, options)
+    }
+
+    /// Loads a provider from the bytes of a snapshot.
+    pub fn from_snapshot_bytes(bytes: &[u8]) -> Result<Self, OptifyError> {
+        Self::from_snapshot_bytes_with_options(bytes, BuilderOptions::default())
+    }
+
+    /// Loads a provider from the bytes of a snapshot with the registrations in `options`.
+    /// See `load_snapshot_with_options`.
+    ///
+    /// A snapshot is a header with the format version and a checksum followed by the tables of the provider in a binary encoding.
+    pub fn from_snapshot_bytes_with_options(
+        bytes: &[u8],
+        options: BuilderOptions,
+    ) -> Result<Self, OptifyError> {
+        if bytes.len() < SNAPSHOT_HEADER_LEN || &bytes[..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC {
+            return Err(OptifyError::Snapshot {
+                message: "The data is not an options snapshot.".to_owned(),
+            });
+        }
+        let (format_version, rest) = bytes[SNAPSHOT_MAGIC.len()..].split_at(4);
+        let format_version = u32::from_le_bytes(format_version.try_into().unwrap());
+        if format_version != SNAPSHOT_FORMAT_VERSION {
+            return Err(OptifyError::Snapshot {
+                message: format!(
+                    "The snapshot has format version {format_version}, but only version {SNAPSHOT_FORMAT_VERSION} is supported. Save the snapshot again with this version."
+                ),
+            });
+        }
+        let (checksum, rest) = rest.split_at(8);
+        let checksum = u64::from_le_bytes(checksum.try_into().unwrap());
+        let (contents_len, contents) = rest.split_at(8);
+        let contents_len = u64::from_le_bytes(contents_len.try_into().unwrap());
+        if contents.len() as u64 != contents_len || get_checksum(contents) != checksum {
+            return Err(OptifyError::Snapshot {
+                message:
+                    "The snapshot is corrupt because its contents do not match its length and checksum."
+                        .to_owned(),
+            });
+        }
+
+        let (snapshot_contents, read_len): (SnapshotContents, usize) =
+            bincode::serde::borrow_decode_from_slice(contents, bincode::config::standard())
+                .map_err(|e| OptifyError::Snapshot {
+                    message: format!("The contents of the snapshot could not be read: {e}"),
+                })?;
+        if read_len != contents.len() {
+            return Err(OptifyError::Snapshot {
+                message: "The snapshot has unexpected data after its contents.".to_owned(),
+            });
+        }
+        Self::from_snapshot_contents(snapshot_contents, options)
+    }
+
+    /// Saves the built provider to a file that can be loaded quickly with `load_snapshot`.
+    /// Like `load_snapshot`, failing to access the file is an `OptifyError::Io`.
+    pub fn save_snapshot(&self, path: impl AsRef<Path>) -> Result<(), OptifyError> {
+        let path = path.as_ref();
+        let bytes = self.to_snapshot_bytes()?;
+        std::fs::write(path, bytes).map_err(|e| OptifyError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Gets the bytes of a snapshot of the built provider.
+    pub fn to_snapshot_bytes(&self) -> Result<Vec<u8>, OptifyError> {
+        // The conditions and the features are converted to JSON like in files so that they can be read back the same way.
+        let (conditions, features) =
+            self.conditions_and_features_to_json()
+                .map_err(|e| OptifyError::Snapshot {
+                    message: format!("The provider could not be serialized: {e}"),
+                })?;
+        let contents = bincode::serde::encode_to_vec(
+            self.to_snapshot_contents(&conditions, &features),
+            bincode::config::standard(),
+        )
+        .map_err(|e| OptifyError::Snapshot {
+            message: format!("The provider could not be serialized: {e}"),
+        })?;
+
+        let mut bytes = Vec::with_capacity(SNAPSHOT_HEADER_LEN + contents.len());
+        bytes.extend_from_slice(SNAPSHOT_MAGIC);
+        bytes.extend_from_slice(&SNAPSHOT_FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&get_checksum(&contents).to_le_bytes());
+        bytes.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&contents);
+        Ok(bytes)
+    }
+}
+
+/// Checks that the values in the snapshot that need registrations have them.
+/// The builder checks the same values when it builds the provider, but registrations are not saved in snapshots.
+pub(crate) fn check_snapshot_registrations(
+    sources: &Sources,
+    custom_pointers: &[String],
+    secret_pointers: &[String],
+    options: &BuilderOptions,
+) -> Result<(), OptifyError> {
+    let mut canonical_feature_names: Vec<&String> = sources.keys().collect();
+    canonical_feature_names.sort_unstable();
+    for canonical_feature_name in canonical_feature_names {
+        let source = &sources[canonical_feature_name];
+        for pointer in custom_pointers {
+            let Some(type_name) = source
+                .pointer(pointer)
+                .and_then(|value| value.get(TYPE_KEY))
+                .and_then(|type_name| type_name.as_str())
+            else {
+                continue;
+            };
+            if !options.configurable_value_types.contains_key(type_name) {
+                return Err(OptifyError::Snapshot {
+                    message: format!(
+                        "The configurable value at {pointer:?} in the feature {canonical_feature_name:?} has the type {type_name:?}, which is not registered. Load the snapshot with the types that were registered when it was saved."
+                    ),
+                });
+            }
+        }
+        // Like when building, a secret with a resolver that was not registered is only an error when it is resolved,
+        // but a snapshot with secrets needs at least some resolvers.
+        if options.secret_resolvers.is_empty() {
+            if let Some(pointer) = secret_pointers
+                .iter()
+                .find(|pointer| source.pointer(pointer).is_some())
+            {
+                return Err(OptifyError::Snapshot {
+                    message: format!(
+                        "The feature {canonical_feature_name:?} has a secret at {pointer:?}, but no secret resolvers were given. Load the snapshot with the secret resolvers that were registered when it was saved."
+                    ),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Computes the 64-bit FNV-1a hash of the bytes.
+fn get_checksum(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_get_checksum() {
+        assert_eq!(get_checksum(b""), 0xcbf29ce484222325);
+        assert_eq!(get_checksum(b"a"), 0xaf63dc4c8601ec8c);
+        assert_ne!(get_checksum(b"ab"), get_checksum(b"ba"));
+    }
+
+    #[test]
+    fn test_snapshot_value_round_trip() {
+        let value = json!({
+            "null": null,
+            "bool": true,
+            "numbers": [0, u64::MAX, -1, i64::MIN, 1.5],
+            "nested": {"text": "é", "empty": {}, "list": []},
+        });
+        let bytes =
+            bincode::serde::encode_to_vec(SnapshotValue::from(&value), bincode::config::standard())
+                .unwrap();
+        let (decoded, _): (SnapshotValue, usize) =
+            bincode::serde::borrow_decode_from_slice(&bytes, bincode::config::standard()).unwrap();
+        assert_eq!(serde_json::Value::from(decoded), value);
+    }
+}
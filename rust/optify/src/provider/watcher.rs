@@ -5,8 +5,10 @@ use std::sync::{mpsc::channel, Arc, Mutex, RwLock};
 
 use crate::builder::builder_options::BuilderOptions;
 use crate::builder::{OptionsRegistryBuilder, OptionsWatcherBuilder};
+use crate::error::OptifyError;
 use crate::provider::{
-    CacheOptions, Features, GetOptionsPreferences, OptionsProvider, OptionsRegistry, WatcherOptions,
+    CacheOptions, EnvOverride, ExplainedValue, Features, GetOptionsPreferences, OptionsContext,
+    OptionsProvider, OptionsRegistry, WatcherOptions,
 };
 use crate::schema::metadata::OptionsMetadata;
 
@@ -15,7 +17,7 @@ pub type OptionsWatcherListener = Arc<dyn Fn(&HashSet<PathBuf>) + Send + Sync>;
 /// A registry which changes the underlying when files are changed.
 /// This is mainly meant to use for local development.
 pub struct OptionsWatcher {
-    current_provider: Arc<RwLock<OptionsProvider>>,
+    current_provider: Arc<RwLock<Arc<OptionsProvider>>>,
     last_modified: Arc<Mutex<std::time::SystemTime>>,
     watched_directories: Vec<PathBuf>,
     // The watcher needs to be held to continue watching files for changes.
@@ -31,7 +33,7 @@ impl OptionsWatcher {
     pub fn build_with_options(
         directory: impl AsRef<Path>,
         watcher_options: WatcherOptions,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, OptifyError> {
         let mut builder = OptionsWatcherBuilder::new();
         builder.with_watcher_options(watcher_options);
         builder.add_directory(directory.as_ref())?;
@@ -42,7 +44,7 @@ impl OptionsWatcher {
         directory: impl AsRef<Path>,
         schema_path: impl AsRef<Path>,
         watcher_options: WatcherOptions,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, OptifyError> {
         let mut builder = OptionsWatcherBuilder::new();
         builder.with_schema(schema_path.as_ref())?;
         builder.add_directory(directory.as_ref())?;
@@ -53,7 +55,7 @@ impl OptionsWatcher {
     pub fn build_from_directories_with_options(
         directories: &[impl AsRef<Path>],
         watcher_options: WatcherOptions,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, OptifyError> {
         let mut builder = OptionsWatcherBuilder::new();
         builder.add_directories(directories)?;
         builder.with_watcher_options(watcher_options);
@@ -64,7 +66,7 @@ impl OptionsWatcher {
         directories: &[impl AsRef<Path>],
         schema_path: impl AsRef<Path>,
         watcher_options: WatcherOptions,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, OptifyError> {
         let mut builder = OptionsWatcherBuilder::new();
         builder.with_watcher_options(watcher_options);
         builder.with_schema(schema_path.as_ref())?;
@@ -76,7 +78,7 @@ impl OptionsWatcher {
         watched_directories: &[impl AsRef<Path>],
         watcher_options: WatcherOptions,
         builder_options: BuilderOptions,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, OptifyError> {
         // Set up the watcher before building in case the files change before building.
         let (tx, rx) = channel();
         let mut debouncer_watcher = new_debouncer(
@@ -114,22 +116,25 @@ impl OptionsWatcher {
                     .for_each(|error| eprintln!("\x1b[31m[optify] {error:?}\x1b[0m")),
             },
         )
-        .map_err(|e| format!("Failed to create debouncer: {e}"))?;
+        .map_err(|e| OptifyError::Watcher {
+            message: format!("Failed to create debouncer: {e}"),
+        })?;
         for dir in watched_directories {
             debouncer_watcher
                 .watch(dir, notify::RecursiveMode::Recursive)
-                .map_err(|e| format!("Failed to watch directory {:?}: {e}", dir.as_ref()))?;
+                .map_err(|e| OptifyError::Watcher {
+                    message: format!("Failed to watch directory {:?}: {e}", dir.as_ref()),
+                })?;
         }
 
         let provider = OptionsProvider::build_from_directories_with_options(
             watched_directories,
             builder_options.clone(),
-        )
-        .map_err(|e| format!("Failed to build provider: {e}"))?;
+        )?;
         let last_modified = Arc::new(Mutex::new(std::time::SystemTime::now()));
 
         let self_ = Self {
-            current_provider: Arc::new(RwLock::new(provider)),
+            current_provider: Arc::new(RwLock::new(Arc::new(provider))),
             last_modified,
             watched_directories: watched_directories
                 .iter()
@@ -146,14 +151,16 @@ impl OptionsWatcher {
 
         std::thread::spawn(move || {
             for paths in rx {
-                let result = std::panic::catch_unwind(|| {
+                // The builder options are only read when building, so they can still be used after a panic,
+                // even though custom Liquid filters and tags are not known to be unwind safe.
+                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                     match OptionsProvider::build_from_directories_with_options(
                         &watched_directories,
                         builder_options.clone(),
                     ) {
                         Ok(new_provider) => match current_provider.write() {
                             Ok(mut provider) => {
-                                *provider = new_provider;
+                                *provider = Arc::new(new_provider);
                                 *last_modified.lock().unwrap() = std::time::SystemTime::now();
                                 eprintln!("\x1b[32m[optify] Successfully rebuilt the OptionsProvider.\x1b[0m");
                                 let listeners_guard = listeners.lock().unwrap();
@@ -171,7 +178,7 @@ impl OptionsWatcher {
                             eprintln!("\x1b[31m[optify] Error rebuilding provider: {err}\x1b[0m");
                         }
                     }
-                });
+                }));
 
                 if result.is_err() {
                     eprintln!("\x1b[31m[optify] Error rebuilding the provider. Will not change the provider until the files are fixed.\x1b[0m");
@@ -186,6 +193,18 @@ impl OptionsWatcher {
         self.listeners.lock().unwrap().push(listener);
     }
 
+    /// Creates a context that resolves and filters the feature names once
+    /// so that options can be retrieved for them many times, such as for a request.
+    /// The context keeps using the current configuration even if the files change while it is used.
+    pub fn context(
+        &self,
+        feature_names: &[impl AsRef<str>],
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> Result<OptionsContext<'static>, OptifyError> {
+        let provider = self.current_provider.read().unwrap().clone();
+        OptionsContext::from_shared_provider(provider, feature_names, preferences)
+    }
+
     /// Returns the time when the provider was finished building.
     pub fn last_modified(&self) -> std::time::SystemTime {
         *self.last_modified.lock().unwrap()
@@ -193,13 +212,15 @@ impl OptionsWatcher {
 }
 
 impl OptionsRegistry for OptionsWatcher {
-    fn build(directory: impl AsRef<Path>) -> Result<OptionsWatcher, String> {
+    fn build(directory: impl AsRef<Path>) -> Result<OptionsWatcher, OptifyError> {
         let mut builder = OptionsWatcherBuilder::new();
         builder.add_directory(directory.as_ref())?;
         builder.build()
     }
 
-    fn build_from_directories(directories: &[impl AsRef<Path>]) -> Result<OptionsWatcher, String> {
+    fn build_from_directories(
+        directories: &[impl AsRef<Path>],
+    ) -> Result<OptionsWatcher, OptifyError> {
         let mut builder = OptionsWatcherBuilder::new();
         builder.add_directories(directories)?;
         builder.build()
@@ -208,7 +229,7 @@ impl OptionsRegistry for OptionsWatcher {
     fn build_from_directories_with_options(
         directories: &[impl AsRef<Path>],
         options: BuilderOptions,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, OptifyError> {
         let mut builder = OptionsWatcherBuilder::new();
         builder.with_options(options)?;
         builder.add_directories(directories)?;
@@ -218,17 +239,37 @@ impl OptionsRegistry for OptionsWatcher {
     fn build_with_options(
         directory: impl AsRef<Path>,
         options: BuilderOptions,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, OptifyError> {
         let mut builder = OptionsWatcherBuilder::new();
         builder.with_options(options)?;
         builder.add_directory(directory.as_ref())?;
         builder.build()
     }
 
+    fn clear_cache(&self) {
+        self.current_provider.read().unwrap().clear_cache()
+    }
+
+    fn explain_options(
+        &self,
+        key: &str,
+        feature_names: &[impl AsRef<str>],
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> std::result::Result<Vec<ExplainedValue>, OptifyError> {
+        self.current_provider
+            .read()
+            .unwrap()
+            .explain_options(key, feature_names, preferences)
+    }
+
     fn get_aliases(&self) -> Vec<String> {
         self.current_provider.read().unwrap().get_aliases()
     }
 
+    fn get_env_overrides(&self) -> Vec<EnvOverride> {
+        self.current_provider.read().unwrap().get_env_overrides()
+    }
+
     fn get_features_and_aliases(&self) -> Vec<String> {
         self.current_provider
             .read()
@@ -241,7 +282,7 @@ impl OptionsRegistry for OptionsWatcher {
         feature_names: &[impl AsRef<str>],
         cache_options: Option<&CacheOptions>,
         preferences: Option<&GetOptionsPreferences>,
-    ) -> std::result::Result<serde_json::Value, String> {
+    ) -> std::result::Result<serde_json::Value, OptifyError> {
         self.current_provider.read().unwrap().get_all_options(
             feature_names,
             cache_options,
@@ -252,7 +293,7 @@ impl OptionsRegistry for OptionsWatcher {
     fn get_canonical_feature_name(
         &self,
         feature_name: &str,
-    ) -> std::result::Result<String, String> {
+    ) -> std::result::Result<String, OptifyError> {
         self.current_provider
             .read()
             .unwrap()
@@ -262,7 +303,7 @@ impl OptionsRegistry for OptionsWatcher {
     fn get_canonical_feature_names(
         &self,
         feature_names: &[impl AsRef<str>],
-    ) -> std::result::Result<Vec<String>, String> {
+    ) -> std::result::Result<Vec<String>, OptifyError> {
         self.current_provider
             .read()
             .unwrap()
@@ -298,7 +339,7 @@ impl OptionsRegistry for OptionsWatcher {
         &self,
         feature_names: &[impl AsRef<str>],
         preferences: Option<&GetOptionsPreferences>,
-    ) -> std::result::Result<Vec<String>, String> {
+    ) -> std::result::Result<Vec<String>, OptifyError> {
         self.current_provider
             .read()
             .unwrap()
@@ -309,20 +350,66 @@ impl OptionsRegistry for OptionsWatcher {
         &self,
         key: &str,
         feature_names: &[impl AsRef<str>],
-    ) -> std::result::Result<serde_json::Value, String> {
+    ) -> std::result::Result<serde_json::Value, OptifyError> {
         self.current_provider
             .read()
             .unwrap()
             .get_options(key, feature_names)
     }
 
+    fn get_options_as<T: serde::de::DeserializeOwned>(
+        &self,
+        key: &str,
+        feature_names: &[impl AsRef<str>],
+        cache_options: Option<&CacheOptions>,
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> std::result::Result<T, OptifyError> {
+        self.current_provider.read().unwrap().get_options_as(
+            key,
+            feature_names,
+            cache_options,
+            preferences,
+        )
+    }
+
+    fn get_options_as_arc<T: serde::de::DeserializeOwned + Send + Sync + 'static>(
+        &self,
+        key: &str,
+        feature_names: &[impl AsRef<str>],
+        cache_options: Option<&CacheOptions>,
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> std::result::Result<Arc<T>, OptifyError> {
+        self.current_provider.read().unwrap().get_options_as_arc(
+            key,
+            feature_names,
+            cache_options,
+            preferences,
+        )
+    }
+
+    fn get_options_batch(
+        &self,
+        keys: &[impl AsRef<str>],
+        feature_names: &[impl AsRef<str>],
+        cache_options: Option<&CacheOptions>,
+        preferences: Option<&GetOptionsPreferences>,
+    ) -> std::result::Result<std::collections::HashMap<String, serde_json::Value>, OptifyError>
+    {
+        self.current_provider.read().unwrap().get_options_batch(
+            keys,
+            feature_names,
+            cache_options,
+            preferences,
+        )
+    }
+
     fn get_options_with_preferences(
         &self,
         key: &str,
         feature_names: &[impl AsRef<str>],
         cache_options: Option<&CacheOptions>,
         preferences: Option<&GetOptionsPreferences>,
-    ) -> std::result::Result<serde_json::Value, String> {
+    ) -> std::result::Result<serde_json::Value, OptifyError> {
         self.current_provider
             .read()
             .unwrap()
@@ -340,7 +427,7 @@ impl OptionsRegistry for OptionsWatcher {
         &self,
         feature_names: &[impl AsRef<str>],
         preferences: Option<&GetOptionsPreferences>,
-    ) -> std::result::Result<Vec<Option<String>>, String> {
+    ) -> std::result::Result<Vec<Option<String>>, OptifyError> {
         self.current_provider
             .read()
             .unwrap()
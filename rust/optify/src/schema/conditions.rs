@@ -85,7 +85,9 @@ impl Condition {
     }
 }
 
+// Serialize in the same format that is used in files.
 #[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
 pub enum ConditionExpression {
     Condition(Condition),
     And { and: Vec<Self> },
@@ -36,7 +36,14 @@ pub struct OptionsMetadata {
     /// For example, emails separated by ";".
     pub owners: Option<String>,
 
+    /// The paths of the files for features with the same canonical feature name that this feature overrides,
+    /// in the order that they were added.
+    /// This is set when directories are added with an overlay mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overridden_paths: Option<Vec<String>>,
+
     /// The path to the file that contains the options.
+    /// When features are overlaid, this is the file that was added last.
     pub path: Option<String>,
 }
 
@@ -55,6 +62,7 @@ impl OptionsMetadata {
             dependents,
             name,
             owners,
+            overridden_paths: None,
             path,
         }
     }
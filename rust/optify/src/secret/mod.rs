@@ -0,0 +1,7 @@
+pub mod secret_impl;
+
+// Re-export the main types for easier access
+pub use secret_impl::{
+    EnvSecretResolver, FileSecretResolver, Secret, SecretResolver, SecretResolvers,
+    ENV_SECRET_RESOLVER, FILE_SECRET_RESOLVER, REDACTED_SECRET,
+};
@@ -0,0 +1,94 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::panic::RefUnwindSafe;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// The name that `EnvSecretResolver` is usually registered with.
+pub const ENV_SECRET_RESOLVER: &str = "env";
+
+/// The name that `FileSecretResolver` is usually registered with.
+pub const FILE_SECRET_RESOLVER: &str = "file";
+
+/// The value used instead of a secret when secrets are redacted.
+pub const REDACTED_SECRET: &str = "[REDACTED]";
+
+/// A reference to a secret, such as a password or an API key, that is resolved when options are retrieved.
+/// Parsed from a `serde_json::Value` such as `{"$type": "Optify.Secret", "resolver": "env", "name": "DB_PASSWORD"}`.
+#[derive(Deserialize, Debug)]
+pub struct Secret {
+    /// The name that the resolver was registered with.
+    pub resolver: String,
+
+    /// The name of the secret for the resolver, such as the name of an environment variable.
+    pub name: String,
+}
+
+/// Resolves the value of a `Secret`.
+/// Resolvers are registered on the builder with `with_secret_resolver`.
+/// Resolvers must be `RefUnwindSafe` so that providers can still be used after a panic is caught, such as in bindings for other languages.
+pub trait SecretResolver: RefUnwindSafe + Send + Sync {
+    /// Returns the value of the secret.
+    /// Error messages should not include the value of the secret.
+    fn resolve(&self, secret: &Secret) -> Result<String, String>;
+}
+
+/// Resolvers mapped to the names that secrets use to refer to them.
+pub type SecretResolvers = HashMap<String, Arc<dyn SecretResolver>>;
+
+/// Resolves secrets from the environment variables of the process.
+/// The name of the secret is the name of the environment variable.
+#[derive(Clone, Debug, Default)]
+pub struct EnvSecretResolver;
+
+impl SecretResolver for EnvSecretResolver {
+    fn resolve(&self, secret: &Secret) -> Result<String, String> {
+        std::env::var(&secret.name).map_err(|e| {
+            format!(
+                "Failed to read the environment variable {:?}: {e}",
+                secret.name
+            )
+        })
+    }
+}
+
+/// Resolves secrets from files in a directory, such as secrets mounted as files by a container orchestrator.
+/// The name of the secret is the path of the file relative to the directory.
+/// One trailing newline is removed from the contents.
+#[derive(Clone, Debug)]
+pub struct FileSecretResolver {
+    directory: PathBuf,
+}
+
+impl FileSecretResolver {
+    pub fn new(directory: impl AsRef<Path>) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl SecretResolver for FileSecretResolver {
+    fn resolve(&self, secret: &Secret) -> Result<String, String> {
+        let relative_path = Path::new(&secret.name);
+        if !relative_path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(format!(
+                "The path {:?} must be relative to the directory for secrets and must not contain \"..\".",
+                secret.name
+            ));
+        }
+        let path = self.directory.join(relative_path);
+        let mut contents = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read the file {path:?}: {e}"))?;
+        if contents.ends_with('\n') {
+            contents.pop();
+            if contents.ends_with('\r') {
+                contents.pop();
+            }
+        }
+        Ok(contents)
+    }
+}
@@ -1,9 +1,14 @@
 use optify::{
-    builder::{BuilderOptions, OptionsProviderBuilder, OptionsRegistryBuilder},
+    builder::{
+        BuilderOptions, DiagnosticCategory, FeatureFileParser, NonUtf8PathPolicy,
+        OptionsProviderBuilder, OptionsRegistryBuilder, SymlinkPolicy,
+    },
     provider::{GetOptionsPreferences, OptionsProvider, OptionsRegistry},
+    OptifyError,
 };
 
 use serde_json::json;
+use std::sync::Arc;
 
 #[test]
 fn test_builder_circular_imports() -> Result<(), Box<dyn std::error::Error>> {
@@ -11,12 +16,17 @@ fn test_builder_circular_imports() -> Result<(), Box<dyn std::error::Error>> {
     match OptionsProvider::build(path) {
         Ok(_) => panic!("Expected an error."),
         Err(e) => {
-            // Just use a big regex instead of being consistent slowing down the builder to keep ordered maps or sets.
-            let pattern = r#"Error when resolving imports for 'a': Cycle detected with import 'b'. The features in the path \(not in order\): \{"a", "b"}|Error when resolving imports for 'a': Cycle detected with import 'b'. The features in the path \(not in order\): \{"b", "a"}|Error when resolving imports for 'b': Cycle detected with import 'a'. The features in the path \(not in order\): \{"a", "b"}|Error when resolving imports for 'b': Cycle detected with import 'a'. The features in the path \(not in order\): \{"b", "a"}"#;
+            // The feature that detects the cycle depends on the order that features are resolved in.
+            let pattern = r#"^Error when resolving imports for 'a': Cycle detected with import 'b'. The features in the path \(not in order\): \["a", "b"\]$|^Error when resolving imports for 'b': Cycle detected with import 'a'. The features in the path \(not in order\): \["a", "b"\]$"#;
+            let message = e.to_string();
             assert!(
-                regex::Regex::new(pattern)?.is_match(&e),
-                "Got: {e}\nExpected pattern: {pattern}",
+                regex::Regex::new(pattern)?.is_match(&message),
+                "Got: {message}\nExpected pattern: {pattern}",
             );
+            match e {
+                OptifyError::ImportCycle { path, .. } => assert_eq!(path, vec!["a", "b"]),
+                _ => panic!("Expected an import cycle error. Got: {e:?}"),
+            }
             Ok(())
         }
     }
@@ -28,11 +38,13 @@ fn test_builder_cycle_in_imports() -> Result<(), Box<dyn std::error::Error>> {
     match OptionsProvider::build_from_directories(&[path]) {
         Ok(_) => panic!("Expected an error."),
         Err(e) => {
-            let pattern = r#"Error when resolving imports for '[abc]': Cycle detected with import '[abc]'. The features in the path \(not in order\): \{("([abc]|start)", ){2,3}"([abc]|start)"}"#;
+            let pattern = r#"Error when resolving imports for '[abc]': Cycle detected with import '[abc]'. The features in the path \(not in order\): \[("([abc]|start)", ){2,3}"([abc]|start)"\]"#;
+            let message = e.to_string();
             assert!(
-                regex::Regex::new(pattern)?.is_match(&e),
-                "Got: {e}\nExpected pattern: {pattern}",
+                regex::Regex::new(pattern)?.is_match(&message),
+                "Got: {message}\nExpected pattern: {pattern}",
             );
+            assert!(matches!(e, OptifyError::ImportCycle { .. }));
             Ok(())
         }
     }
@@ -46,15 +58,93 @@ fn test_builder_duplicate_alias() -> Result<(), Box<dyn std::error::Error>> {
         Ok(_) => panic!("Expected an error."),
         Err(e) => {
             let pattern = r#"The alias 'b' for canonical feature name 'a' is already mapped to 'b'\.|The alias 'b' for canonical feature name 'b' is already mapped to 'a'\."#;
+            let message = e.to_string();
             assert!(
-                regex::Regex::new(pattern)?.is_match(&e),
-                "Got: {e}\nExpected pattern: {pattern}",
+                regex::Regex::new(pattern)?.is_match(&message),
+                "Got: {message}\nExpected pattern: {pattern}",
             );
+            assert!(matches!(e, OptifyError::DuplicateAlias { ref alias, .. } if alias == "b"));
             Ok(())
         }
     }
 }
 
+/// Parses lines such as `key=value` into options for a feature.
+struct PropertiesParser;
+
+impl FeatureFileParser for PropertiesParser {
+    fn parse(&self, contents: &str) -> Result<serde_json::Value, String> {
+        let mut options = serde_json::Map::new();
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Expected key=value, got {line:?}"))?;
+            options.insert(key.trim().to_owned(), json!(value.trim()));
+        }
+        Ok(json!({"options": options}))
+    }
+}
+
+#[test]
+fn test_builder_feature_file_parsers() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let directory = temp_dir.path();
+    std::fs::write(
+        directory.join("commented.json"),
+        r#"{
+            // Comments and trailing commas are allowed in .json files.
+            "options": {
+                "list": [1, 2,],
+                /* A block comment. */ "url": "https://example.com/*path*/",
+            },
+        }"#,
+    )?;
+    std::fs::write(
+        directory.join("also_commented.jsonc"),
+        "{\"options\": {\"jsonc\": true}} // Done.",
+    )?;
+    std::fs::write(
+        directory.join("custom.properties"),
+        "key = value\nother=2\n",
+    )?;
+
+    // Without a parser for the extension, the file is a raw file for configurable strings.
+    let provider = OptionsProvider::build(directory)?;
+    let mut features = provider.get_features();
+    features.sort();
+    assert_eq!(features, vec!["also_commented", "commented"]);
+    let options = provider.get_all_options(&["commented", "also_commented"], None, None)?;
+    assert_eq!(
+        options,
+        json!({"jsonc": true, "list": [1, 2], "url": "https://example.com/*path*/"})
+    );
+
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_feature_file_parser("properties", Arc::new(PropertiesParser))?;
+    builder.add_directory(directory)?;
+    let provider = builder.build()?;
+    assert_eq!(
+        provider.get_all_options(&["custom"], None, None)?,
+        json!({"key": "value", "other": "2"})
+    );
+
+    // Registered parsers replace the built-in parser for the same extension.
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_feature_file_parser("json", Arc::new(PropertiesParser))?;
+    match builder.add_directory(directory) {
+        Err(OptifyError::Parse { file, message }) => {
+            assert!(file.ends_with("commented.json"), "Unexpected file: {file}");
+            assert!(
+                message.starts_with("Expected key=value"),
+                "Unexpected message: {message}"
+            );
+        }
+        other => panic!("Expected a parse error, got: {:?}", other.err()),
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_builder_invalid_file() -> Result<(), Box<dyn std::error::Error>> {
     let path = std::path::Path::new("tests/invalid_file");
@@ -65,7 +155,8 @@ fn test_builder_invalid_file() -> Result<(), Box<dyn std::error::Error>> {
                 .unwrap()
                 .to_string_lossy()
                 .to_string();
-            assert_eq!(e, format!("Error loading file '{expected_path}': simple key expected at byte 31 line 4 column 1 in tests/invalid_file/invalid.yaml"));
+            assert_eq!(e.to_string(), format!("Error loading file '{expected_path}': simple key expected at byte 31 line 4 column 1 in tests/invalid_file/invalid.yaml"));
+            assert!(matches!(e, OptifyError::Parse { file, .. } if file == expected_path));
             Ok(())
         }
     }
@@ -77,9 +168,16 @@ fn test_builder_conditions_in_imported_feature() -> Result<(), Box<dyn std::erro
     match OptionsProvider::build(path) {
         Ok(_) => panic!("Expected an error."),
         Err(e) => {
-            assert!(e.starts_with(
+            assert!(e.to_string().starts_with(
                 "Error when resolving imports for 'parent': The import 'invalid' has conditions. Conditions cannot be used in imported features."
             ));
+            assert_eq!(
+                e,
+                OptifyError::ImportHasConditions {
+                    feature: "parent".to_owned(),
+                    import: "invalid".to_owned(),
+                }
+            );
             Ok(())
         }
     }
@@ -99,7 +197,8 @@ fn test_builder_invalid_condition_pattern() -> Result<(), Box<dyn std::error::Er
             let expected = format!(
                 "Error deserializing configuration for file '{expected_path}': regex parse error:\n    {{invalid}}\n    ^\nerror: repetition operator missing expression"
             );
-            assert_eq!(e, expected);
+            assert_eq!(e.to_string(), expected);
+            assert!(matches!(e, OptifyError::Deserialize { .. }));
             Ok(())
         }
     }
@@ -141,7 +240,7 @@ fn test_builder_used_canonical_alias() {
         Ok(_) => panic!("Expected an error."),
         Err(e) => {
             assert_eq!(
-                e,
+                e.to_string(),
                 "The alias 'a' for canonical feature name 'a' is already mapped to 'a'.",
             );
         }
@@ -271,3 +370,761 @@ fn test_track_file_references_by_key_name_for_arguments_feature(
     assert_eq!(referenced_features, vec!["arguments", "feature_with_cs"]);
     Ok(())
 }
+
+#[test]
+fn test_builder_add_feature_and_raw_file() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_options(BuilderOptions {
+        are_configurable_strings_enabled: true,
+        ..BuilderOptions::default()
+    })?;
+    builder.add_feature(
+        "base",
+        json!({
+            "options": {
+                "myConfig": {
+                    "greeting": {
+                        "$type": "Optify.ConfigurableString",
+                        "base": { "file": "templates/greeting.liquid" },
+                        "arguments": { "name": "World" }
+                    },
+                    "size": 1
+                }
+            }
+        }),
+    )?;
+    builder.add_feature(
+        "child",
+        json!({
+            "metadata": { "aliases": ["c"] },
+            "imports": ["base"],
+            "options": { "myConfig": { "size": 2 } }
+        }),
+    )?;
+    builder.add_feature(
+        "conditional",
+        json!({
+            "conditions": { "jsonPointer": "/env", "equals": "test" },
+            "options": { "myConfig": { "size": 3 } }
+        }),
+    )?;
+    builder.add_raw_file("templates/greeting.liquid", "Hello, {{ name }}!")?;
+    let provider = builder.build()?;
+
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_strings_enabled = true;
+    preferences.set_constraints(Some(json!({"env": "prod"})));
+    let options = provider.get_options_with_preferences(
+        "myConfig",
+        &["c", "conditional"],
+        None,
+        Some(&preferences),
+    )?;
+    assert_eq!(options, json!({"greeting": "Hello, World!", "size": 2}));
+
+    preferences.set_constraints(Some(json!({"env": "test"})));
+    let options = provider.get_options_with_preferences(
+        "/myConfig/size",
+        &["c", "conditional"],
+        None,
+        Some(&preferences),
+    )?;
+    assert_eq!(options, json!(3));
+
+    let metadata = provider.get_feature_metadata("child").unwrap();
+    assert_eq!(metadata.name, Some("child".to_owned()));
+    assert_eq!(metadata.path, None);
+    assert_eq!(
+        provider.get_feature_metadata("base").unwrap().dependents,
+        Some(vec!["child".to_owned()])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_builder_liquid_template_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let directory = temp_dir.path();
+    std::fs::create_dir_all(directory.join("templates"))?;
+    std::fs::write(directory.join("templates/bad.liquid"), "{% if %}")?;
+    std::fs::write(
+        directory.join("templates/good.liquid"),
+        "Hello, {{ name }}!",
+    )?;
+    std::fs::write(
+        directory.join("inline.json"),
+        r#"{"options": {"myConfig": {"greeting": {
+            "$type": "Optify.ConfigurableString",
+            "base": {"liquid": "Hello, {{ name"}
+        }}}}"#,
+    )?;
+    std::fs::write(
+        directory.join("file.json"),
+        r#"{"options": {"myConfig": {
+            "bad": {"$type": "Optify.ConfigurableString", "base": {"file": "templates/bad.liquid"}},
+            "good": {
+                "$type": "Optify.ConfigurableString",
+                "base": {"file": "templates/good.liquid"},
+                "arguments": {"name": "World"}
+            }
+        }}}"#,
+    )?;
+
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_options(BuilderOptions {
+        are_configurable_values_enabled: true,
+        collect_diagnostics: true,
+        ..BuilderOptions::default()
+    })?;
+    builder.add_directory(directory)?;
+    let (provider, report) = builder.build_with_report();
+    assert!(provider.is_none());
+    assert_eq!(report.errors.len(), 2);
+    assert!(report
+        .errors
+        .iter()
+        .all(|error| error.category == DiagnosticCategory::Parse));
+    match &report.errors[0].error {
+        OptifyError::LiquidTemplate { file, pointer, .. } => {
+            assert_eq!(file, "templates/bad.liquid");
+            assert_eq!(pointer, &None);
+        }
+        other => panic!("Expected a Liquid template error, got: {other:?}"),
+    }
+    match &report.errors[1].error {
+        OptifyError::LiquidTemplate { file, pointer, .. } => {
+            assert!(file.ends_with("inline.json"), "Got: {file}");
+            assert_eq!(pointer.as_deref(), Some("/myConfig/greeting"));
+        }
+        other => panic!("Expected a Liquid template error, got: {other:?}"),
+    }
+
+    // Templates that were parsed when building are rendered when options are retrieved.
+    std::fs::remove_file(directory.join("inline.json"))?;
+    std::fs::write(
+        directory.join("file.json"),
+        r#"{"options": {"myConfig": {"good": {
+            "$type": "Optify.ConfigurableString",
+            "base": {"file": "templates/good.liquid"},
+            "arguments": {"name": "World"}
+        }}}}"#,
+    )?;
+    let provider = OptionsProvider::build_with_options(
+        directory,
+        BuilderOptions {
+            are_configurable_values_enabled: true,
+            ..BuilderOptions::default()
+        },
+    )?;
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_values_enabled = true;
+    assert_eq!(
+        provider.get_options_with_preferences(
+            "/myConfig/good",
+            &["file"],
+            None,
+            Some(&preferences)
+        )?,
+        json!("Hello, World!")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_builder_add_feature_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = OptionsProviderBuilder::new();
+    builder.add_feature("a", json!({"options": {"key": 1}}))?;
+
+    match builder.add_feature("a", json!({})) {
+        Err(OptifyError::DuplicateFeature { name }) => assert_eq!(name, "a"),
+        other => panic!("Expected a duplicate feature error, got: {:?}", other.err()),
+    }
+    match builder.add_feature("b", json!({"imports": 5})) {
+        Err(OptifyError::Deserialize { file, .. }) => assert_eq!(file, "b"),
+        other => panic!("Expected a deserialize error, got: {:?}", other.err()),
+    }
+
+    builder.add_raw_file("file.txt", "contents")?;
+    match builder.add_raw_file("file.txt", "other contents") {
+        Err(OptifyError::DuplicateFile { relative_path }) => assert_eq!(relative_path, "file.txt"),
+        other => panic!("Expected a duplicate file error, got: {:?}", other.err()),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_builder_collect_diagnostics() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let directory = temp_dir.path();
+    std::fs::write(directory.join("bad.json"), "{ not json")?;
+    std::fs::write(
+        directory.join("a.json"),
+        r#"{"metadata": {"aliases": ["dup"]}, "imports": ["missing"]}"#,
+    )?;
+    std::fs::write(
+        directory.join("b.json"),
+        r#"{"metadata": {"aliases": ["dup"]}}"#,
+    )?;
+    std::fs::write(directory.join("good.json"), r#"{"options": {"key": 1}}"#)?;
+
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_options(BuilderOptions {
+        collect_diagnostics: true,
+        ..BuilderOptions::default()
+    })?;
+    builder.add_directory(directory)?;
+    builder.add_directory(directory.join("does not exist"))?;
+    builder.add_feature("c", json!({"imports": ["d"]}))?;
+    builder.add_feature("d", json!({"imports": ["c"]}))?;
+
+    let (provider, report) = builder.build_with_report();
+    assert!(provider.is_none());
+    assert!(report.warnings.is_empty());
+    let mut categories: Vec<String> = report
+        .errors
+        .iter()
+        .map(|error| format!("{:?}", error.category))
+        .collect();
+    categories.sort_unstable();
+    assert_eq!(
+        categories,
+        vec!["Directory", "Duplicate", "Import", "Import", "Parse"]
+    );
+
+    let parse_error = report
+        .errors
+        .iter()
+        .find(|error| error.category == DiagnosticCategory::Parse)
+        .unwrap();
+    assert!(parse_error.path.as_ref().unwrap().ends_with("bad.json"));
+    let missing_import = report
+        .errors
+        .iter()
+        .find(|error| matches!(error.error, OptifyError::NonCanonicalImport { .. }))
+        .unwrap();
+    assert!(missing_import.path.as_ref().unwrap().ends_with("a.json"));
+    assert!(report
+        .errors
+        .iter()
+        .any(|error| matches!(error.error, OptifyError::ImportCycle { .. })));
+
+    // `build` fails with the first error.
+    assert_eq!(builder.build().err(), Some(report.errors[0].error.clone()));
+
+    Ok(())
+}
+
+#[test]
+fn test_builder_build_with_report_without_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_options(BuilderOptions {
+        collect_diagnostics: true,
+        ..BuilderOptions::default()
+    })?;
+    builder.add_directory("../../tests/test_suites/inheritance/configs")?;
+
+    let (provider, report) = builder.build_with_report();
+    assert!(!report.has_errors());
+    let provider = provider.unwrap();
+    assert_eq!(
+        provider.get_options("/myConfig/key1", &["grandparent"])?,
+        json!("value")
+    );
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_builder_symlink_policies() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let directory = temp_dir.path().join("configs");
+    std::fs::create_dir(&directory)?;
+    let target = temp_dir.path().join("target.json");
+    std::fs::write(&target, r#"{"options": {"key": 1}}"#)?;
+    std::os::unix::fs::symlink(&target, directory.join("linked.json"))?;
+
+    let build = |symlink_policy| {
+        let mut builder = OptionsProviderBuilder::new();
+        builder.with_options(BuilderOptions {
+            collect_diagnostics: true,
+            symlink_policy,
+            ..BuilderOptions::default()
+        })?;
+        builder.add_directory(&directory)?;
+        Ok::<_, OptifyError>(builder.build_with_report())
+    };
+
+    let (provider, report) = build(SymlinkPolicy::Follow)?;
+    assert!(report.warnings.is_empty());
+    assert_eq!(provider.unwrap().get_options("key", &["linked"])?, json!(1));
+
+    let (provider, report) = build(SymlinkPolicy::Skip)?;
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(report.warnings[0].category, DiagnosticCategory::Path);
+    assert!(provider.unwrap().get_features().is_empty());
+
+    let (provider, report) = build(SymlinkPolicy::Error)?;
+    assert!(provider.is_none());
+    assert!(matches!(
+        report.errors[0].error,
+        OptifyError::InvalidPath { .. }
+    ));
+
+    // A broken link is an error instead of a panic.
+    std::fs::remove_file(&target)?;
+    match OptionsProvider::build(&directory) {
+        Err(OptifyError::Io { path, .. }) => assert!(path.ends_with("linked.json")),
+        other => panic!("Expected an IO error, got: {:?}", other.err()),
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_builder_non_utf8_path_policies() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let temp_dir = tempfile::tempdir()?;
+    let directory = temp_dir.path();
+    let file_name = std::ffi::OsStr::from_bytes(b"bad\xff.json");
+    std::fs::write(directory.join(file_name), r#"{"options": {"key": 1}}"#)?;
+
+    let build_with_policy = |non_utf8_path_policy| {
+        OptionsProvider::build_with_options(
+            directory,
+            BuilderOptions {
+                non_utf8_path_policy,
+                ..BuilderOptions::default()
+            },
+        )
+    };
+
+    assert!(matches!(
+        build_with_policy(NonUtf8PathPolicy::Error),
+        Err(OptifyError::InvalidPath { .. })
+    ));
+    assert!(build_with_policy(NonUtf8PathPolicy::Skip)?
+        .get_features()
+        .is_empty());
+    assert_eq!(
+        build_with_policy(NonUtf8PathPolicy::Lossy)?.get_options("key", &["bad\u{FFFD}"])?,
+        json!(1)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_builder_ignores_files() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let directory = temp_dir.path();
+    let write = |relative_path: &str, contents: &str| {
+        let path = directory.join(relative_path);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, contents)
+    };
+    write(".optifyignore", "drafts/\n*.md\n")?;
+    write(
+        ".optify/config.json",
+        r#"{"areConfigurableValuesEnabled": true, "ignore": ["scratch.json"], "rawFileGlobs": ["templates/**"]}"#,
+    )?;
+    write(
+        "a.json",
+        r#"{"options": {
+            "allowed": {"$type": "Optify.ConfigurableString", "base": {"file": "templates/t.liquid"}},
+            "notAllowed": {"$type": "Optify.ConfigurableString", "base": {"file": "notes.txt"}}
+        }}"#,
+    )?;
+    write("drafts/b.json", r#"{"options": {}}"#)?;
+    write("excluded/c.json", r#"{"options": {}}"#)?;
+    write("scratch.json", "not valid JSON")?;
+    write("README.md", "# Configs")?;
+    write("templates/t.liquid", "from the template")?;
+    write("notes.txt", "notes")?;
+
+    let provider = OptionsProvider::build_with_options(
+        directory,
+        BuilderOptions {
+            exclude_globs: vec!["excluded/**".to_owned()],
+            ..BuilderOptions::default()
+        },
+    )?;
+    assert_eq!(provider.get_features(), vec!["a"]);
+
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_strings_enabled = true;
+    assert_eq!(
+        provider.get_options_with_preferences("allowed", &["a"], None, Some(&preferences))?,
+        json!("from the template")
+    );
+    assert!(provider
+        .get_options_with_preferences("notAllowed", &["a"], None, Some(&preferences))
+        .is_err());
+
+    let provider = OptionsProvider::build_with_options(
+        directory,
+        BuilderOptions {
+            include_globs: vec!["excluded/*.json".to_owned()],
+            ..BuilderOptions::default()
+        },
+    )?;
+    assert_eq!(provider.get_features(), vec!["excluded/c"]);
+
+    match OptionsProvider::build_with_options(
+        directory,
+        BuilderOptions {
+            exclude_globs: vec!["[".to_owned()],
+            ..BuilderOptions::default()
+        },
+    ) {
+        Err(OptifyError::Glob { pattern, .. }) => assert_eq!(pattern, "["),
+        other => panic!("Expected a glob error, got: {:?}", other.err()),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_builder_nested_ignore_files() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let directory = temp_dir.path();
+    let write = |relative_path: &str, contents: &str| {
+        let path = directory.join(relative_path);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, contents)
+    };
+    write(".optifyignore", "*.md\n")?;
+    // Patterns are relative to the directory with the file and can include files that a parent directory ignores.
+    write("nested/.optifyignore", "/local.json\n!README.md\n")?;
+    write(
+        "a.json",
+        r#"{"options": {
+            "readme": {"$type": "Optify.ConfigurableString", "base": {"file": "nested/README.md"}},
+            "ignoreFile": {"$type": "Optify.ConfigurableString", "base": {"file": "nested/.optifyignore"}}
+        }}"#,
+    )?;
+    write("README.md", "# Configs")?;
+    write("nested/local.json", "not valid JSON")?;
+    write("nested/deeper/local.json", r#"{"options": {}}"#)?;
+    write("nested/README.md", "nested readme")?;
+
+    let provider = OptionsProvider::build_with_options(
+        directory,
+        BuilderOptions {
+            are_configurable_values_enabled: true,
+            ..BuilderOptions::default()
+        },
+    )?;
+    let mut features = provider.get_features();
+    features.sort();
+    assert_eq!(features, vec!["a", "nested/deeper/local"]);
+
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_strings_enabled = true;
+    assert_eq!(
+        provider.get_options_with_preferences("readme", &["a"], None, Some(&preferences))?,
+        json!("nested readme")
+    );
+    // Ignore files are not loaded for configurable strings.
+    assert!(provider
+        .get_options_with_preferences("ignoreFile", &["a"], None, Some(&preferences))
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_builder_namespaces() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let write = |relative_path: &str, contents: &str| {
+        let path = temp_dir.path().join(relative_path);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, contents)
+    };
+    let feature_a = r#"{
+        "metadata": {"aliases": ["a"]},
+        "imports": ["base"],
+        "options": {"message": {"$type": "Optify.ConfigurableString", "base": {"file": "t.liquid"}}}
+    }"#;
+    write(
+        "payments/.optify/config.json",
+        r#"{"namespace": "payments"}"#,
+    )?;
+    write("payments/feature_A.json", feature_a)?;
+    write(
+        "payments/base.json",
+        r#"{"imports": ["shipping/base"], "options": {"team": "payments"}}"#,
+    )?;
+    write("payments/t.liquid", "payments template")?;
+    write("shipping/feature_A.json", feature_a)?;
+    write(
+        "shipping/base.json",
+        r#"{"options": {"team": "shipping", "shipping": true}}"#,
+    )?;
+    write("shipping/t.liquid", "shipping template")?;
+
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_options(BuilderOptions {
+        are_configurable_values_enabled: true,
+        are_configurable_strings_enabled: true,
+        ..BuilderOptions::default()
+    })?;
+    builder.add_directory(temp_dir.path().join("payments"))?;
+    builder.add_directory_with_prefix(temp_dir.path().join("shipping"), "shipping")?;
+    let provider = builder.build()?;
+
+    let mut features = provider.get_features();
+    features.sort_unstable();
+    assert_eq!(
+        features,
+        vec![
+            "payments/base",
+            "payments/feature_A",
+            "shipping/base",
+            "shipping/feature_A"
+        ]
+    );
+    assert_eq!(
+        provider.get_canonical_feature_name("shipping/a")?,
+        "shipping/feature_A"
+    );
+
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_strings_enabled = true;
+    let options = provider.get_all_options(&["payments/a"], None, Some(&preferences))?;
+    assert_eq!(
+        options,
+        json!({"message": "payments template", "shipping": true, "team": "payments"})
+    );
+    let options = provider.get_all_options(&["shipping/a"], None, Some(&preferences))?;
+    assert_eq!(
+        options,
+        json!({"message": "shipping template", "shipping": true, "team": "shipping"})
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_builder_overlay_directories() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let write = |relative_path: &str, contents: &str| {
+        let path = temp_dir.path().join(relative_path);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, contents)
+    };
+    write(
+        "library/a.json",
+        r#"{"metadata": {"aliases": ["x"], "owners": "library"}, "options": {"config": {"k1": 1, "k2": 2}}}"#,
+    )?;
+    write("library/b.json", r#"{"options": {"config": {"k3": 3}}}"#)?;
+    write("merge/.optify/config.json", r#"{"overlayMode": "merge"}"#)?;
+    write("merge/a.json", r#"{"options": {"config": {"k2": 20}}}"#)?;
+    write(
+        "replace/.optify/config.json",
+        r#"{"overlayMode": "replace"}"#,
+    )?;
+    write("replace/a.json", r#"{"options": {"config": {"k2": 20}}}"#)?;
+    let directory = |name: &str| temp_dir.path().join(name);
+
+    let provider =
+        OptionsProvider::build_from_directories(&[directory("library"), directory("merge")])?;
+    assert_eq!(
+        provider.get_options("config", &["x"])?,
+        json!({"k1": 1, "k2": 20})
+    );
+    let metadata = provider.get_feature_metadata("a").unwrap();
+    assert_eq!(metadata.owners, Some("library".to_owned()));
+    assert!(metadata.path.unwrap().contains("merge"));
+    let overridden_paths = metadata.overridden_paths.unwrap();
+    assert_eq!(overridden_paths.len(), 1);
+    assert!(overridden_paths[0].contains("library"));
+    // Features that are not overlaid do not have the property when serialized.
+    let metadata = serde_json::to_value(provider.get_feature_metadata("b").unwrap())?;
+    assert!(metadata.get("overridden_paths").is_none());
+
+    let provider =
+        OptionsProvider::build_from_directories(&[directory("library"), directory("replace")])?;
+    assert_eq!(provider.get_options("config", &["a"])?, json!({"k2": 20}));
+    assert_eq!(provider.get_options("config", &["b"])?, json!({"k3": 3}));
+    assert!(matches!(
+        provider.get_canonical_feature_name("x"),
+        Err(OptifyError::UnknownFeature { .. })
+    ));
+
+    // Overlays are chosen by the directory that is added later.
+    match OptionsProvider::build_from_directories(&[directory("merge"), directory("library")]) {
+        Err(OptifyError::DuplicateFeature { name }) => assert_eq!(name, "a"),
+        other => panic!("Expected a duplicate feature error, got: {:?}", other.err()),
+    }
+
+    // Files in the same directory are still duplicates.
+    write("replace/a.yaml", "options:\n  config:\n    k2: 200\n")?;
+    match OptionsProvider::build(directory("replace")) {
+        Err(OptifyError::DuplicateFeature { name }) => assert_eq!(name, "a"),
+        other => panic!("Expected a duplicate feature error, got: {:?}", other.err()),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_builder_nested_directory_configs() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let write = |relative_path: &str, contents: &str| {
+        let path = temp_dir.path().join(relative_path);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, contents)
+    };
+    let configurable_string =
+        r#"{"options": {"greeting": {"$type": "Optify.ConfigurableString", "base": "hello"}}}"#;
+    write(
+        ".optify/config.json",
+        r#"{"areConfigurableValuesEnabled": true}"#,
+    )?;
+    write("inherited/a.json", configurable_string)?;
+    write("rooted/.optify/config.json", r#"{"root": true}"#)?;
+    // Pointers to configurable values are tracked per key across features, so use a different key.
+    write(
+        "rooted/a.json",
+        r#"{"options": {"farewell": {"$type": "Optify.ConfigurableString", "base": "bye"}}}"#,
+    )?;
+    write(
+        "rooted/templated/.optify/config.json",
+        r#"{"areConfigurableValuesEnabled": true}"#,
+    )?;
+    write("rooted/templated/b.json", configurable_string)?;
+    let directory = |name: &str| temp_dir.path().join(name);
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_strings_enabled = true;
+
+    // Configuration files in parent directories apply.
+    let provider = OptionsProvider::build(directory("inherited"))?;
+    assert_eq!(
+        provider.get_options_with_preferences("greeting", &["a"], None, Some(&preferences))?,
+        json!("hello")
+    );
+
+    // The search stops at a configuration file with `"root": true`
+    // and configuration files in subdirectories only apply to their subtree.
+    let provider = OptionsProvider::build(directory("rooted"))?;
+    assert_eq!(
+        provider.get_options_with_preferences("farewell", &["a"], None, Some(&preferences))?,
+        json!({"$type": "Optify.ConfigurableString", "base": "bye"})
+    );
+    assert_eq!(
+        provider.get_options_with_preferences(
+            "greeting",
+            &["templated/b"],
+            None,
+            Some(&preferences)
+        )?,
+        json!("hello")
+    );
+
+    // A schema in a subdirectory's configuration only validates that subtree.
+    write(
+        "rooted/strict/.optify/config.json",
+        r#"{"schemaPath": "schema.json"}"#,
+    )?;
+    write(
+        "rooted/strict/.optify/schema.json",
+        r#"{"type": "object", "required": ["metadata"]}"#,
+    )?;
+    write("rooted/strict/c.json", r#"{"options": {}}"#)?;
+    match OptionsProvider::build(directory("rooted")) {
+        Err(OptifyError::SchemaValidation { file, .. }) => assert!(file.ends_with("c.json")),
+        other => panic!("Expected a schema validation error, got: {:?}", other.err()),
+    }
+    write("rooted/strict/c.json", r#"{"metadata": {}, "options": {}}"#)?;
+    let provider = OptionsProvider::build(directory("rooted"))?;
+    let mut features = provider.get_features();
+    features.sort();
+    assert_eq!(features, vec!["a", "strict/c", "templated/b"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_builder_subdirectory_configs_filter_files() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let write = |relative_path: &str, contents: &str| {
+        let path = temp_dir.path().join(relative_path);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, contents)
+    };
+    let directory = |name: &str| temp_dir.path().join(name);
+
+    // `ignore` patterns are relative to the subdirectory
+    // and `rawFileGlobs` are relative to the directory that is added, like in its configuration.
+    write(
+        "filters/sub/.optify/config.json",
+        r#"{"areConfigurableValuesEnabled": true, "ignore": ["/skip.json"], "rawFileGlobs": ["sub/templates/**"]}"#,
+    )?;
+    write("filters/skip.json", r#"{"options": {}}"#)?;
+    write("filters/sub/skip.json", "not valid JSON")?;
+    write(
+        "filters/sub/a.json",
+        r#"{"options": {
+            "allowed": {"$type": "Optify.ConfigurableString", "base": {"file": "sub/templates/t.liquid"}},
+            "notAllowed": {"$type": "Optify.ConfigurableString", "base": {"file": "sub/notes.txt"}}
+        }}"#,
+    )?;
+    write("filters/sub/templates/t.liquid", "from the template")?;
+    write("filters/sub/notes.txt", "notes")?;
+    let provider = OptionsProvider::build(directory("filters"))?;
+    let mut features = provider.get_features();
+    features.sort();
+    assert_eq!(features, vec!["skip", "sub/a"]);
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_strings_enabled = true;
+    assert_eq!(
+        provider.get_options_with_preferences("allowed", &["sub/a"], None, Some(&preferences))?,
+        json!("from the template")
+    );
+    assert!(provider
+        .get_options_with_preferences("notAllowed", &["sub/a"], None, Some(&preferences))
+        .is_err());
+
+    // A namespace only applies to a directory that is added.
+    write(
+        "namespaced/sub/.optify/config.json",
+        r#"{"namespace": "other"}"#,
+    )?;
+    write("namespaced/sub/a.json", r#"{"options": {}}"#)?;
+    match OptionsProvider::build(directory("namespaced")) {
+        Err(OptifyError::BuilderConfig { path, message }) => {
+            assert!(path.ends_with("config.json"), "{path}");
+            assert!(message.contains("namespace"), "{message}");
+        }
+        other => panic!("Expected a builder config error, got: {:?}", other.err()),
+    }
+
+    // Symbolic links are handled the same way for the whole directory that is added.
+    write(
+        "links/sub/.optify/config.json",
+        r#"{"symlinkPolicy": "skip"}"#,
+    )?;
+    write("links/sub/a.json", r#"{"options": {}}"#)?;
+    match OptionsProvider::build(directory("links")) {
+        Err(OptifyError::BuilderConfig { message, .. }) => {
+            assert!(message.contains("symlinkPolicy"), "{message}")
+        }
+        other => panic!("Expected a builder config error, got: {:?}", other.err()),
+    }
+    let provider = OptionsProvider::build_with_options(
+        directory("links"),
+        BuilderOptions {
+            symlink_policy: SymlinkPolicy::Skip,
+            ..BuilderOptions::default()
+        },
+    )?;
+    assert_eq!(provider.get_features(), vec!["sub/a"]);
+
+    Ok(())
+}
@@ -1,4 +1,8 @@
-use optify::provider::{CacheOptions, GetOptionsPreferences, OptionsProvider, OptionsRegistry};
+use optify::builder::{BuilderOptions, OptionsProviderBuilder, OptionsRegistryBuilder};
+use optify::provider::{
+    CacheOptions, CacheScope, GetOptionsPreferences, OptionsProvider, OptionsRegistry,
+};
+use optify::OptifyError;
 
 fn get_new_provider() -> OptionsProvider {
     let path = std::path::Path::new("../../tests/test_suites/simple/configs");
@@ -13,7 +17,7 @@ fn get_new_configurable_values_provider() -> OptionsProvider {
 #[test]
 fn test_entire_config_cache_hit() -> Result<(), Box<dyn std::error::Error>> {
     let provider = get_new_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     // First call should populate the cache
     let result1 = provider.get_all_options(&["a"], Some(&cache_options), None)?;
@@ -29,7 +33,7 @@ fn test_entire_config_cache_hit() -> Result<(), Box<dyn std::error::Error>> {
 #[test]
 fn test_options_cache_hit() -> Result<(), Box<dyn std::error::Error>> {
     let provider = get_new_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     // First call should populate the cache
     let result1 =
@@ -51,7 +55,7 @@ fn test_options_cache_hit() -> Result<(), Box<dyn std::error::Error>> {
 #[test]
 fn test_cache_different_features() -> Result<(), Box<dyn std::error::Error>> {
     let provider = get_new_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     let result_a =
         provider.get_options_with_preferences("myConfig", &["a"], Some(&cache_options), None)?;
@@ -65,7 +69,7 @@ fn test_cache_different_features() -> Result<(), Box<dyn std::error::Error>> {
 #[test]
 fn test_cache_with_preferences() -> Result<(), Box<dyn std::error::Error>> {
     let provider = get_new_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     let mut preferences1 = GetOptionsPreferences::new();
     preferences1.skip_feature_name_conversion = false;
@@ -84,17 +88,85 @@ fn test_cache_with_preferences() -> Result<(), Box<dyn std::error::Error>> {
 #[test]
 fn test_cache_with_overrides() -> Result<(), Box<dyn std::error::Error>> {
     let provider = get_new_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     let mut preferences = GetOptionsPreferences::new();
-    preferences.overrides = Some(serde_json::json!({"test": "override"}));
+    preferences.overrides = Some(
+        serde_json::json!({"myConfig": {"new key": "override", "myObject": {"one": 10, "two": 20}}}),
+    );
 
-    let result = provider.get_all_options(&["a"], Some(&cache_options), Some(&preferences));
+    let result1 = provider.get_all_options(&["a"], Some(&cache_options), Some(&preferences))?;
+    assert_eq!(result1["myConfig"]["new key"], "override");
+    assert_eq!(result1["myConfig"]["myObject"]["one"], 10);
 
-    assert!(result.is_err());
-    assert!(result
-        .unwrap_err()
-        .contains("Caching when overrides are given is not supported"));
+    // The same overrides with the properties in a different order should hit the cache.
+    let mut reordered_preferences = GetOptionsPreferences::new();
+    reordered_preferences.overrides = Some(serde_json::from_str(
+        r#"{"myConfig": {"myObject": {"two": 20, "one": 10}, "new key": "override"}}"#,
+    )?);
+    let result2 =
+        provider.get_all_options(&["a"], Some(&cache_options), Some(&reordered_preferences))?;
+    assert_eq!(result1, result2);
+
+    let options = provider.get_options_with_preferences(
+        "myConfig",
+        &["a"],
+        Some(&cache_options),
+        Some(&preferences),
+    )?;
+    let cached_options = provider.get_options_from_cache(
+        "myConfig",
+        &["a"],
+        Some(&cache_options),
+        Some(&reordered_preferences),
+    )?;
+    assert_eq!(cached_options, Some(options.clone()));
+    assert_eq!(options, result1["myConfig"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_cache_with_different_overrides() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = get_new_provider();
+    let cache_options = CacheOptions::default();
+
+    let without_overrides =
+        provider.get_options_with_preferences("myConfig", &["a"], Some(&cache_options), None)?;
+
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.overrides = Some(serde_json::json!({"myConfig": {"rootString": "override 1"}}));
+    let result1 = provider.get_options_with_preferences(
+        "myConfig",
+        &["a"],
+        Some(&cache_options),
+        Some(&preferences),
+    )?;
+
+    preferences.overrides = Some(serde_json::json!({"myConfig": {"rootString": "override 2"}}));
+    let result2 = provider.get_options_with_preferences(
+        "myConfig",
+        &["a"],
+        Some(&cache_options),
+        Some(&preferences),
+    )?;
+
+    assert_eq!(without_overrides["rootString"], "root string same");
+    assert_eq!(result1["rootString"], "override 1");
+    assert_eq!(result2["rootString"], "override 2");
+
+    let all_options_without_overrides =
+        provider.get_all_options(&["a"], Some(&cache_options), None)?;
+    let all_options_with_overrides =
+        provider.get_all_options(&["a"], Some(&cache_options), Some(&preferences))?;
+    assert_eq!(
+        all_options_without_overrides["myConfig"]["rootString"],
+        "root string same"
+    );
+    assert_eq!(
+        all_options_with_overrides["myConfig"]["rootString"],
+        "override 2"
+    );
 
     Ok(())
 }
@@ -102,7 +174,7 @@ fn test_cache_with_overrides() -> Result<(), Box<dyn std::error::Error>> {
 #[test]
 fn test_cache_feature_name_conversion() -> Result<(), Box<dyn std::error::Error>> {
     let provider = get_new_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     // Test that different string types work with cache
     let feature_names_str: Vec<&str> = vec!["a"];
@@ -129,7 +201,7 @@ fn test_cache_feature_name_conversion() -> Result<(), Box<dyn std::error::Error>
 #[test]
 fn test_cache_multiple_features() -> Result<(), Box<dyn std::error::Error>> {
     let provider = get_new_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     // Test caching with multiple features
     let result1 =
@@ -152,7 +224,7 @@ fn test_cache_multiple_features() -> Result<(), Box<dyn std::error::Error>> {
 #[test]
 fn test_cache_multiple_features_get_options() -> Result<(), Box<dyn std::error::Error>> {
     let provider = get_new_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     // Test caching with multiple features
     let result1 = provider.get_options_with_preferences(
@@ -185,7 +257,7 @@ fn test_cache_multiple_features_get_options() -> Result<(), Box<dyn std::error::
 fn test_cache_multiple_features_get_options_with_preferences(
 ) -> Result<(), Box<dyn std::error::Error>> {
     let provider = get_new_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
     let mut preferences = GetOptionsPreferences::new();
     preferences.skip_feature_name_conversion = true;
     let feature_names = &["feature_A", "feature_B/initial"];
@@ -224,7 +296,7 @@ fn test_cache_multiple_features_get_options_with_preferences(
 #[test]
 fn test_cache_empty_features() -> Result<(), Box<dyn std::error::Error>> {
     let provider = get_new_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     let empty_features: Vec<&str> = vec![];
     let result1 = provider.get_all_options(&empty_features, Some(&cache_options), None)?;
@@ -238,7 +310,7 @@ fn test_cache_empty_features() -> Result<(), Box<dyn std::error::Error>> {
 #[test]
 fn test_cache_get_all_options_configurable_strings() -> Result<(), Box<dyn std::error::Error>> {
     let provider = get_new_configurable_values_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
     let feature_names = &["simple"];
 
     // Test with are_configurable_strings_enabled = false (default)
@@ -282,7 +354,7 @@ fn test_cache_get_all_options_configurable_strings() -> Result<(), Box<dyn std::
 fn test_cache_configurable_strings_false_equals_no_preferences(
 ) -> Result<(), Box<dyn std::error::Error>> {
     let provider = get_new_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
     // Test with are_configurable_strings_enabled = false (default)
     let mut preferences_false = GetOptionsPreferences::new();
     preferences_false.are_configurable_strings_enabled = false;
@@ -320,7 +392,7 @@ fn test_cache_configurable_strings_false_equals_no_preferences(
 #[test]
 fn test_cache_get_options_configurable_strings_enabled() -> Result<(), Box<dyn std::error::Error>> {
     let provider = get_new_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     // First call with are_configurable_strings_enabled = false
     let mut preferences_false = GetOptionsPreferences::new();
@@ -376,7 +448,7 @@ fn test_cache_get_options_configurable_strings_enabled() -> Result<(), Box<dyn s
 #[test]
 fn test_cache_constraints() -> Result<(), Box<dyn std::error::Error>> {
     let provider = get_new_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     // Test with constraints
     let mut preferences1 = GetOptionsPreferences::new();
@@ -393,10 +465,68 @@ fn test_cache_constraints() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn test_cache_constraints_read_by_templates() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_options(BuilderOptions {
+        are_configurable_strings_enabled: true,
+        ..BuilderOptions::default()
+    })?;
+    builder.add_feature(
+        "a",
+        serde_json::json!({"options": {"greeting": {
+            "$type": "Optify.ConfigurableString",
+            "base": {"liquid": "Hello in {{ constraints.locale }}"},
+        }}}),
+    )?;
+    let provider = builder.build()?;
+    let cache_options = CacheOptions::default();
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_values_enabled = true;
+
+    preferences.set_constraints_json(Some(r#"{"locale": "en", "tenant": 1}"#));
+    let result_en = provider.get_options_with_preferences(
+        "greeting",
+        &["a"],
+        Some(&cache_options),
+        Some(&preferences),
+    )?;
+    assert_eq!(result_en, serde_json::json!("Hello in en"));
+
+    // Constraints that templates read are part of the cache key.
+    preferences.set_constraints_json(Some(r#"{"locale": "fr", "tenant": 1}"#));
+    assert!(provider
+        .get_options_from_cache("greeting", &["a"], Some(&cache_options), Some(&preferences))?
+        .is_none());
+    assert_eq!(
+        provider.get_options_with_preferences(
+            "greeting",
+            &["a"],
+            Some(&cache_options),
+            Some(&preferences),
+        )?,
+        serde_json::json!("Hello in fr")
+    );
+
+    // Other constraints do not change the result, so it is shared.
+    preferences.set_constraints_json(Some(r#"{"locale": "en", "tenant": 2}"#));
+    assert_eq!(
+        provider.get_options_from_cache(
+            "greeting",
+            &["a"],
+            Some(&cache_options),
+            Some(&preferences)
+        )?,
+        Some(result_en)
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_cache_get_options_with_invalid_feature() {
     let provider = get_new_provider();
-    let cache_options = CacheOptions {};
+    let cache_options = CacheOptions::default();
 
     let result = provider.get_options_with_preferences(
         "myConfig",
@@ -406,8 +536,87 @@ fn test_cache_get_options_with_invalid_feature() {
     );
 
     assert!(result.is_err());
+    let error = result.unwrap_err();
     assert_eq!(
-        result.unwrap_err(),
+        error.to_string(),
         "Feature name \"invalid_feature\" is not a known feature."
     );
+    assert_eq!(
+        error,
+        OptifyError::UnknownFeature {
+            name: "invalid_feature".to_owned()
+        }
+    );
+}
+
+#[test]
+fn test_cache_max_entries() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = get_new_provider();
+    let cache_options = CacheOptions {
+        max_entries: Some(1),
+        ..Default::default()
+    };
+
+    provider.get_options_with_preferences("myConfig", &["a"], Some(&cache_options), None)?;
+    provider.get_options_with_preferences("myConfig", &["b"], Some(&cache_options), None)?;
+
+    let evicted =
+        provider.get_options_from_cache("myConfig", &["a"], Some(&cache_options), None)?;
+    assert!(evicted.is_none());
+    let cached = provider.get_options_from_cache("myConfig", &["b"], Some(&cache_options), None)?;
+    assert!(cached.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn test_cache_time_to_live() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = get_new_provider();
+    let cache_options = CacheOptions {
+        time_to_live: Some(std::time::Duration::ZERO),
+        ..Default::default()
+    };
+
+    provider.get_options_with_preferences("myConfig", &["a"], Some(&cache_options), None)?;
+
+    let expired =
+        provider.get_options_from_cache("myConfig", &["a"], Some(&cache_options), None)?;
+    assert!(expired.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_cache_scope_entire_config_skips_options() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = get_new_provider();
+    let cache_options = CacheOptions {
+        scope: CacheScope::EntireConfig,
+        ..Default::default()
+    };
+
+    provider.get_options_with_preferences("myConfig", &["a"], Some(&cache_options), None)?;
+
+    let cached = provider.get_options_from_cache(
+        "myConfig",
+        &["a"],
+        Some(&CacheOptions::default()),
+        None,
+    )?;
+    assert!(cached.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_clear_cache() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = get_new_provider();
+    let cache_options = CacheOptions::default();
+
+    provider.get_options_with_preferences("myConfig", &["a"], Some(&cache_options), None)?;
+    provider.clear_cache();
+
+    let cached = provider.get_options_from_cache("myConfig", &["a"], Some(&cache_options), None)?;
+    assert!(cached.is_none());
+
+    Ok(())
 }
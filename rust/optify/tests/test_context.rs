@@ -0,0 +1,96 @@
+use std::fs::File;
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+use optify::provider::{
+    GetOptionsPreferences, OptionsProvider, OptionsRegistry, OptionsWatcher, WatcherOptions,
+};
+use optify::OptifyError;
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Config {
+    key: String,
+}
+
+#[test]
+fn test_context_filters_features_once() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = OptionsProvider::build("../../tests/test_suites/conditions/configs")?;
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.set_constraints(Some(json!({"info": 3, "status": "new"})));
+    let features = ["B", "A", "C"];
+
+    let context = provider.context(&features, Some(&preferences))?;
+    assert_eq!(context.feature_names(), ["B", "A"]);
+    assert_eq!(
+        context.get("config")?,
+        provider.get_options_with_preferences("config", &features, None, Some(&preferences))?
+    );
+    assert_eq!(context.get("/config/key")?, json!("from A"));
+    assert_eq!(
+        context.get_all()?,
+        provider.get_all_options(&features, None, Some(&preferences))?
+    );
+    let config: Config = context.get_as("config")?;
+    assert_eq!(
+        config,
+        Config {
+            key: "from A".to_owned()
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_context_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = OptionsProvider::build("../../tests/test_suites/simple/configs")?;
+
+    let result = provider.context(&["does not exist"], None);
+    assert!(matches!(result, Err(OptifyError::UnknownFeature { .. })));
+
+    let context = provider.context(&["a"], None)?;
+    match context.get("missing") {
+        Err(OptifyError::KeyNotFound { key, features }) => {
+            assert_eq!(key, "missing");
+            assert_eq!(features, vec!["a".to_owned()]);
+        }
+        other => panic!("Expected a KeyNotFound error, got: {other:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_watcher_context_keeps_configuration_after_reload() -> Result<(), Box<dyn std::error::Error>>
+{
+    let temp_dir = tempfile::tempdir()?;
+    let test_dir = temp_dir.path();
+    let options_file = test_dir.join("feature.json");
+    File::create(&options_file)?.write_all(b"{\"options\":{\"test\":42}}")?;
+
+    let watcher = OptionsWatcher::build_with_options(
+        test_dir,
+        WatcherOptions::new(Duration::from_millis(10)),
+    )?;
+    let created_at = watcher.last_modified();
+    let context = watcher.context(&["feature"], None)?;
+
+    File::create(&options_file)?.write_all(b"{\"options\":{\"test\":43}}")?;
+    let start_time = std::time::Instant::now();
+    let max_sleep_time = 3000;
+    while watcher.last_modified() == created_at {
+        thread::sleep(Duration::from_millis(50));
+        if start_time.elapsed().as_millis() > max_sleep_time {
+            panic!("Watcher did not update after {max_sleep_time}ms.");
+        }
+    }
+
+    assert_eq!(watcher.get_options("test", &["feature"])?, json!(43));
+    assert_eq!(context.get("test")?, json!(42));
+    assert_eq!(watcher.context(&["feature"], None)?.get("test")?, json!(43));
+
+    Ok(())
+}
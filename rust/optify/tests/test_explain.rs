@@ -0,0 +1,184 @@
+use optify::builder::{OptionsProviderBuilder, OptionsRegistryBuilder};
+use optify::provider::{
+    ExplainedValue, GetOptionsPreferences, OptionsProvider, OptionsRegistry, ShadowedValue,
+    ValueSource,
+};
+use optify::OptifyError;
+use serde_json::json;
+
+fn source(feature: &str, imported_by: &[&str]) -> ValueSource {
+    ValueSource {
+        environment_variable: None,
+        feature: Some(feature.to_owned()),
+        imported_by: imported_by.iter().map(|f| f.to_string()).collect(),
+    }
+}
+
+fn find<'a>(explanation: &'a [ExplainedValue], pointer: &str) -> &'a ExplainedValue {
+    explanation
+        .iter()
+        .find(|value| value.pointer == pointer)
+        .unwrap_or_else(|| panic!("Expected an explanation for {pointer:?}"))
+}
+
+#[test]
+fn test_explain_options() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = OptionsProvider::build("../../tests/test_suites/simple/configs")?;
+    let explanation = provider.explain_options("myConfig", &["A", "B"], None)?;
+
+    let root_string2 = find(&explanation, "/rootString2");
+    assert_eq!(root_string2.value, json!("override"));
+    assert_eq!(root_string2.source, source("feature_B/initial", &[]));
+    assert_eq!(
+        root_string2.shadowed,
+        vec![ShadowedValue {
+            pointer: "/rootString2".to_owned(),
+            source: source("feature_A", &[]),
+            value: json!("gets overridden"),
+        }]
+    );
+
+    let one = find(&explanation, "/myObject/one");
+    assert_eq!(one.value, json!(1));
+    assert_eq!(one.source, source("feature_A", &[]));
+    assert!(one.shadowed.is_empty());
+
+    // The leaves match the merged options.
+    let options = provider.get_options("myConfig", &["A", "B"])?;
+    for value in &explanation {
+        assert_eq!(options.pointer(&value.pointer), Some(&value.value));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_explain_options_with_imports() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = OptionsProvider::build("../../tests/test_suites/inheritance/configs")?;
+    let explanation = provider.explain_options("myConfig", &["grandparent"], None)?;
+
+    assert_eq!(
+        explanation,
+        vec![
+            ExplainedValue {
+                pointer: "/key1".to_owned(),
+                shadowed: vec![],
+                source: source("base1", &["grandparent", "parent1"]),
+                value: json!("value"),
+            },
+            ExplainedValue {
+                pointer: "/key2".to_owned(),
+                shadowed: vec![ShadowedValue {
+                    pointer: "/key2".to_owned(),
+                    source: source("parent1", &["grandparent"]),
+                    value: json!("parent1 value"),
+                }],
+                source: source("base2", &["grandparent", "parent2"]),
+                value: json!("from base2"),
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_explain_options_with_pointer_and_overrides() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = OptionsProvider::build("../../tests/test_suites/simple/configs")?;
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.overrides = Some(json!({"myConfig": {"myObject": {"deeper": {"wtv": 5}}}}));
+    let explanation =
+        provider.explain_options("/myConfig/myObject/deeper", &["A", "B"], Some(&preferences))?;
+
+    let wtv = find(&explanation, "/wtv");
+    assert_eq!(wtv.value, json!(5));
+    assert_eq!(
+        wtv.source,
+        ValueSource {
+            environment_variable: None,
+            feature: None,
+            imported_by: vec![],
+        }
+    );
+    assert_eq!(
+        wtv.shadowed
+            .iter()
+            .map(|shadowed| shadowed.value.clone())
+            .collect::<Vec<_>>(),
+        vec![json!(3333), json!(3)]
+    );
+
+    let list = provider.explain_options("/myConfig/myObject/deeper/list/0", &["A", "B"], None)?;
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].pointer, "");
+    assert_eq!(list[0].value, json!(55));
+    assert_eq!(list[0].shadowed[0].value, json!(1));
+
+    Ok(())
+}
+
+#[test]
+fn test_explain_options_key_not_found() {
+    let provider = OptionsProvider::build("../../tests/test_suites/simple/configs").unwrap();
+    let result = provider.explain_options("missing", &["A"], None);
+    assert!(matches!(result, Err(OptifyError::KeyNotFound { .. })));
+}
+
+#[test]
+fn test_explain_options_with_merge_directives() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = OptionsProviderBuilder::new();
+    builder.add_feature(
+        "base",
+        json!({"options": {"config": {
+            "list": [1, 2],
+            "object": {"a": 1},
+            "removed": "base value",
+        }}}),
+    )?;
+    builder.add_feature(
+        "child",
+        json!({"imports": ["base"], "options": {"config": {
+            "list": {"$append": [3]},
+            "object": {"$merge": "replace", "c": 3},
+            "removed": {"$delete": true},
+        }}}),
+    )?;
+    builder.add_feature(
+        "extra",
+        json!({"options": {"config": {"list": {"$prepend": [0]}}}}),
+    )?;
+    let provider = builder.build()?;
+    let features = ["child", "extra"];
+    let explanation = provider.explain_options("config", &features, None)?;
+
+    let list = find(&explanation, "/list");
+    assert_eq!(list.value, json!([0, 1, 2, 3]));
+    assert_eq!(list.source, source("extra", &[]));
+    assert_eq!(
+        list.shadowed
+            .iter()
+            .map(|shadowed| shadowed.value.clone())
+            .collect::<Vec<_>>(),
+        vec![json!([1, 2, 3]), json!([1, 2])]
+    );
+
+    let c = find(&explanation, "/object/c");
+    assert_eq!(c.source, source("child", &[]));
+    assert_eq!(
+        c.shadowed,
+        vec![ShadowedValue {
+            pointer: "/object/a".to_owned(),
+            source: source("base", &["child"]),
+            value: json!(1),
+        }]
+    );
+
+    // The leaves match the merged options.
+    let options = provider.get_options("config", &features)?;
+    assert_eq!(explanation.len(), 2);
+    for value in &explanation {
+        assert_eq!(options.pointer(&value.pointer), Some(&value.value));
+    }
+
+    Ok(())
+}
@@ -1,8 +1,18 @@
+use liquid_core::{
+    Display_filter, Filter, FilterReflection, Language, ParseFilter, ParseTag, Renderable, Runtime,
+    TagReflection, TagTokenIter, Value, ValueView,
+};
 use optify::{
-    builder::{OptionsProviderBuilder, OptionsRegistryBuilder},
-    provider::{GetOptionsPreferences, OptionsProvider, OptionsRegistry},
+    builder::{BuilderOptions, OptionsProviderBuilder, OptionsRegistryBuilder},
+    configurable_values::{ConfigurableValueContext, ConfigurableValueType},
+    provider::{CacheOptions, GetOptionsPreferences, OptionsProvider, OptionsRegistry},
+    secret::{EnvSecretResolver, FileSecretResolver, REDACTED_SECRET},
+    OptifyError,
+};
+use std::{
+    fs,
+    sync::{Arc, OnceLock},
 };
-use std::{fs, sync::OnceLock};
 
 static CONDITIONS_PROVIDER: OnceLock<OptionsProvider> = OnceLock::new();
 static CONFIGURABLE_STRINGS_PROVIDER: OnceLock<OptionsProvider> = OnceLock::new();
@@ -142,7 +152,16 @@ fn test_provider_get_options_missing_key() -> Result<(), Box<dyn std::error::Err
     let provider = get_provider();
     let opts = provider.get_options(key, &feature_names);
     assert!(opts.is_err());
-    assert_eq!(opts.unwrap_err(), "Error getting options with features [\"a\"]: configuration property \"does not exist\" not found");
+    let error = opts.unwrap_err();
+    assert_eq!(error.to_string(), "Error getting options with features [\"a\"]: configuration property \"does not exist\" not found");
+    assert_eq!(
+        error,
+        OptifyError::KeyNotFound {
+            key: key.to_owned(),
+            features: vec!["a".to_owned()],
+        }
+    );
+    assert_eq!(error.code(), "key_not_found");
 
     let mut preferences = GetOptionsPreferences::new();
     preferences.overrides = Some(serde_json::json!({
@@ -163,7 +182,7 @@ fn test_provider_get_options_no_features() -> Result<(), Box<dyn std::error::Err
     let opts = provider.get_options(key, &feature_names);
     assert!(opts.is_err());
     assert_eq!(
-        opts.unwrap_err(),
+        opts.unwrap_err().to_string(),
         "Error getting options with features []: configuration property \"wtv\" not found"
     );
 
@@ -477,3 +496,908 @@ fn test_configurable_values_get_all_options_with_overrides(
 
     Ok(())
 }
+
+#[test]
+fn test_provider_get_options_with_json_pointer() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = get_provider();
+    let features = ["A", "b"];
+    let entire_options = provider.get_options("myConfig", &features)?;
+
+    for pointer in [
+        "/myConfig",
+        "/myConfig/rootString",
+        "/myConfig/myArray",
+        "/myConfig/myArray/1",
+        "/myConfig/myObject",
+        "/myConfig/myObject/one",
+        "/myConfig/myObject/two",
+        "/myConfig/myObject/deeper/list",
+        "/myConfig/myObject/deeper/new",
+    ] {
+        let expected = entire_options
+            .pointer(&pointer["/myConfig".len()..])
+            .unwrap();
+        let options = provider.get_options(pointer, &features)?;
+        assert_eq!(&options, expected, "pointer: {pointer}");
+    }
+
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.overrides = Some(serde_json::json!({"myConfig": {"myObject": {"one": 11}}}));
+    let options = provider.get_options_with_preferences(
+        "/myConfig/myObject/one",
+        &features,
+        None,
+        Some(&preferences),
+    )?;
+    assert_eq!(options, serde_json::json!(11));
+
+    let error = provider
+        .get_options("/myConfig/myObject/missing", &features)
+        .unwrap_err();
+    assert_eq!(
+        error,
+        OptifyError::KeyNotFound {
+            key: "/myConfig/myObject/missing".to_owned(),
+            features: vec!["A".to_owned(), "b".to_owned()],
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_provider_get_options_with_json_pointer_and_configurable_values(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let provider = get_configurable_values_provider();
+    let features = ["AI_docs_example"];
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_values_enabled = true;
+    let entire_options =
+        provider.get_options_with_preferences("chat", &features, None, Some(&preferences))?;
+
+    for pointer in [
+        "/chat/system_instructions",
+        "/chat/tools",
+        // Inside of a configurable list.
+        "/chat/tools/0",
+        "/chat/tools/1/description",
+    ] {
+        let expected = entire_options.pointer(&pointer["/chat".len()..]).unwrap();
+        let options =
+            provider.get_options_with_preferences(pointer, &features, None, Some(&preferences))?;
+        assert_eq!(&options, expected, "pointer: {pointer}");
+    }
+
+    // Without configurable values enabled, the raw definitions are returned.
+    let options = provider.get_options("/chat/tools/$type", &features)?;
+    assert_eq!(options, "Optify.ConfigurableList");
+
+    Ok(())
+}
+
+#[test]
+fn test_provider_get_options_batch() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = get_provider();
+    let features = ["A", "b"];
+    let keys = ["myConfig", "/myConfig/myObject/two"];
+    let cache_options = CacheOptions::default();
+
+    for cache_options in [None, Some(&cache_options)] {
+        let batch = provider.get_options_batch(&keys, &features, cache_options, None)?;
+        assert_eq!(batch.len(), 2);
+        for key in keys {
+            assert_eq!(
+                batch[key],
+                provider.get_options(key, &features)?,
+                "key: {key}"
+            );
+        }
+    }
+
+    let error = provider
+        .get_options_batch(&["myConfig", "missing"], &features, None, None)
+        .unwrap_err();
+    assert_eq!(
+        error,
+        OptifyError::KeyNotFound {
+            key: "missing".to_owned(),
+            features: vec!["A".to_owned(), "b".to_owned()],
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_provider_env_overrides() -> Result<(), Box<dyn std::error::Error>> {
+    // Use a prefix that is unique to this test because environment variables are shared by all tests in the process.
+    let prefix = "OPTIFY_TEST_ENV_OVERRIDES";
+    std::env::set_var(format!("{prefix}__myConfig__myObject__one"), "5");
+    std::env::set_var(
+        format!("{prefix}__myConfig__rootString"),
+        "from the environment",
+    );
+    let path = "../../tests/test_suites/simple/configs";
+    let options = BuilderOptions {
+        env_overrides_prefix: Some(prefix.to_owned()),
+        ..BuilderOptions::default()
+    };
+    let provider = OptionsProvider::build_with_options(path, options)?;
+
+    let features = ["A", "b"];
+    let my_object = provider.get_options("/myConfig/myObject", &features)?;
+    assert_eq!(my_object["one"], 5);
+    assert_eq!(my_object["two"], 22);
+    let config = provider.get_all_options(&features, None, None)?;
+    assert_eq!(config["myConfig"]["rootString"], "from the environment");
+    assert_eq!(config["myConfig"]["myObject"]["one"], 5);
+
+    // Environment variables win over the overrides in the preferences.
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.overrides =
+        Some(serde_json::json!({"myConfig": {"myObject": {"one": 6, "two": 7}}}));
+    let my_object =
+        provider.get_options_with_preferences("myConfig", &features, None, Some(&preferences))?
+            ["myObject"]
+            .clone();
+    assert_eq!(my_object["one"], 5);
+    assert_eq!(my_object["two"], 7);
+
+    let env_overrides = provider.get_env_overrides();
+    assert_eq!(env_overrides.len(), 2);
+    assert_eq!(
+        env_overrides[0].variable,
+        format!("{prefix}__myConfig__myObject__one")
+    );
+    assert_eq!(env_overrides[0].pointer, "/myConfig/myObject/one");
+    assert_eq!(env_overrides[0].value, 5);
+    assert_eq!(env_overrides[1].pointer, "/myConfig/rootString");
+
+    let explanation = provider.explain_options("/myConfig/myObject", &features, None)?;
+    let one = explanation.iter().find(|e| e.pointer == "/one").unwrap();
+    assert_eq!(
+        one.source.environment_variable,
+        Some(format!("{prefix}__myConfig__myObject__one"))
+    );
+    assert_eq!(one.shadowed.len(), 1);
+
+    // Environment variables are only read when a prefix is set.
+    let provider = OptionsProvider::build(path)?;
+    assert!(provider.get_env_overrides().is_empty());
+    assert_eq!(
+        provider.get_options("/myConfig/myObject/one", &features)?,
+        1
+    );
+
+    let prefix = "OPTIFY_TEST_INVALID_ENV_OVERRIDES";
+    std::env::set_var(format!("{prefix}__myConfig____one"), "5");
+    let options = BuilderOptions {
+        env_overrides_prefix: Some(prefix.to_owned()),
+        ..BuilderOptions::default()
+    };
+    match OptionsProvider::build_with_options(path, options) {
+        Err(OptifyError::InvalidEnvironmentVariable { name, .. }) => {
+            assert_eq!(name, format!("{prefix}__myConfig____one"))
+        }
+        other => panic!(
+            "Expected an invalid environment variable error, got: {:?}",
+            other.err()
+        ),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_provider_secrets() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let configs_dir = temp_dir.path().join("configs");
+    fs::create_dir_all(configs_dir.join(".optify"))?;
+    fs::write(
+        configs_dir.join(".optify/config.json"),
+        r#"{"areConfigurableValuesEnabled": true}"#,
+    )?;
+    fs::write(
+        configs_dir.join("feature.json"),
+        r#"{
+            "options": {
+                "database": {
+                    "host": "db.example.com",
+                    "password": {"$type": "Optify.Secret", "resolver": "env", "name": "OPTIFY_TEST_SECRET_PASSWORD"},
+                    "apiKey": {"$type": "Optify.Secret", "resolver": "file", "name": "api_key"}
+                }
+            }
+        }"#,
+    )?;
+    let secrets_dir = temp_dir.path().join("secrets");
+    fs::create_dir_all(&secrets_dir)?;
+    fs::write(secrets_dir.join("api_key"), "key from file\n")?;
+    std::env::set_var("OPTIFY_TEST_SECRET_PASSWORD", "password 1");
+
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_secret_resolver("env", Arc::new(EnvSecretResolver))?;
+    builder.with_secret_resolver("file", Arc::new(FileSecretResolver::new(&secrets_dir)))?;
+    builder.add_directory(&configs_dir)?;
+    let provider = builder.build()?;
+
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_values_enabled = true;
+    let cache_options = CacheOptions::default();
+    let database = provider.get_options_with_preferences(
+        "database",
+        &["feature"],
+        Some(&cache_options),
+        Some(&preferences),
+    )?;
+    assert_eq!(database["host"], "db.example.com");
+    assert_eq!(database["password"], "password 1");
+    assert_eq!(database["apiKey"], "key from file");
+
+    // Options with secrets are not cached by default, so changes to secrets are used.
+    std::env::set_var("OPTIFY_TEST_SECRET_PASSWORD", "password 2");
+    let database = provider.get_options_with_preferences(
+        "database",
+        &["feature"],
+        Some(&cache_options),
+        Some(&preferences),
+    )?;
+    assert_eq!(database["password"], "password 2");
+
+    let cache_options = CacheOptions {
+        are_secrets_cached: true,
+        ..CacheOptions::default()
+    };
+    provider.get_options_with_preferences(
+        "database",
+        &["feature"],
+        Some(&cache_options),
+        Some(&preferences),
+    )?;
+    std::env::set_var("OPTIFY_TEST_SECRET_PASSWORD", "password 3");
+    let database = provider.get_options_with_preferences(
+        "database",
+        &["feature"],
+        Some(&cache_options),
+        Some(&preferences),
+    )?;
+    assert_eq!(database["password"], "password 2");
+
+    preferences.redact_secrets = true;
+    let config = provider.get_all_options(&["feature"], None, Some(&preferences))?;
+    assert_eq!(config["database"]["password"], REDACTED_SECRET);
+    assert_eq!(config["database"]["apiKey"], REDACTED_SECRET);
+    assert_eq!(config["database"]["host"], "db.example.com");
+
+    // Secrets are left as they are when configurable values are not enabled for the request.
+    let database = provider.get_options("database", &["feature"])?;
+    assert_eq!(database["password"]["$type"], "Optify.Secret");
+
+    // Resolvers are not saved in snapshots, so they are given when loading.
+    let bytes = provider.to_snapshot_bytes()?;
+    assert!(matches!(
+        OptionsProvider::from_snapshot_bytes(&bytes),
+        Err(OptifyError::Snapshot { .. })
+    ));
+    let mut secret_resolvers = optify::secret::SecretResolvers::new();
+    secret_resolvers.insert("env".to_owned(), Arc::new(EnvSecretResolver));
+    secret_resolvers.insert(
+        "file".to_owned(),
+        Arc::new(FileSecretResolver::new(&secrets_dir)),
+    );
+    let loaded = OptionsProvider::from_snapshot_bytes_with_options(
+        &bytes,
+        BuilderOptions {
+            secret_resolvers,
+            ..BuilderOptions::default()
+        },
+    )?;
+    preferences.redact_secrets = false;
+    let database =
+        loaded.get_options_with_preferences("database", &["feature"], None, Some(&preferences))?;
+    assert_eq!(database["password"], "password 3");
+    assert_eq!(database["apiKey"], "key from file");
+
+    // A secret that uses a resolver that was not registered is an error.
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_secret_resolver("env", Arc::new(EnvSecretResolver))?;
+    builder.add_directory(&configs_dir)?;
+    let provider = builder.build()?;
+    preferences.redact_secrets = false;
+    match provider.get_options_with_preferences("database", &["feature"], None, Some(&preferences))
+    {
+        Err(OptifyError::ConfigurableValue { pointer, message }) => {
+            // The pointer is from the top of the options, not relative to the key.
+            assert_eq!(pointer, "/database/apiKey");
+            assert!(message.contains("file"), "Unexpected message: {message}")
+        }
+        other => panic!("Expected a configurable value error, got: {other:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_provider_merge_directives() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = OptionsProviderBuilder::new();
+    builder.add_feature(
+        "base",
+        serde_json::json!({"options": {"config": {
+            "list": [1, 2],
+            "object": {"a": 1, "b": 2},
+            "removed": "base value",
+            "kept": true,
+        }}}),
+    )?;
+    builder.add_feature(
+        "child",
+        serde_json::json!({"imports": ["base"], "options": {"config": {
+            "list": {"$append": [3]},
+            "object": {"$merge": "replace", "c": 3},
+            "removed": {"$delete": true},
+        }}}),
+    )?;
+    builder.add_feature(
+        "extra",
+        serde_json::json!({"options": {"config": {
+            "list": {"$prepend": [0]},
+            "object": {"d": 4},
+        }}}),
+    )?;
+    let provider = builder.build()?;
+
+    // Directives are applied to the imports.
+    let expected = serde_json::json!({
+        "list": [1, 2, 3],
+        "object": {"c": 3},
+        "kept": true,
+    });
+    assert_eq!(provider.get_options("config", &["child"])?, expected);
+    assert_eq!(
+        provider.get_all_options(&["child"], None, None)?,
+        serde_json::json!({"config": expected})
+    );
+
+    // Directives in later features are applied to the options from earlier features.
+    let features = ["child", "extra"];
+    let expected = serde_json::json!({
+        "list": [0, 1, 2, 3],
+        "object": {"c": 3, "d": 4},
+        "kept": true,
+    });
+    assert_eq!(provider.get_options("config", &features)?, expected);
+    assert_eq!(
+        provider.get_all_options(&features, None, None)?,
+        serde_json::json!({"config": expected})
+    );
+    assert_eq!(
+        provider.get_options("/config/list/0", &features)?,
+        serde_json::json!(0)
+    );
+    assert_eq!(
+        provider.get_options("/config/object", &features)?,
+        serde_json::json!({"c": 3, "d": 4})
+    );
+    match provider.get_options("/config/removed", &features) {
+        Err(OptifyError::KeyNotFound { key, .. }) => assert_eq!(key, "/config/removed"),
+        other => panic!("Expected a key not found error, got: {other:?}"),
+    }
+
+    // Without a lower-priority array, the items are used as the array.
+    assert_eq!(
+        provider.get_options("/config/list", &["extra"])?,
+        serde_json::json!([0])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_provider_merge_directives_in_overrides() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = OptionsProviderBuilder::new();
+    builder.add_feature(
+        "base",
+        serde_json::json!({"options": {"config": {
+            "list": [1, 2],
+            "removed": "base value",
+            "kept": true,
+        }}}),
+    )?;
+    let provider = builder.build()?;
+
+    // The features do not have directives, so they are only in the overrides.
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.overrides = Some(serde_json::json!({"config": {
+        "list": {"$append": [3]},
+        "removed": {"$delete": true},
+    }}));
+    let expected = serde_json::json!({"list": [1, 2, 3], "kept": true});
+    assert_eq!(
+        provider.get_options_with_preferences("config", &["base"], None, Some(&preferences))?,
+        expected
+    );
+    assert_eq!(
+        provider.get_all_options(&["base"], None, Some(&preferences))?,
+        serde_json::json!({"config": expected})
+    );
+    assert_eq!(
+        provider.get_options_with_preferences(
+            "/config/list",
+            &["base"],
+            None,
+            Some(&preferences)
+        )?,
+        serde_json::json!([1, 2, 3])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_provider_references() -> Result<(), Box<dyn std::error::Error>> {
+    let options = BuilderOptions {
+        are_configurable_strings_enabled: true,
+        ..BuilderOptions::default()
+    };
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_options(options.clone())?;
+    builder.add_feature(
+        "base",
+        serde_json::json!({"options": {
+            "endpoints": {"api": "https://api.example.com"},
+            "service": {
+                "url": {"$type": "Optify.Reference", "pointer": "/endpoints/api"},
+                "backupUrl": {"$type": "Optify.Reference", "pointer": "/service/url"},
+                "endpoints": {"$type": "Optify.Reference", "pointer": "/endpoints"},
+            },
+        }}),
+    )?;
+    builder.add_feature(
+        "prod",
+        serde_json::json!({"options": {"endpoints": {"api": "https://prod.example.com"}}}),
+    )?;
+    let provider = builder.build()?;
+
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_values_enabled = true;
+    // References are resolved after the features are merged.
+    let features = ["base", "prod"];
+    let expected = serde_json::json!({
+        "url": "https://prod.example.com",
+        "backupUrl": "https://prod.example.com",
+        "endpoints": {"api": "https://prod.example.com"},
+    });
+    assert_eq!(
+        provider.get_options_with_preferences("service", &features, None, Some(&preferences))?,
+        expected
+    );
+    assert_eq!(
+        provider.get_all_options(&features, None, Some(&preferences))?["service"],
+        expected
+    );
+    assert_eq!(
+        provider.get_options_with_preferences(
+            "/service/backupUrl",
+            &["base"],
+            None,
+            Some(&preferences)
+        )?,
+        serde_json::json!("https://api.example.com")
+    );
+
+    // Overrides can change references to create cycles, which are reported when getting options.
+    preferences.overrides = Some(serde_json::json!({"service": {
+        "url": {"$type": "Optify.Reference", "pointer": "/service/backupUrl"},
+    }}));
+    match provider.get_options_with_preferences("/service/url", &features, None, Some(&preferences))
+    {
+        Err(OptifyError::ReferenceCycle { pointers }) => assert_eq!(
+            pointers,
+            vec!["/service/url", "/service/backupUrl", "/service/url"]
+        ),
+        other => panic!("Expected a reference cycle error, got: {other:?}"),
+    }
+
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_options(options.clone())?;
+    builder.add_feature(
+        "dangling",
+        serde_json::json!({"options": {"service": {
+            "url": {"$type": "Optify.Reference", "pointer": "/endpoints/missing"},
+        }}}),
+    )?;
+    match builder.build() {
+        Err(e @ OptifyError::DanglingReference { .. }) => assert_eq!(
+            e.to_string(),
+            "The reference at \"/service/url\" in 'dangling' points to \"/endpoints/missing\", which is not in the options of any feature."
+        ),
+        other => panic!("Expected a dangling reference error, got: {:?}", other.err()),
+    }
+
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_options(options)?;
+    builder.add_feature(
+        "cycle",
+        serde_json::json!({"options": {"service": {
+            "a": {"$type": "Optify.Reference", "pointer": "/service/b"},
+            "b": {"$type": "Optify.Reference", "pointer": "/service/a"},
+        }}}),
+    )?;
+    match builder.build() {
+        Err(OptifyError::ReferenceCycle { pointers }) => {
+            assert_eq!(pointers, vec!["/service/a", "/service/b", "/service/a"])
+        }
+        other => panic!("Expected a reference cycle error, got: {:?}", other.err()),
+    }
+
+    Ok(())
+}
+
+struct DurationType;
+
+impl ConfigurableValueType for DurationType {
+    fn type_name(&self) -> &str {
+        "Test.Duration"
+    }
+
+    fn validate(&self, value: &serde_json::Value) -> Result<(), String> {
+        match value.get("seconds") {
+            Some(seconds) if seconds.is_u64() => Ok(()),
+            _ => Err("\"seconds\" must be a non-negative integer.".to_owned()),
+        }
+    }
+
+    fn build(
+        &self,
+        value: &serde_json::Value,
+        context: &ConfigurableValueContext,
+    ) -> Result<serde_json::Value, String> {
+        let seconds = value["seconds"].as_u64().ok_or("Missing seconds.")?;
+        if context.feature_names.iter().any(|name| name == "fast") {
+            return Ok(serde_json::json!(seconds * 100));
+        }
+        Ok(serde_json::json!(seconds * 1000))
+    }
+}
+
+#[test]
+fn test_provider_configurable_value_types() -> Result<(), Box<dyn std::error::Error>> {
+    let options = BuilderOptions {
+        are_configurable_strings_enabled: true,
+        ..BuilderOptions::default()
+    };
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_configurable_value_type(Arc::new(DurationType))?;
+    builder.with_options(options.clone())?;
+    builder.add_feature(
+        "base",
+        serde_json::json!({"options": {"client": {
+            "timeout": {"$type": "Test.Duration", "seconds": 5},
+            "retries": 3,
+        }}}),
+    )?;
+    builder.add_feature(
+        "slow",
+        serde_json::json!({"imports": ["base"], "options": {"client": {"timeout": {"seconds": 60}}}}),
+    )?;
+    builder.add_feature("fast", serde_json::json!({"options": {}}))?;
+    let provider = builder.build()?;
+
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_values_enabled = true;
+    assert_eq!(
+        provider.get_options_with_preferences("client", &["base"], None, Some(&preferences))?,
+        serde_json::json!({"timeout": 5000, "retries": 3})
+    );
+    assert_eq!(
+        provider.get_options_with_preferences(
+            "/client/timeout",
+            &["slow"],
+            None,
+            Some(&preferences)
+        )?,
+        serde_json::json!(60000)
+    );
+    assert_eq!(
+        provider.get_all_options(&["base", "fast"], None, Some(&preferences))?,
+        serde_json::json!({"client": {"timeout": 500, "retries": 3}})
+    );
+    // Values are only built when configurable values are enabled.
+    assert_eq!(
+        provider.get_options("/client/timeout", &["base"])?,
+        serde_json::json!({"$type": "Test.Duration", "seconds": 5})
+    );
+
+    // Types are not saved in snapshots, so they are given when loading.
+    let bytes = provider.to_snapshot_bytes()?;
+    match OptionsProvider::from_snapshot_bytes(&bytes) {
+        Err(OptifyError::Snapshot { message }) => {
+            assert!(message.contains("Test.Duration"), "{message}")
+        }
+        other => panic!("Expected a snapshot error, got: {:?}", other.err()),
+    }
+    let mut configurable_value_types = optify::configurable_values::ConfigurableValueTypes::new();
+    configurable_value_types.insert("Test.Duration".to_owned(), Arc::new(DurationType));
+    let loaded = OptionsProvider::from_snapshot_bytes_with_options(
+        &bytes,
+        BuilderOptions {
+            configurable_value_types,
+            ..BuilderOptions::default()
+        },
+    )?;
+    assert_eq!(
+        loaded.get_all_options(&["base", "fast"], None, Some(&preferences))?,
+        serde_json::json!({"client": {"timeout": 500, "retries": 3}})
+    );
+
+    // Values are validated after imports are merged in.
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_configurable_value_type(Arc::new(DurationType))?;
+    builder.with_options(options)?;
+    builder.add_feature(
+        "invalid",
+        serde_json::json!({"options": {"client": {
+            "timeout": {"$type": "Test.Duration", "seconds": -1},
+        }}}),
+    )?;
+    match builder.build() {
+        Err(e @ OptifyError::InvalidConfigurableValue { .. }) => assert_eq!(
+            e.to_string(),
+            "The configurable value at \"/client/timeout\" in 'invalid' is not valid: \"seconds\" must be a non-negative integer."
+        ),
+        other => panic!(
+            "Expected an invalid configurable value error, got: {:?}",
+            other.err()
+        ),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_provider_template_scopes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_options(BuilderOptions {
+        are_configurable_strings_enabled: true,
+        ..BuilderOptions::default()
+    })?;
+    builder.add_feature(
+        "base",
+        serde_json::json!({"options": {
+            "service": {"region": "us-east", "port": 8080},
+            "greeting": {
+                "$type": "Optify.ConfigurableString",
+                "base": {"liquid": "{{ salutation }} from {{ options.service.region }}:{{ options.service.port }} in {{ constraints.locale }}"},
+                "arguments": {"salutation": "Hello"},
+            },
+        }}),
+    )?;
+    builder.add_feature(
+        "west",
+        serde_json::json!({"options": {"service": {"region": "us-west"}}}),
+    )?;
+    builder.add_feature(
+        "shadowed",
+        serde_json::json!({"options": {"greeting": {"arguments": {"options": "hidden"}, "base": {"liquid": "{{ options }}"}}}}),
+    )?;
+    let provider = builder.build()?;
+
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_values_enabled = true;
+    preferences.set_constraints_json(Some(r#"{"locale": "en"}"#));
+    assert_eq!(
+        provider.get_options_with_preferences("greeting", &["base"], None, Some(&preferences))?,
+        serde_json::json!("Hello from us-east:8080 in en")
+    );
+
+    // Options are read from the merged options for all of the features.
+    preferences.set_constraints_json(Some(r#"{"locale": "fr"}"#));
+    assert_eq!(
+        provider.get_options_with_preferences(
+            "greeting",
+            &["base", "west"],
+            None,
+            Some(&preferences)
+        )?,
+        serde_json::json!("Hello from us-west:8080 in fr")
+    );
+    assert_eq!(
+        provider.get_all_options(&["base", "west"], None, Some(&preferences))?["greeting"],
+        serde_json::json!("Hello from us-west:8080 in fr")
+    );
+
+    // Overrides are included in the options that templates read.
+    preferences.overrides = Some(serde_json::json!({"service": {"port": 443}}));
+    assert_eq!(
+        provider.get_options_with_preferences("/greeting", &["base"], None, Some(&preferences))?,
+        serde_json::json!("Hello from us-east:443 in fr")
+    );
+    preferences.overrides = None;
+
+    // Constraints that are not given cannot be read.
+    preferences.constraints = None;
+    let error = provider
+        .get_options_with_preferences("greeting", &["base"], None, Some(&preferences))
+        .unwrap_err();
+    assert!(
+        matches!(&error, OptifyError::ConfigurableValue { message, .. } if message.contains("requested index=locale")),
+        "{error}"
+    );
+
+    // An argument with the same name as a scope hides the scope.
+    assert_eq!(
+        provider.get_options_with_preferences(
+            "greeting",
+            &["base", "shadowed"],
+            None,
+            Some(&preferences)
+        )?,
+        serde_json::json!("hidden")
+    );
+
+    Ok(())
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "shout",
+    description = "Makes a string uppercase and adds an exclamation mark.",
+    parsed(ShoutFilter)
+)]
+struct Shout;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "shout"]
+struct ShoutFilter;
+
+impl Filter for ShoutFilter {
+    fn evaluate(
+        &self,
+        input: &dyn ValueView,
+        _runtime: &dyn Runtime,
+    ) -> liquid_core::Result<Value> {
+        Ok(Value::scalar(format!(
+            "{}!",
+            input.to_kstr().to_uppercase()
+        )))
+    }
+}
+
+#[derive(Clone)]
+struct AppVersionTag;
+
+impl TagReflection for AppVersionTag {
+    fn tag(&self) -> &str {
+        "app_version"
+    }
+
+    fn description(&self) -> &str {
+        "Writes the version of the app."
+    }
+}
+
+impl ParseTag for AppVersionTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter<'_>,
+        _options: &Language,
+    ) -> liquid_core::Result<Box<dyn Renderable>> {
+        arguments.expect_nothing()?;
+        Ok(Box::new(AppVersion))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[derive(Debug)]
+struct AppVersion;
+
+impl Renderable for AppVersion {
+    fn render_to(
+        &self,
+        writer: &mut dyn std::io::Write,
+        _runtime: &dyn Runtime,
+    ) -> liquid_core::Result<()> {
+        write!(writer, "v2.1").map_err(|e| liquid_core::Error::with_msg(e.to_string()))
+    }
+}
+
+#[test]
+fn test_provider_liquid_filters_and_tags() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let directory = temp_dir.path();
+    fs::write(
+        directory.join("footer.liquid"),
+        "{{ name | shout }} {% app_version %}",
+    )?;
+    fs::write(
+        directory.join("feature.json"),
+        r#"{"options": {"messages": {
+            "inline": {
+                "$type": "Optify.ConfigurableString",
+                "base": {"liquid": "{{ greeting }} ({% app_version %})"},
+                "arguments": {"greeting": {"liquid": "{{ name | shout }}"}, "name": "hello"}
+            },
+            "file": {
+                "$type": "Optify.ConfigurableString",
+                "base": {"file": "footer.liquid"},
+                "arguments": {"name": "bye"}
+            }
+        }}}"#,
+    )?;
+    let options = BuilderOptions {
+        are_configurable_values_enabled: true,
+        ..BuilderOptions::default()
+    };
+
+    // Templates with filters and tags that are not registered cannot be parsed.
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_options(options.clone())?;
+    builder.add_directory(directory)?;
+    assert!(matches!(
+        builder.build(),
+        Err(OptifyError::LiquidTemplate { .. })
+    ));
+
+    // Registrations are kept when the options are set later.
+    let mut builder = OptionsProviderBuilder::new();
+    builder.with_liquid_filter(Box::new(Shout))?;
+    builder.with_liquid_tag(Box::new(AppVersionTag))?;
+    builder.with_options(options)?;
+    builder.add_directory(directory)?;
+    let provider = builder.build()?;
+
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_values_enabled = true;
+    let expected = serde_json::json!({"inline": "HELLO! (v2.1)", "file": "BYE! v2.1"});
+    assert_eq!(
+        provider.get_options_with_preferences(
+            "messages",
+            &["feature"],
+            None,
+            Some(&preferences)
+        )?,
+        expected
+    );
+
+    // Templates in overrides are parsed with the same filters and tags.
+    preferences.overrides = Some(serde_json::json!({"messages": {"inline": {
+        "base": {"liquid": "{{ name | shout }} {% app_version %}"},
+    }}}));
+    assert_eq!(
+        provider.get_options_with_preferences(
+            "/messages/inline",
+            &["feature"],
+            None,
+            Some(&preferences)
+        )?,
+        serde_json::json!("HELLO! v2.1")
+    );
+    preferences.overrides = None;
+
+    // Filters and tags are not saved in snapshots, so they are given when loading.
+    let bytes = provider.to_snapshot_bytes()?;
+    assert!(matches!(
+        OptionsProvider::from_snapshot_bytes(&bytes),
+        Err(OptifyError::LiquidTemplate { .. })
+    ));
+    let mut filters = optify::configurable_string::LiquidFilters::new();
+    filters.insert("shout".to_owned(), Box::new(Shout));
+    let mut tags = optify::configurable_string::LiquidTags::new();
+    tags.insert("app_version".to_owned(), Box::new(AppVersionTag));
+    let loaded = OptionsProvider::from_snapshot_bytes_with_options(
+        &bytes,
+        BuilderOptions {
+            liquid_filters: filters,
+            liquid_tags: tags,
+            ..BuilderOptions::default()
+        },
+    )?;
+    assert_eq!(
+        loaded.get_options_with_preferences("messages", &["feature"], None, Some(&preferences))?,
+        expected
+    );
+
+    Ok(())
+}
@@ -1,13 +1,14 @@
 use optify::{
     builder::{OptionsRegistryBuilder, OptionsWatcherBuilder},
     provider::{OptionsProvider, OptionsRegistry, OptionsWatcher},
+    OptifyError,
 };
 
 const CONFIGURABLE_VALUES_CONFIGS_DIR: &str = "../../tests/test_suites/configurable_values/configs";
 const CONFIGURABLE_VALUES_SCHEMA_PATH: &str =
     "../../tests/test_suites/configurable_values/configs/.optify/schema.json";
 
-fn build_configurable_values_config(config: &str) -> Result<OptionsProvider, String> {
+fn build_configurable_values_config(config: &str) -> Result<OptionsProvider, OptifyError> {
     use std::fs;
     use tempfile::TempDir;
 
@@ -19,7 +20,7 @@ fn build_configurable_values_config(config: &str) -> Result<OptionsProvider, Str
 }
 
 #[test]
-fn test_configurable_list_item_type_is_validated() -> Result<(), String> {
+fn test_configurable_list_item_type_is_validated() -> Result<(), Box<dyn std::error::Error>> {
     let invalid_configs = [
         (
             "direct array",
@@ -104,18 +105,19 @@ fn test_configurable_list_item_type_is_validated() -> Result<(), String> {
 
     for (case_name, invalid_config) in invalid_configs {
         let result = build_configurable_values_config(invalid_config);
-        let error_message = match result {
+        let error = match result {
             Ok(_) => {
                 return Err(format!(
                     "Expected {case_name} ConfigurableList item schema validation to fail"
-                ));
+                )
+                .into());
             }
             Err(error) => error,
         };
 
         assert!(
-            error_message.contains("Schema validation failed"),
-            "Expected {case_name} schema validation error, got: {error_message}"
+            matches!(error, OptifyError::SchemaValidation { .. }),
+            "Expected {case_name} schema validation error, got: {error}"
         );
     }
 
@@ -123,7 +125,7 @@ fn test_configurable_list_item_type_is_validated() -> Result<(), String> {
 }
 
 #[test]
-fn test_configurable_list_partial_items_are_valid() -> Result<(), String> {
+fn test_configurable_list_partial_items_are_valid() -> Result<(), Box<dyn std::error::Error>> {
     let partial_configs = [
         r#"{
             "options": {
@@ -181,16 +183,16 @@ fn test_configurable_list_partial_items_are_valid() -> Result<(), String> {
 }
 
 #[test]
-fn test_configurable_values_configs_adhere_to_schema() -> Result<(), String> {
+fn test_configurable_values_configs_adhere_to_schema() -> Result<(), Box<dyn std::error::Error>> {
     OptionsProvider::build_with_schema(
         CONFIGURABLE_VALUES_CONFIGS_DIR,
         CONFIGURABLE_VALUES_SCHEMA_PATH,
-    )
-    .map(|_| ())
+    )?;
+    Ok(())
 }
 
 #[test]
-fn test_simple_configs_adhere_to_schema() -> Result<(), String> {
+fn test_simple_configs_adhere_to_schema() -> Result<(), Box<dyn std::error::Error>> {
     let configs_dir = "../../tests/test_suites/simple/configs";
     let schema_path = "../../schemas/feature_file.json";
     let result = OptionsWatcher::build_with_schema(configs_dir, schema_path);
@@ -205,7 +207,7 @@ fn test_simple_configs_adhere_to_schema() -> Result<(), String> {
 }
 
 #[test]
-fn test_schema_with_urns() -> Result<(), String> {
+fn test_schema_with_urns() -> Result<(), Box<dyn std::error::Error>> {
     let configs_dir = "../../tests/test_suites/inheritance/configs";
     let schema_path = "../../tests/test_suites/inheritance/configs/.optify/schema.json";
     let result = OptionsProvider::build_with_schema(configs_dir, schema_path);
@@ -220,7 +222,7 @@ fn test_schema_with_urns() -> Result<(), String> {
 }
 
 #[test]
-fn test_invalid_file_fails_schema_validation() -> Result<(), String> {
+fn test_invalid_file_fails_schema_validation() -> Result<(), Box<dyn std::error::Error>> {
     use std::fs;
     use tempfile::TempDir;
 
@@ -248,9 +250,14 @@ fn test_invalid_file_fails_schema_validation() -> Result<(), String> {
         "Expected schema validation to fail for invalid file"
     );
 
-    let error_message = result.err().unwrap();
+    let error = result.err().unwrap();
+    assert!(
+        matches!(error, OptifyError::SchemaValidation { .. }),
+        "Expected a schema validation error, got: {error:?}"
+    );
+    let error_message = error.to_string();
     assert!(
-        error_message.contains("Failed to build provider: Schema validation failed for \""),
+        error_message.starts_with("Schema validation failed for \""),
         "Expected error message to mention schema validation, got: {error_message}"
     );
     assert!(
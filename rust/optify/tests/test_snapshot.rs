@@ -0,0 +1,90 @@
+use optify::provider::{GetOptionsPreferences, OptionsProvider, OptionsRegistry};
+use optify::OptifyError;
+use serde_json::json;
+
+#[test]
+fn test_save_and_load_snapshot() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = OptionsProvider::build("../../tests/test_suites/inheritance/configs")?;
+    let temp_dir = tempfile::tempdir()?;
+    let snapshot_path = temp_dir.path().join("options.snapshot");
+    provider.save_snapshot(&snapshot_path)?;
+
+    let loaded = OptionsProvider::load_snapshot(&snapshot_path)?;
+    let mut features = loaded.get_features();
+    features.sort();
+    let mut expected_features = provider.get_features();
+    expected_features.sort();
+    assert_eq!(features, expected_features);
+    assert_eq!(
+        loaded.get_canonical_feature_name("SUPER")?,
+        provider.get_canonical_feature_name("SUPER")?
+    );
+    assert_eq!(
+        loaded.get_all_options(&["grandparent", "feature_C"], None, None)?,
+        provider.get_all_options(&["grandparent", "feature_C"], None, None)?
+    );
+    assert_eq!(
+        loaded.get_feature_metadata("super").unwrap().owners,
+        Some("su".to_owned())
+    );
+    assert_eq!(
+        loaded.explain_options("myConfig", &["grandparent"], None)?,
+        provider.explain_options("myConfig", &["grandparent"], None)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_keeps_conditions() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = OptionsProvider::build("../../tests/test_suites/conditions/configs")?;
+    let loaded = OptionsProvider::from_snapshot_bytes(&provider.to_snapshot_bytes()?)?;
+
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.set_constraints(Some(json!({"info": 3, "status": "new"})));
+    assert!(loaded.has_conditions("A"));
+    assert_eq!(
+        loaded.get_filtered_feature_names(&["A", "B", "C"], Some(&preferences))?,
+        vec!["A", "B"]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_snapshots() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = OptionsProvider::build("../../tests/test_suites/simple/configs")?;
+    let bytes = provider.to_snapshot_bytes()?;
+
+    let is_snapshot_error = |bytes: &[u8]| {
+        matches!(
+            OptionsProvider::from_snapshot_bytes(bytes),
+            Err(OptifyError::Snapshot { .. })
+        )
+    };
+
+    assert!(is_snapshot_error(b"not a snapshot"));
+    assert!(is_snapshot_error(&bytes[..bytes.len() - 1]));
+
+    let mut corrupt = bytes.clone();
+    let last = corrupt.len() - 2;
+    corrupt[last] ^= 1;
+    assert!(is_snapshot_error(&corrupt));
+
+    let mut other_version = bytes.clone();
+    other_version[8] = other_version[8].wrapping_add(1);
+    match OptionsProvider::from_snapshot_bytes(&other_version) {
+        Err(OptifyError::Snapshot { message }) => {
+            assert!(message.contains("format version"), "{message}")
+        }
+        other => panic!("Expected a snapshot error, got: {:?}", other.err()),
+    }
+
+    let result = OptionsProvider::load_snapshot("does_not_exist.snapshot");
+    assert!(matches!(result, Err(OptifyError::Io { .. })));
+
+    let result = provider.save_snapshot("does_not_exist/options.snapshot");
+    assert!(matches!(result, Err(OptifyError::Io { .. })));
+
+    Ok(())
+}
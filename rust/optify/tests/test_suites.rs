@@ -5,7 +5,20 @@ use optify::OptionsProvider;
 
 fn test_suite(path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
     let provider = OptionsProvider::build(path.join("configs"))?;
+    // A provider loaded from a snapshot must behave the same as the provider that was built.
+    let provider_from_snapshot =
+        OptionsProvider::from_snapshot_bytes(&provider.to_snapshot_bytes()?)?;
+    for provider in [provider, provider_from_snapshot] {
+        check_expectations(path, &provider)?;
+    }
+
+    Ok(())
+}
 
+fn check_expectations(
+    path: &std::path::Path,
+    provider: &OptionsProvider,
+) -> Result<(), Box<dyn std::error::Error>> {
     let expectations = fs::read_dir(path.join("expectations"))?;
     for expectation_entry in expectations {
         let expectation_path = expectation_entry?.path();
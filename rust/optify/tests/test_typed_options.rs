@@ -0,0 +1,196 @@
+use std::sync::Arc;
+
+use optify::provider::{CacheOptions, GetOptionsPreferences, OptionsProvider, OptionsRegistry};
+use optify::OptifyError;
+use serde::Deserialize;
+
+fn get_provider() -> OptionsProvider {
+    OptionsProvider::build("../../tests/test_suites/simple/configs").unwrap()
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct MyObject {
+    one: Option<i32>,
+    two: i32,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct MyConfig {
+    root_string: String,
+    my_array: Vec<String>,
+    my_object: MyObject,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WrongDeeper {
+    #[allow(dead_code)]
+    list: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WrongObject {
+    #[allow(dead_code)]
+    deeper: WrongDeeper,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WrongConfig {
+    #[allow(dead_code)]
+    my_object: WrongObject,
+}
+
+#[test]
+fn test_get_options_as() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = get_provider();
+    let config: MyConfig = provider.get_options_as("myConfig", &["a", "b"], None, None)?;
+    assert_eq!(
+        config,
+        MyConfig {
+            root_string: "root string same".to_owned(),
+            my_array: vec!["different item 1".to_owned(), "item 2".to_owned()],
+            my_object: MyObject {
+                one: Some(1),
+                two: 22,
+            },
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_get_options_as_error_has_path_and_feature_file() {
+    let provider = get_provider();
+    let error = provider
+        .get_options_as::<WrongConfig>("myConfig", &["a", "b"], None, None)
+        .unwrap_err();
+
+    match &error {
+        OptifyError::OptionsDeserialize {
+            key,
+            features,
+            pointer,
+            feature_file,
+            message,
+        } => {
+            assert_eq!(key, "myConfig");
+            assert_eq!(features, &vec!["a".to_owned(), "b".to_owned()]);
+            assert_eq!(pointer, "/myObject/deeper/list/0");
+            let feature_file = feature_file.as_ref().expect("feature file to be found");
+            assert!(
+                feature_file.ends_with("initial.yaml"),
+                "Expected the value to come from feature_B/initial.yaml, got: {feature_file}"
+            );
+            assert!(message.contains("expected a string"), "{message}");
+        }
+        _ => panic!("Expected an OptionsDeserialize error, got: {error:?}"),
+    }
+}
+
+#[test]
+fn test_get_options_as_error_from_import_has_imported_feature_file(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let directory = temp_dir.path();
+    std::fs::write(
+        directory.join("base.json"),
+        r#"{"options": {"myConfig": {"myObject": {"deeper": {"list": [1]}}}}}"#,
+    )?;
+    std::fs::write(
+        directory.join("child.json"),
+        r#"{"imports": ["base"], "options": {"myConfig": {"myObject": {"other": true}}}}"#,
+    )?;
+    let provider = OptionsProvider::build(directory)?;
+    let error = provider
+        .get_options_as::<WrongConfig>("myConfig", &["child"], None, None)
+        .unwrap_err();
+
+    match &error {
+        OptifyError::OptionsDeserialize {
+            pointer,
+            feature_file,
+            ..
+        } => {
+            assert_eq!(pointer, "/myObject/deeper/list/0");
+            let feature_file = feature_file.as_ref().expect("feature file to be found");
+            assert!(
+                feature_file.ends_with("base.json"),
+                "Expected the value to come from the imported base.json, got: {feature_file}"
+            );
+        }
+        _ => panic!("Expected an OptionsDeserialize error, got: {error:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_get_options_as_error_from_overrides_has_no_feature_file() {
+    let provider = get_provider();
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.overrides = Some(serde_json::json!({
+        "myConfig": {
+            "myObject": {
+                "deeper": {
+                    "list": [true]
+                }
+            }
+        }
+    }));
+    let error = provider
+        .get_options_as::<WrongConfig>("myConfig", &["a"], None, Some(&preferences))
+        .unwrap_err();
+
+    match error {
+        OptifyError::OptionsDeserialize {
+            pointer,
+            feature_file,
+            ..
+        } => {
+            assert_eq!(pointer, "/myObject/deeper/list/0");
+            assert_eq!(feature_file, None);
+        }
+        _ => panic!("Expected an OptionsDeserialize error, got: {error:?}"),
+    }
+}
+
+#[test]
+fn test_get_options_as_arc_caches_per_type() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = get_provider();
+    let cache_options = CacheOptions::default();
+
+    let config1: Arc<MyConfig> =
+        provider.get_options_as_arc("myConfig", &["a"], Some(&cache_options), None)?;
+    let config2: Arc<MyConfig> =
+        provider.get_options_as_arc("myConfig", &["A"], Some(&cache_options), None)?;
+    assert!(Arc::ptr_eq(&config1, &config2));
+
+    let object: Arc<serde_json::Value> =
+        provider.get_options_as_arc("myConfig", &["a"], Some(&cache_options), None)?;
+    assert_eq!(object["rootString"], "root string same");
+
+    provider.clear_cache();
+    let config3: Arc<MyConfig> =
+        provider.get_options_as_arc("myConfig", &["a"], Some(&cache_options), None)?;
+    assert!(!Arc::ptr_eq(&config1, &config3));
+    assert_eq!(config1, config3);
+
+    Ok(())
+}
+
+#[test]
+fn test_get_options_as_arc_without_cache() -> Result<(), Box<dyn std::error::Error>> {
+    let provider = get_provider();
+
+    let config1: Arc<MyConfig> = provider.get_options_as_arc("myConfig", &["a"], None, None)?;
+    let config2: Arc<MyConfig> = provider.get_options_as_arc("myConfig", &["a"], None, None)?;
+    assert!(!Arc::ptr_eq(&config1, &config2));
+    assert_eq!(config1, config2);
+
+    Ok(())
+}
@@ -1,5 +1,9 @@
-use optify::builder::{OptionsRegistryBuilder, OptionsWatcherBuilder};
-use optify::provider::{OptionsRegistry, OptionsWatcher, WatcherOptions};
+use liquid_core::{
+    Display_filter, Filter, FilterReflection, ParseFilter, Runtime, Value, ValueView,
+};
+use optify::builder::{BuilderOptions, OptionsRegistryBuilder, OptionsWatcherBuilder};
+use optify::provider::{GetOptionsPreferences, OptionsRegistry, OptionsWatcher, WatcherOptions};
+use optify::OptifyError;
 use std::fs::File;
 use std::io::Write;
 use std::thread;
@@ -121,7 +125,9 @@ fn test_watchable_builder_multiple_directories() -> Result<(), Box<dyn std::erro
     );
     assert_eq!(
         options1.err().unwrap(),
-        "Feature name \"test1\" is not a known feature."
+        OptifyError::UnknownFeature {
+            name: "test1".to_owned()
+        }
     );
 
     Ok(())
@@ -172,3 +178,90 @@ fn test_watchable_builder_error_rebuilding_provider() -> Result<(), Box<dyn std:
 
     Ok(())
 }
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "shout",
+    description = "Makes a string uppercase and adds an exclamation mark.",
+    parsed(ShoutFilter)
+)]
+struct Shout;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "shout"]
+struct ShoutFilter;
+
+impl Filter for ShoutFilter {
+    fn evaluate(
+        &self,
+        input: &dyn ValueView,
+        _runtime: &dyn Runtime,
+    ) -> liquid_core::Result<Value> {
+        Ok(Value::scalar(format!(
+            "{}!",
+            input.to_kstr().to_uppercase()
+        )))
+    }
+}
+
+#[test]
+fn test_watchable_builder_liquid_filters() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let test_dir = temp_dir.path();
+    let options_file = test_dir.join("greeting.json");
+    let write_options = |name: &str| {
+        std::fs::write(
+            &options_file,
+            format!(
+                r#"{{"options": {{"greeting": {{
+                    "$type": "Optify.ConfigurableString",
+                    "base": {{"liquid": "{{{{ name | shout }}}}"}},
+                    "arguments": {{"name": "{name}"}}
+                }}}}}}"#
+            ),
+        )
+    };
+    write_options("hello")?;
+
+    let mut builder = OptionsWatcherBuilder::new();
+    builder.with_liquid_filter(Box::new(Shout))?;
+    builder.with_options(BuilderOptions {
+        are_configurable_values_enabled: true,
+        ..BuilderOptions::default()
+    })?;
+    builder.add_directory(test_dir)?;
+    builder.with_watcher_options(WatcherOptions::new(DEFAULT_DEBOUNCE_DURATION));
+    let provider = builder.build()?;
+    let created_at = provider.last_modified();
+
+    let mut preferences = GetOptionsPreferences::new();
+    preferences.are_configurable_values_enabled = true;
+    let options = provider.get_options_with_preferences(
+        "greeting",
+        &["greeting"],
+        None,
+        Some(&preferences),
+    )?;
+    assert_eq!(options, serde_json::json!("HELLO!"));
+
+    // The filters are still used when the provider is rebuilt.
+    write_options("bye")?;
+    let start_time = std::time::Instant::now();
+    let max_sleep_time = 3000;
+    while provider.last_modified() == created_at {
+        thread::sleep(Duration::from_millis(SLEEP_TIME));
+        if start_time.elapsed().as_millis() > max_sleep_time {
+            panic!("Provider did not update after {max_sleep_time}ms.");
+        }
+    }
+
+    let options = provider.get_options_with_preferences(
+        "greeting",
+        &["greeting"],
+        None,
+        Some(&preferences),
+    )?;
+    assert_eq!(options, serde_json::json!("BYE!"));
+
+    Ok(())
+}